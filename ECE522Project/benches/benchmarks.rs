@@ -1,9 +1,8 @@
 use tree_collections::prelude::*;
 
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::seq::{IteratorRandom, SliceRandom};
 use rand::{rngs::StdRng, SeedableRng};
-use rand::seq::{SliceRandom, IteratorRandom};
-use criterion::{BenchmarkId, criterion_group, criterion_main, Criterion};
-
 
 const N: usize = 64;
 pub struct MyRngSeed(pub [u8; N]);
@@ -11,17 +10,10 @@ pub struct MyRng(MyRngSeed);
 
 const TREE_SIZE: [u32; 5] = [10_000, 40_000, 70_000, 100_000, 130_000];
 
-fn benchmark_avl_insert(tree_size: u32) {
-    let mut avl = AVLTree::new();
-    for v in 0..tree_size {
-        avl.insert(v);
-    }
-}
-
-fn benchmark_rbt_insert(tree_size: u32) {
-    let mut rbt = FastRBTree::new();
+fn benchmark_insert<S: OrderedSet<u32>>(tree_size: u32) {
+    let mut tree = S::new();
     for v in 0..tree_size {
-        rbt.insert(v);
+        tree.insert(v);
     }
 }
 
@@ -32,60 +24,35 @@ fn benchmark_rbt_insert(tree_size: u32) {
 //     }
 // }
 
-
-fn benchmark_avl(tree_size: u32) {
-    let mut avl = AVLTree::new();
+fn benchmark_search<S: OrderedSet<u32>>(tree_size: u32) {
+    let mut tree = S::new();
     for v in 0..tree_size {
-        avl.insert(v);
+        tree.insert(v);
     }
     for v in 0..tree_size / 10 {
-        avl.contains(v);
+        tree.contains(&v);
     }
 }
 
-fn benchmark_rbt(tree_size: u32) {
-    let mut rbt = FastRBTree::new();
-    for v in 0..tree_size {
-        rbt.insert(v);
+fn benchmark_insert_delete<S: OrderedSet<u32>>(tree_size: u32) {
+    let seed = [0u8; 32];
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    let mut data: Vec<u32> = (0..tree_size).collect();
+    data.shuffle(&mut rng);
+    let sample = data
+        .iter()
+        .choose_multiple(&mut rng, (tree_size / 100) as usize);
+
+    let mut tree = S::new();
+    for v in &data {
+        tree.insert(*v);
     }
-    for v in 0..tree_size / 10 {
-        rbt.contains(v);
+
+    for v in sample.iter() {
+        tree.delete(*v);
     }
 }
 
-// fn benchmark_avl_insert_delete(tree_size: u32) {
-//     let seed = [0u8; 32];
-//     let mut rng: StdRng = SeedableRng::from_seed(seed);
-//     let mut data: Vec<u32> = (0..tree_size).collect();
-//     data.shuffle(&mut rng);
-//     let sample = data.iter().choose_multiple(&mut rng, (tree_size / 100) as usize);
-
-//     let mut avl = AVLTree::new();
-//     for v in &data {
-//         avl.insert(*v);
-//     }
-//     for v in sample.iter() {
-//         avl.delete(**v);
-//     }
-// }
-
-// fn benchmark_rbt_insert_delete(tree_size: u32) {
-//     let seed = [0u8; 32];
-//     let mut rng: StdRng = SeedableRng::from_seed(seed);
-//     let mut data: Vec<u32> = (0..tree_size).collect();
-//     data.shuffle(&mut rng);
-//     let sample = data.iter().choose_multiple(&mut rng, (tree_size / 100) as usize);
-
-//     let mut rbt = RBTree::new();
-//     for v in &data {
-//         rbt.insert(*v);
-//     }
-
-//     for v in sample.iter() {
-//         rbt.delete(**v);
-//     }
-// }
-
 // fn benchmark_bst_insert_delete(tree_size: u32) {
 //     let seed = [0u8; 32];
 //     let mut rng: StdRng = SeedableRng::from_seed(seed);
@@ -99,67 +66,90 @@ fn benchmark_rbt(tree_size: u32) {
 //     }
 
 //     for v in sample.iter() {
-//         rbt.delete(**v);
+//         rbt.delete(*v);
 //     }
 // }
 
 fn bench_compare_insert(c: &mut Criterion) {
     let mut group = c.benchmark_group("Insert");
     for (i, size) in TREE_SIZE.iter().enumerate() {
-        group.bench_with_input(
-            BenchmarkId::new("AVL", i), size,
-            |b, n| b.iter(|| benchmark_avl_insert(*n))
-        );
-        group.bench_with_input(
-            BenchmarkId::new("RBT", i), size,
-            |b, n| {
-                b.iter(|| benchmark_rbt_insert(*n));
-            }
-        );
+        group.bench_with_input(BenchmarkId::new("AVL", i), size, |b, n| {
+            b.iter(|| benchmark_insert::<AVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("RBT", i), size, |b, n| {
+            b.iter(|| benchmark_insert::<FastRBTree<u32>>(*n));
+        });
+        group.bench_with_input(BenchmarkId::new("ArenaAVL", i), size, |b, n| {
+            b.iter(|| benchmark_insert::<ArenaAVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("ArenaRBT", i), size, |b, n| {
+            b.iter(|| benchmark_insert::<ArenaRBTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("BoxAVL", i), size, |b, n| {
+            b.iter(|| benchmark_insert::<BoxAVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("BoxRBT", i), size, |b, n| {
+            b.iter(|| benchmark_insert::<BoxRBTree<u32>>(*n))
+        });
     }
     group.finish();
 }
 
 fn bench_compare_search_insert(c: &mut Criterion) {
     let mut group = c.benchmark_group("Search");
-    
+
     for (i, size) in TREE_SIZE.iter().enumerate() {
-        group.bench_with_input(
-            BenchmarkId::new("AVL", i), size,
-            |b, n| b.iter(|| benchmark_avl(*n))
-        );
-        group.bench_with_input(
-            BenchmarkId::new("RBT", i), size,
-            |b, n| {
-                b.iter(|| benchmark_rbt(*n));
-            }
-        );
+        group.bench_with_input(BenchmarkId::new("AVL", i), size, |b, n| {
+            b.iter(|| benchmark_search::<AVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("RBT", i), size, |b, n| {
+            b.iter(|| benchmark_search::<FastRBTree<u32>>(*n));
+        });
+        group.bench_with_input(BenchmarkId::new("ArenaAVL", i), size, |b, n| {
+            b.iter(|| benchmark_search::<ArenaAVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("ArenaRBT", i), size, |b, n| {
+            b.iter(|| benchmark_search::<ArenaRBTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("BoxAVL", i), size, |b, n| {
+            b.iter(|| benchmark_search::<BoxAVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("BoxRBT", i), size, |b, n| {
+            b.iter(|| benchmark_search::<BoxRBTree<u32>>(*n))
+        });
     }
     group.finish();
 }
 
-// fn bench_compare_insert_delete(c: &mut Criterion) {
-//     let mut group = c.benchmark_group("Insert_delete");
-//     for (i, size) in TREE_SIZE.iter().enumerate() {
-//         group.bench_with_input(
-//             BenchmarkId::new("AVL", i), size,
-//             |b, n| b.iter(|| benchmark_avl_insert_delete(*n))
-//         );
-//         group.bench_with_input(
-//             BenchmarkId::new("RBT", i), size,
-//             |b, n| {
-//                 b.iter(|| benchmark_rbt_insert_delete(*n));
-//             }
-//         );
-//     }
-//     group.finish();
-// }
-
+fn bench_compare_insert_delete(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Insert_delete");
+    for (i, size) in TREE_SIZE.iter().enumerate() {
+        group.bench_with_input(BenchmarkId::new("AVL", i), size, |b, n| {
+            b.iter(|| benchmark_insert_delete::<AVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("RBT", i), size, |b, n| {
+            b.iter(|| benchmark_insert_delete::<FastRBTree<u32>>(*n));
+        });
+        group.bench_with_input(BenchmarkId::new("ArenaAVL", i), size, |b, n| {
+            b.iter(|| benchmark_insert_delete::<ArenaAVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("ArenaRBT", i), size, |b, n| {
+            b.iter(|| benchmark_insert_delete::<ArenaRBTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("BoxAVL", i), size, |b, n| {
+            b.iter(|| benchmark_insert_delete::<BoxAVLTree<u32>>(*n))
+        });
+        group.bench_with_input(BenchmarkId::new("BoxRBT", i), size, |b, n| {
+            b.iter(|| benchmark_insert_delete::<BoxRBTree<u32>>(*n))
+        });
+    }
+    group.finish();
+}
 
 criterion_group!(
     benches,
     bench_compare_insert,
     bench_compare_search_insert,
-    // bench_compare_insert_delete
+    bench_compare_insert_delete
 );
-criterion_main!(benches);
\ No newline at end of file
+criterion_main!(benches);
@@ -0,0 +1,191 @@
+//! Arena storage for trees
+//!
+//! Nodes of the arena-backed trees live in one `Vec` and refer to each other by `u32`
+//! indices instead of `Rc<RefCell<...>>`, so a node costs no reference counters or
+//! borrow flags. Removed nodes are put on a free list and their slots are reused.
+
+use std::ops::{Index, IndexMut};
+
+use crate::commonTrait::OwnedNode;
+use crate::nodeHandle::NodeHandle;
+
+/// Index standing for an empty child or an empty tree
+pub const NIL: u32 = u32::MAX;
+
+/// Node stored in an [Arena](struct.Arena.html), `M` is the balancing data of the tree
+#[derive(Clone, Debug)]
+pub struct ArenaNode<T, M> {
+    /// Data stored in the node
+    pub value: T,
+    pub left: u32,
+    pub right: u32,
+    /// Number of nodes in the subtree rooted at this node
+    pub size: u32,
+    /// Height for AVL trees or color for red-black trees
+    pub meta: M,
+}
+
+#[derive(Clone, Debug)]
+enum Slot<T, M> {
+    Occupied(ArenaNode<T, M>),
+    // a free slot, holding the next free slot
+    Vacant(u32),
+}
+
+/// Storage of the nodes of an arena-backed tree
+#[derive(Clone, Debug)]
+pub struct Arena<T, M> {
+    slots: Vec<Slot<T, M>>,
+    // the first free slot
+    free: u32,
+}
+
+impl<T, M> Default for Arena<T, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, M> Index<u32> for Arena<T, M> {
+    type Output = ArenaNode<T, M>;
+
+    fn index(&self, index: u32) -> &ArenaNode<T, M> {
+        match &self.slots[index as usize] {
+            Slot::Occupied(node) => node,
+            Slot::Vacant(_) => panic!("node {} is freed", index),
+        }
+    }
+}
+
+impl<T, M> IndexMut<u32> for Arena<T, M> {
+    fn index_mut(&mut self, index: u32) -> &mut ArenaNode<T, M> {
+        match &mut self.slots[index as usize] {
+            Slot::Occupied(node) => node,
+            Slot::Vacant(_) => panic!("node {} is freed", index),
+        }
+    }
+}
+
+/// Implementations of Arena
+impl<T, M> Arena<T, M> {
+    /// Create an empty arena
+    pub fn new() -> Self {
+        Arena {
+            slots: vec![],
+            free: NIL,
+        }
+    }
+
+    /// Store a new leaf node and return its index, reusing a free slot if there is one
+    pub fn alloc(&mut self, value: T, meta: M) -> u32 {
+        let node = ArenaNode {
+            value,
+            left: NIL,
+            right: NIL,
+            size: 1,
+            meta,
+        };
+        if self.free == NIL {
+            let index = self.slots.len() as u32;
+            assert!(index != NIL, "too many nodes in the arena");
+            self.slots.push(Slot::Occupied(node));
+            index
+        } else {
+            let index = self.free;
+            self.free = match self.slots[index as usize] {
+                Slot::Vacant(next) => next,
+                Slot::Occupied(_) => unreachable!(),
+            };
+            self.slots[index as usize] = Slot::Occupied(node);
+            index
+        }
+    }
+
+    /// Remove the node from the arena and return it, its slot will be reused
+    pub fn free(&mut self, index: u32) -> ArenaNode<T, M> {
+        let slot = std::mem::replace(&mut self.slots[index as usize], Slot::Vacant(self.free));
+        self.free = index;
+        match slot {
+            Slot::Occupied(node) => node,
+            Slot::Vacant(_) => panic!("node {} is freed", index),
+        }
+    }
+
+    /// Number of slots, including the free ones
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Size of the subtree, 0 for `NIL`
+    pub fn size(&self, index: u32) -> u32 {
+        if index == NIL {
+            0
+        } else {
+            self[index].size
+        }
+    }
+
+    /// Get a handle to the node at the index, `None` for `NIL`
+    pub fn handle(&self, index: u32) -> Option<ArenaHandle<'_, T, M>> {
+        if index == NIL {
+            None
+        } else {
+            Some(ArenaHandle { arena: self, index })
+        }
+    }
+
+    /// Recount the subtree size of the node from its children
+    pub fn update_size(&mut self, index: u32) {
+        let size = self.size(self[index].left) + self.size(self[index].right) + 1;
+        self[index].size = size;
+    }
+}
+
+// a node is moved out of the arena by its index
+impl<T, M> OwnedNode<T, Arena<T, M>> for u32 {
+    fn split(self, arena: &mut Arena<T, M>) -> (Option<Self>, T, Option<Self>) {
+        let node = arena.free(self);
+        let child = |index| if index == NIL { None } else { Some(index) };
+        (child(node.left), node.value, child(node.right))
+    }
+}
+
+/// Node of an arena-backed tree, reached through the arena holding it and its index
+pub struct ArenaHandle<'a, T, M> {
+    arena: &'a Arena<T, M>,
+    index: u32,
+}
+
+// copying a handle copies the reference to the arena, not the node
+impl<'a, T, M> Clone for ArenaHandle<'a, T, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, M> Copy for ArenaHandle<'a, T, M> {}
+
+impl<'a, T, M> ArenaHandle<'a, T, M> {
+    /// Get the index of the node in the arena
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl<'a, T, M> NodeHandle<T> for ArenaHandle<'a, T, M> {
+    fn get_left(&self) -> Option<Self> {
+        self.arena.handle(self.arena[self.index].left)
+    }
+
+    fn get_right(&self) -> Option<Self> {
+        self.arena.handle(self.arena[self.index].right)
+    }
+
+    fn get_size(&self) -> usize {
+        self.arena[self.index].size as usize
+    }
+
+    fn with_value<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        f(&self.arena[self.index].value)
+    }
+}
@@ -2,35 +2,53 @@
 //!
 //! You can generate an AVL tree, and insert or delete nodes.
 
-use std::cell::RefCell;
-use std::cmp::{max, Ordering};
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
 use std::fmt;
-use std::io;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
 use std::rc::Rc;
 
-pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait};
+use crate::commonTrait::{
+    clone_nodes, collect_sorted, dedup_sorted, drop_nodes, is_sorted, Comparator, IntoIter, Iter,
+};
+pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait, OrderedSet};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+use crate::treeMap::MapBackend;
 
 type AVLTreeNode<T> = Rc<RefCell<TreeNode<T>>>;
 type OptionAVLTreeNode<T> = Option<AVLTreeNode<T>>;
 
 /// Node struct for AVLTree
 #[derive(Clone, Debug, PartialEq)]
-pub struct TreeNode<T: Ord + Copy + fmt::Debug> {
+pub struct TreeNode<T: Ord> {
     pub value: T,
     left: OptionAVLTreeNode<T>,
     right: OptionAVLTreeNode<T>,
     height: usize,
+    /// Number of nodes in the subtree rooted at this node
+    size: usize,
 }
 
 // extend from common tree trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeTrait<T, TreeNode<T>> for AVLTree<T> {
+impl<T: Ord> CommonTreeTrait<T> for AVLTree<T> {
+    type Handle<'a>
+        = AVLTreeNode<T>
+    where
+        Self: 'a;
+
     fn get_root(&self) -> OptionAVLTreeNode<T> {
         return self.root.clone();
     }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
 }
 
 // extend from common tree node trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
+impl<T: Ord> CommonTreeNodeTrait<T> for TreeNode<T> {
     fn get_left(&self) -> OptionAVLTreeNode<T> {
         return self.left.clone();
     }
@@ -39,13 +57,155 @@ impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
         return self.right.clone();
     }
 
-    fn get_value(&self) -> T {
-        return self.value;
+    fn get_value(&self) -> &T {
+        &self.value
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn take_left(&mut self) -> OptionAVLTreeNode<T> {
+        self.left.take()
+    }
+
+    fn take_right(&mut self) -> OptionAVLTreeNode<T> {
+        self.right.take()
+    }
+
+    fn into_value(self) -> T {
+        self.value
+    }
+}
+
+// free the nodes without recursing through them
+impl<T: Ord> Drop for AVLTree<T> {
+    fn drop(&mut self) {
+        drop_nodes(self.root.take());
+    }
+}
+
+// copy every node, so that changing the copy leaves the original alone
+impl<T: Ord + Clone> Clone for AVLTree<T> {
+    fn clone(&self) -> Self {
+        let root = clone_nodes(&self.root, |node, left, right| {
+            Rc::new(RefCell::new(TreeNode {
+                value: node.value.clone(),
+                left,
+                right,
+                height: node.height,
+                size: node.size,
+            }))
+        });
+        AVLTree {
+            root,
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for AVLTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, AVLTreeNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter::new(self.root.take(), len)
+    }
+}
+
+// iterate over the tree in order
+impl<'a, T: Ord + Clone> IntoIterator for &'a AVLTree<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, AVLTreeNode<T>>;
+
+    fn into_iter(self) -> Iter<'a, T, AVLTreeNode<T>> {
+        self.iter()
+    }
+}
+
+// build the tree from values in ascending order directly, or insert them one by one
+impl<T: Ord> FromIterator<T> for AVLTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = AVLTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+// values in ascending order are built into a tree and appended, joining it in O(log n)
+// time when the two do not overlap, values in any other order are inserted one by one
+impl<T: Ord> Extend<T> for AVLTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let values: Vec<T> = iter.into_iter().collect();
+        if is_sorted(&values, &self.comparator) {
+            let values = dedup_sorted(values, &self.comparator);
+            let mut other = Self::from_sorted_values(values, self.comparator.clone());
+            self.append(&mut other);
+        } else {
+            for value in values {
+                self.insert(value);
+            }
+        }
+    }
+}
+
+// construct and mutate AVLTree in generic code
+impl<T: Ord> OrderedSet<T> for AVLTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        AVLTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        AVLTree {
+            root: None,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        AVLTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        AVLTree::delete(self, value)
+    }
+}
+
+// back a TreeMap with AVLTree
+impl<T: Ord> MapBackend<T> for AVLTree<T> {
+    type Node = TreeNode<T>;
+
+    fn get_root_node(&self) -> OptionAVLTreeNode<T> {
+        self.root.clone()
+    }
+
+    fn borrow_value(node: &Rc<RefCell<TreeNode<T>>>) -> Ref<'_, T> {
+        Ref::map(node.borrow(), |node| &node.value)
+    }
+
+    fn borrow_value_mut(node: &Rc<RefCell<TreeNode<T>>>) -> RefMut<'_, T> {
+        RefMut::map(node.borrow_mut(), |node| &mut node.value)
+    }
+
+    fn replace(&mut self, value: T) -> Option<T> {
+        let root = self.root.take();
+        let (root, replaced) = self.node_insert(root, value);
+        self.root = root;
+        replaced
+    }
+
+    fn remove_node<F: Fn(&T) -> Ordering>(&mut self, cmp: &F) -> OptionAVLTreeNode<T> {
+        let root = self.root.take();
+        let (root, removed) = self.node_delete(root, cmp);
+        self.root = root;
+        removed
     }
 }
 
 /// Implementations of AVLTreeNode
-impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
+impl<T: Ord> TreeNode<T> {
     /// Create a new node of type OptionAVLTreeNode , which will be called by [AVLTree](struct.AVLTree.html)
     fn new(value: T) -> OptionAVLTreeNode<T> {
         Some(Rc::new(RefCell::new(Self {
@@ -53,63 +213,57 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
             left: None,
             right: None,
             height: 1, // default height of a new node is 1，which is a leave
+            size: 1,
         })))
     }
 
-    fn get_data(&self) -> T {
-        return self.value;
+    fn get_data(&self) -> &T {
+        &self.value
     }
 }
 
-pub struct AVLTree<T: Ord + Copy + fmt::Debug> {
+pub struct AVLTree<T: Ord> {
     root: OptionAVLTreeNode<T>,
+    comparator: Comparator<T>,
 }
 
-/// Implementations of AVLTree
-impl<T: Ord + Copy + fmt::Debug> AVLTree<T> {
-    /// Creates a new AVL tree
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use ECE522Project::avlTree::AVLTree; 
-    /// let mut avl_tree: AVLTree<u32> = AVLTree::new();
-    /// ```
-    pub fn new() -> Self {
-        Self { root: None }
+// draw AVLTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for AVLTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        dot::to_dot(
+            self.get_root(),
+            nil_leaves.then(DotStyle::default),
+            |node| {
+                let left_height = self.get_left_height(node) as i64;
+                DotStyle::avl(left_height, self.get_right_height(node) as i64)
+            },
+        )
     }
+}
 
-    pub fn preorder_traverse(&self, node: AVLTreeNode<T>, container: &mut Vec<T>) {
-        container.push(node.borrow().value);
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.preorder_traverse(left.unwrap(), container);
-        }
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.preorder_traverse(right.unwrap(), container);
-        }
+// draw AVLTree as text
+impl<T: Ord + fmt::Debug> Pretty for AVLTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.get_root(), |node| {
+            Some(format!("h={}", node.borrow().height))
+        })
     }
+}
 
-    pub fn in_order_traverse(&self, node: AVLTreeNode<T>, container: &mut Vec<T>) {
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.in_order_traverse(left.unwrap(), container);
-        }
-        container.push(node.borrow().value);
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.in_order_traverse(right.unwrap(), container);
-        }
+impl<T: Ord> Default for AVLTree<T> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-
+/// Implementations of AVLTree
+impl<T: Ord> AVLTree<T> {
     /// Judge if the AVL tree is empty
     ///
     /// # Example
     ///
     /// ```
-    /// use ECE522Project::avlTree::AVLTree; 
+    /// use tree_collections::avlTree::AVLTree;
     /// let mut avl_tree = AVLTree::new();
     /// println!("{}", avl_tree.is_tree_empty());  // true
     /// avl_tree.insert(1);
@@ -119,198 +273,240 @@ impl<T: Ord + Copy + fmt::Debug> AVLTree<T> {
         self.root.clone().map(|_| false).unwrap_or(true)
     }
 
-    pub fn insert(&mut self, insert_value: T) {
-        let root = self.root.take();
-        // TreeNode is type OptionAVLTreeNode, so the code is simplified.
-        match root {
-            None => self.root = TreeNode::new(insert_value),
-            Some(n) => self.root = self.node_insert(Some(n), insert_value),
+    /// Insert a new value to the AVL tree, return false if the value already exists
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTree::AVLTree;
+    /// let mut avl_tree = AVLTree::new();
+    /// assert!(avl_tree.insert(1));
+    /// assert!(!avl_tree.insert(1));
+    /// ```
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        self.replace(insert_value).is_none()
+    }
+
+    /// Delete a value from the AVL tree, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTree::AVLTree;
+    /// let mut avl_tree = AVLTree::new();
+    /// avl_tree.insert(1);
+    /// assert!(avl_tree.delete(&1));
+    /// assert!(!avl_tree.delete(&1));
+    /// ```
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        let comparator = self.comparator.clone();
+        self.remove_node(&|value: &T| comparator.compare(delete_value, value))
+            .is_some()
+    }
+
+    //Determine whether the tree is balanced
+    fn is_balanced(&self, node: OptionAVLTreeNode<T>) -> bool {
+        match node {
+            Some(node) => {
+                if self.get_balance_factor(&node) <= 1.0 {
+                    self.is_balanced(node.borrow().left.clone())
+                        && self.is_balanced(node.borrow().right.clone())
+                } else {
+                    false
+                }
+            }
+            None => true,
+        }
+    }
+}
+
+/// Balancing code of AVLTree, which is shared with [AVLMap](../treeMap/type.AVLMap.html)
+impl<T: Ord> AVLTree<T> {
+    /// Creates a new AVL tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTree::AVLTree;
+    /// let mut avl_tree: AVLTree<u32> = AVLTree::new();
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            comparator: Comparator::default(),
+        }
+    }
+
+    /// Creates a new AVL tree ordered by the comparator instead of `Ord`, which tells how
+    /// the first value compares with the second one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut avl_tree = AVLTree::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// avl_tree.insert(1);
+    /// avl_tree.insert(3);
+    /// avl_tree.insert(2);
+    /// assert_eq!(vec![3, 2, 1], avl_tree.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        Self {
+            root: None,
+            comparator: Comparator::new(cmp),
         }
     }
 
-    /// Inserts a node, return a new root, which will be called by
+    /// Inserts a node, return a new root and the replaced equal value, which will be called by
     /// [AVLTree.insert](struct.AVLTree.html#method.insert)
-    fn node_insert(&mut self, node: OptionAVLTreeNode<T>, insert_value: T) -> OptionAVLTreeNode<T> {
-        let ret_node = match node {
+    fn node_insert(
+        &mut self,
+        node: OptionAVLTreeNode<T>,
+        insert_value: T,
+    ) -> (OptionAVLTreeNode<T>, Option<T>) {
+        let (ret_node, replaced) = match node {
             Some(n) => {
-                let node_value = n.borrow().value;
-                if insert_value < node_value {
-                    let left = n.borrow().left.clone();
-                    n.borrow_mut().left = self.node_insert(left, insert_value);
-                } else if insert_value > node_value {
-                    let right = n.borrow().right.clone();
-                    n.borrow_mut().right = self.node_insert(right, insert_value);
-                } else {
-                    n.borrow_mut().value = insert_value; // equal, update value
+                let ordering = self.comparator.compare(&insert_value, &n.borrow().value);
+                let replaced = match ordering {
+                    Ordering::Less => {
+                        let left = n.borrow_mut().left.take();
+                        let (left, replaced) = self.node_insert(left, insert_value);
+                        n.borrow_mut().left = left;
+                        replaced
+                    }
+                    Ordering::Greater => {
+                        let right = n.borrow_mut().right.take();
+                        let (right, replaced) = self.node_insert(right, insert_value);
+                        n.borrow_mut().right = right;
+                        replaced
+                    }
+                    // equal, update value
+                    Ordering::Equal => {
+                        Some(std::mem::replace(&mut n.borrow_mut().value, insert_value))
+                    }
+                };
+                (n, replaced)
+            }
+            None => (TreeNode::new(insert_value).unwrap(), None),
+        };
+        (Some(self.rebalance(ret_node)), replaced)
+    }
+
+    /// Deletes a node, return a new root and the removed node, which will be called by
+    /// [AVLTree.delete](struct.AVLTree.html#method.delete).
+    /// `cmp` tells how the value to delete compares with the given value.
+    fn node_delete<F: Fn(&T) -> Ordering>(
+        &mut self,
+        node: OptionAVLTreeNode<T>,
+        cmp: &F,
+    ) -> (OptionAVLTreeNode<T>, OptionAVLTreeNode<T>) {
+        let n = match node {
+            None => return (None, None), // reach the leaves, the value is not found
+            Some(n) => n,
+        };
+        let ordering = cmp(&n.borrow().value);
+        let (ret_node, removed) = match ordering {
+            // look left
+            Ordering::Less => {
+                let left = n.borrow_mut().left.take();
+                let (left, removed) = self.node_delete(left, cmp);
+                n.borrow_mut().left = left;
+                (Some(n), removed)
+            }
+            // look right
+            Ordering::Greater => {
+                let right = n.borrow_mut().right.take();
+                let (right, removed) = self.node_delete(right, cmp);
+                n.borrow_mut().right = right;
+                (Some(n), removed)
+            }
+            // found the node which should be deleted
+            Ordering::Equal => {
+                let left = n.borrow_mut().left.take();
+                let right = n.borrow_mut().right.take();
+                match (left, right) {
+                    (None, None) => (None, Some(n)),
+                    (None, Some(r)) => (Some(r), Some(n)), // The left subtree of the node to be deleted is empty, r is new root
+                    (Some(l), None) => (Some(l), Some(n)), // The right subtree of the node to be deleted is empty, l is new root
+
+                    // The left and right subtrees of the node to be deleted(node n) are not empty.
+                    // Find the smallest node A that is larger than the node n, and swap their values.
+                    // Then the value to delete is the smallest one in the right subtree, delete it there.
+                    (Some(l), Some(r)) => {
+                        {
+                            let min_node = Self::get_min_node(r.clone());
+                            std::mem::swap(
+                                &mut n.borrow_mut().value,
+                                &mut min_node.borrow_mut().value,
+                            );
+                        }
+                        let (r, removed) = self.node_delete(Some(r), cmp);
+                        n.borrow_mut().left = Some(l);
+                        n.borrow_mut().right = r;
+                        (Some(n), removed)
+                    }
                 }
-                n
             }
-            None => TreeNode::new(insert_value).unwrap(),
         };
 
-        // update height
-        ret_node.borrow_mut().height = self
-            .get_left_height(&ret_node)
-            .max(self.get_right_height(&ret_node))
-            + 1;
+        // update and maintain
+        (ret_node.map(|n| self.rebalance(n)), removed)
+    }
+
+    // find the node with the min value in the subtree
+    fn get_min_node(node: AVLTreeNode<T>) -> AVLTreeNode<T> {
+        let left = node.borrow().left.clone();
+        match left {
+            Some(left) => Self::get_min_node(left),
+            None => node,
+        }
+    }
+
+    /// Updates the height and rotates the node if it is unbalanced, return the new root
+    /// of the subtree, which will be called by
+    /// [AVLTree.node_insert](struct.AVLTree.html#method.node_insert) and
+    /// [AVLTree.node_delete](struct.AVLTree.html#method.node_delete)
+    fn rebalance(&self, n: AVLTreeNode<T>) -> AVLTreeNode<T> {
+        // update height and size
+        n.borrow_mut().height = self.get_left_height(&n).max(self.get_right_height(&n)) + 1;
+        self.update_size(&n);
 
         // update balance factor
-        let balance_factor = self.get_balance_factor(&ret_node);
+        let balance_factor = self.get_balance_factor(&n);
 
         // maintain
         // case LL: right rotate
-        if balance_factor > 1.0
-            && self.get_balance_factor(&ret_node.borrow().left.clone().unwrap()) >= 0.0
+        if balance_factor > 1.0 && self.get_balance_factor(&n.borrow().left.clone().unwrap()) >= 0.0
         {
-            return Some(self.right_rotate(ret_node));
+            return self.right_rotate(n);
         }
 
         // case RR: left rotate
         if balance_factor < -1.0
-            && self.get_balance_factor(&ret_node.borrow().right.clone().unwrap()) <= 0.0
+            && self.get_balance_factor(&n.borrow().right.clone().unwrap()) <= 0.0
         {
-            return Some(self.left_rotate(ret_node));
+            return self.left_rotate(n);
         }
 
         // case LR: left rotate + right rotate
-        if balance_factor > 1.0
-            && self.get_balance_factor(&ret_node.borrow().left.clone().unwrap()) < 0.0
+        if balance_factor > 1.0 && self.get_balance_factor(&n.borrow().left.clone().unwrap()) < 0.0
         {
-            // ret_node.borrow_mut().left = Some(self.left_rotate(ret_node.borrow_mut().left.clone().unwrap())); // 发生移动
-            // return Some(self.right_rotate(ret_node))
-
-            let left = ret_node.borrow().left.clone().take().unwrap();
-            ret_node.borrow_mut().left = Some(self.left_rotate(left));
-            return Some(self.right_rotate(ret_node));
+            let left = n.borrow_mut().left.take().unwrap();
+            n.borrow_mut().left = Some(self.left_rotate(left));
+            return self.right_rotate(n);
         }
 
         // case RL: right rotate + left rotate
         if balance_factor < -1.0
-            && self.get_balance_factor(&ret_node.borrow().right.clone().unwrap()) > 0.0
+            && self.get_balance_factor(&n.borrow().right.clone().unwrap()) > 0.0
         {
-            // ret_node.borrow_mut().right = Some(self.right_rotate(ret_node.borrow_mut().right.clone().unwrap())); // 发生移动
-            // return Some(self.left_rotate(ret_node))
-
-            let right = ret_node.borrow().right.clone().take().unwrap();
-            ret_node.borrow_mut().right = Some(self.right_rotate(right));
-            return Some(self.left_rotate(ret_node));
+            let right = n.borrow_mut().right.take().unwrap();
+            n.borrow_mut().right = Some(self.right_rotate(right));
+            return self.left_rotate(n);
         }
-        Some(ret_node)
+        n
     }
 
-    /// Delete a value from AVL tree
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use ECE522Project::avlTree::AVLTree; 
-    /// let mut avl_tree = AVLTree::new();
-    /// avl_tree.insert(1);
-    /// avl_tree.delete(1);
-    /// ```
-    pub fn delete(&mut self, delete_value: T) {
-        let root = self.root.take();
-        match root {
-            None => return, // 这棵树是空的，没办法delete，所以什么都没发生??? 还是应该返回null？？？？？？？
-            Some(n) => self.root = self.node_delete(Some(n), delete_value),
-        }
-    }
-    /// Deletes a node, return a new root, which will be called by
-    /// [AVLTree.delete](struct.AVLTree.html#method.delete)
-    // delete node, return new root
-    fn node_delete(&mut self, node: OptionAVLTreeNode<T>, delete_value: T) -> OptionAVLTreeNode<T> {
-        let ret_node = match node {
-            None => node, // 遍历到叶子节点，但是还是没有找到，所以应该返回null，还是说因为叶子节点就是null，所以返回node就可以？？？
-            Some(mut n) => {
-                let node_value = n.borrow().value;
-                if delete_value < node_value {
-                    // look left
-                    let left = n.borrow().left.clone();
-                    n.borrow_mut().left = self.node_delete(left, delete_value);
-                    Some(n) // 返回option
-                } else if delete_value > node_value {
-                    // look right
-                    let right = n.borrow().right.clone();
-                    n.borrow_mut().right = self.node_delete(right, delete_value);
-                    Some(n) // 返回option
-                } else {
-                    // found the node which should be deleted
-                    let left = n.borrow().left.clone();
-                    let right = n.borrow().right.clone();
-                    let ret = match (left.clone(), right.clone()) {
-                        (None, Some(r)) => Some(r), // The left subtree of the node to be deleted is empty, r is new root
-                        (Some(l), None) => Some(l), // The right subtree of the node to be deleted is empty, l is new root
-
-                        // 我想通过左子树为空/右子树为空/左右都不为空，这三种情况进行分类，但是缺少左右子树都为空
-                        // 如果把(None, Some(r)) => r，改成(None, _) => right.unwrap()???，或者改成(_, Some(r)) => r???
-                        (None, None) => None,
-
-                        // The left and right subtrees of the node to be deleted(node n) are not empty.
-                        // Find the smallest node A that is larger than the node n.
-                        (Some(_), Some(right)) => {
-                            let min_value = right.borrow().get_min_value_in_children(); // Find the value of node A which is the minimum value of the right subtree
-                            n.borrow_mut().value = min_value; // Change the value of node n to the value of node A.
-                            let right = n.borrow().right.clone().take();
-                            n.borrow_mut().right = self.node_delete(right, min_value); // Delete the node A in the right subtree.
-                            Some(n) // return new root
-                        }
-                    };
-                    ret // 返回option
-                }
-            }
-        };
-
-        // update and maintain
-        match ret_node {
-            None => ret_node,
-            Some(n) => {
-                // update height
-                n.borrow_mut().height = self
-                    .get_left_height(&n) // 借用了发生移动的
-                    .max(self.get_right_height(&n))
-                    + 1; // 把option类型的ret_node都改成了n
-
-                // update balance factor
-                let balance_factor = self.get_balance_factor(&n);
-
-                // maintain
-                // case LL: right rotate
-                if balance_factor > 1.0
-                    && self.get_balance_factor(&n.borrow().left.clone().unwrap()) >= 0.0
-                {
-                    return Some(self.right_rotate(n));
-                }
-
-                // case RR: left rotate
-                if balance_factor < -1.0
-                    && self.get_balance_factor(&n.borrow().right.clone().unwrap()) <= 0.0
-                {
-                    return Some(self.left_rotate(n));
-                }
-
-                // case LR: left rotate + right rotate
-                if balance_factor > 1.0
-                    && self.get_balance_factor(&n.borrow().left.clone().unwrap()) < 0.0
-                {
-                    let left = n.borrow().left.clone().take().unwrap();
-                    n.borrow_mut().left = Some(self.left_rotate(left));
-                    return Some(self.right_rotate(n));
-                }
-
-                // case RL: right rotate + left rotate
-                if balance_factor < -1.0
-                    && self.get_balance_factor(&n.borrow().right.clone().unwrap()) > 0.0
-                {
-                    let right = n.borrow().right.clone().take().unwrap();
-                    n.borrow_mut().right = Some(self.right_rotate(right));
-                    return Some(self.left_rotate(n));
-                }
-                Some(n)
-            }
-        }
-    }
-
-
     fn get_height(&self, node: OptionAVLTreeNode<T>) -> usize {
         // default height of an empty tree is 0
         node.map_or(0, |n| n.borrow().height)
@@ -327,21 +523,17 @@ impl<T: Ord + Copy + fmt::Debug> AVLTree<T> {
         self.get_left_height(n) as f64 - self.get_right_height(n) as f64
     }
 
-    //Determine whether the tree is balanced
-    fn is_balanced(&self, node: OptionAVLTreeNode<T>) -> bool {
-        match node {
-            Some(node) => {
-                if self.get_balance_factor(&node) <= 1.0 {
-                    self.is_balanced(node.borrow().left.clone())
-                        && self.is_balanced(node.borrow().right.clone())
-                } else {
-                    false
-                }
-            }
-            None => true,
-        }
+    fn get_size(&self, node: OptionAVLTreeNode<T>) -> usize {
+        // default size of an empty tree is 0
+        node.map_or(0, |n| n.borrow().size)
     }
 
+    // recount the subtree size from the children
+    fn update_size(&self, n: &AVLTreeNode<T>) {
+        let size =
+            self.get_size(n.borrow().left.clone()) + self.get_size(n.borrow().right.clone()) + 1;
+        n.borrow_mut().size = size;
+    }
 
     //                 y                                     x
     //               /    \                                 /   \
@@ -358,9 +550,11 @@ impl<T: Ord + Copy + fmt::Debug> AVLTree<T> {
         x.borrow_mut().right = Some(y.clone());
         y.borrow_mut().left = t_3; // 借用了发生移动的y
 
-        // update height of x and y
+        // update height and size of x and y
         y.borrow_mut().height = self.get_left_height(&y).max(self.get_right_height(&y)) + 1;
         x.borrow_mut().height = self.get_left_height(&x).max(self.get_right_height(&x)) + 1;
+        self.update_size(&y);
+        self.update_size(&x);
 
         return x;
     }
@@ -382,17 +576,436 @@ impl<T: Ord + Copy + fmt::Debug> AVLTree<T> {
         //y.borrow_mut().right = Some(T2); // 借用了发生移动的y
         y.borrow_mut().right = t_2;
 
-        // update height of x and y
+        // update height and size of x and y
         y.borrow_mut().height = self.get_left_height(&y).max(self.get_right_height(&y)) + 1;
         x.borrow_mut().height = self.get_left_height(&x).max(self.get_right_height(&x)) + 1;
+        self.update_size(&y);
+        self.update_size(&x);
 
         return x;
     }
 }
 
+/// Set operations of AVLTree, which split and join subtrees instead of inserting values
+/// one by one, taking O(m log(n / m + 1)) time for trees of sizes m <= n
+impl<T: Ord> AVLTree<T> {
+    // join the trees of the values less and greater than the value of the detached node
+    // through the node, return the root of the joined tree
+    fn join(
+        &self,
+        left: OptionAVLTreeNode<T>,
+        node: AVLTreeNode<T>,
+        right: OptionAVLTreeNode<T>,
+    ) -> AVLTreeNode<T> {
+        let left_height = self.get_height(left.clone());
+        let right_height = self.get_height(right.clone());
+        // descend along the inner side of the taller tree to a subtree as tall as the other,
+        // and rebalance on the way back up
+        if left_height > right_height + 1 {
+            let left = left.unwrap();
+            let left_right = left.borrow_mut().right.take();
+            let joined = self.join(left_right, node, right);
+            left.borrow_mut().right = Some(joined);
+            return self.rebalance(left);
+        }
+        if right_height > left_height + 1 {
+            let right = right.unwrap();
+            let right_left = right.borrow_mut().left.take();
+            let joined = self.join(left, node, right_left);
+            right.borrow_mut().left = Some(joined);
+            return self.rebalance(right);
+        }
+        node.borrow_mut().left = left;
+        node.borrow_mut().right = right;
+        self.rebalance(node)
+    }
+
+    // join the trees of the values less and greater than each other
+    fn join_without_node(
+        &self,
+        left: OptionAVLTreeNode<T>,
+        right: OptionAVLTreeNode<T>,
+    ) -> OptionAVLTreeNode<T> {
+        match left {
+            None => right,
+            Some(left) => {
+                let (left, last) = self.split_last(left);
+                Some(self.join(left, last, right))
+            }
+        }
+    }
+
+    // detach the node of the max value, return the rest of the tree and the node
+    fn split_last(&self, node: AVLTreeNode<T>) -> (OptionAVLTreeNode<T>, AVLTreeNode<T>) {
+        let left = node.borrow_mut().left.take();
+        let right = node.borrow_mut().right.take();
+        match right {
+            None => (left, node),
+            Some(right) => {
+                let (right, last) = self.split_last(right);
+                (Some(self.join(left, node, right)), last)
+            }
+        }
+    }
+
+    // split the tree into the trees of the values less and greater than the given value,
+    // and the detached node of the value if it is in the tree
+    fn split(
+        &self,
+        node: OptionAVLTreeNode<T>,
+        value: &T,
+    ) -> (
+        OptionAVLTreeNode<T>,
+        OptionAVLTreeNode<T>,
+        OptionAVLTreeNode<T>,
+    ) {
+        let node = match node {
+            Some(node) => node,
+            None => return (None, None, None),
+        };
+        let left = node.borrow_mut().left.take();
+        let right = node.borrow_mut().right.take();
+        let ordering = self.comparator.compare(value, &node.borrow().value);
+        match ordering {
+            Ordering::Less => {
+                let (less, found, greater) = self.split(left, value);
+                (less, found, Some(self.join(greater, node, right)))
+            }
+            Ordering::Greater => {
+                let (less, found, greater) = self.split(right, value);
+                (Some(self.join(left, node, less)), found, greater)
+            }
+            Ordering::Equal => (left, Some(node), right),
+        }
+    }
+
+    // a value in both trees is kept from the first one
+    fn union_nodes(
+        &self,
+        a: OptionAVLTreeNode<T>,
+        b: OptionAVLTreeNode<T>,
+    ) -> OptionAVLTreeNode<T> {
+        let (a, b) = match (a, b) {
+            (None, b) => return b,
+            (a, None) => return a,
+            (Some(a), Some(b)) => (a, b),
+        };
+        let b_left = b.borrow_mut().left.take();
+        let b_right = b.borrow_mut().right.take();
+        let (a_less, a_found, a_greater) = self.split(Some(a), &b.borrow().value);
+        let left = self.union_nodes(a_less, b_left);
+        let right = self.union_nodes(a_greater, b_right);
+        Some(self.join(left, a_found.unwrap_or(b), right))
+    }
+
+    // the values are kept from the first tree
+    fn intersection_nodes(
+        &self,
+        a: OptionAVLTreeNode<T>,
+        b: OptionAVLTreeNode<T>,
+    ) -> OptionAVLTreeNode<T> {
+        let (a, b) = match (a, b) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return None,
+        };
+        let b_left = b.borrow_mut().left.take();
+        let b_right = b.borrow_mut().right.take();
+        let (a_less, a_found, a_greater) = self.split(Some(a), &b.borrow().value);
+        let left = self.intersection_nodes(a_less, b_left);
+        let right = self.intersection_nodes(a_greater, b_right);
+        match a_found {
+            Some(node) => Some(self.join(left, node, right)),
+            None => self.join_without_node(left, right),
+        }
+    }
+
+    fn difference_nodes(
+        &self,
+        a: OptionAVLTreeNode<T>,
+        b: OptionAVLTreeNode<T>,
+    ) -> OptionAVLTreeNode<T> {
+        let (a, b) = match (a, b) {
+            (None, _) => return None,
+            (a, None) => return a,
+            (Some(a), Some(b)) => (a, b),
+        };
+        let b_left = b.borrow_mut().left.take();
+        let b_right = b.borrow_mut().right.take();
+        let (a_less, _, a_greater) = self.split(Some(a), &b.borrow().value);
+        let left = self.difference_nodes(a_less, b_left);
+        let right = self.difference_nodes(a_greater, b_right);
+        self.join_without_node(left, right)
+    }
+
+    fn symmetric_difference_nodes(
+        &self,
+        a: OptionAVLTreeNode<T>,
+        b: OptionAVLTreeNode<T>,
+    ) -> OptionAVLTreeNode<T> {
+        let (a, b) = match (a, b) {
+            (None, b) => return b,
+            (a, None) => return a,
+            (Some(a), Some(b)) => (a, b),
+        };
+        let b_left = b.borrow_mut().left.take();
+        let b_right = b.borrow_mut().right.take();
+        let (a_less, a_found, a_greater) = self.split(Some(a), &b.borrow().value);
+        let left = self.symmetric_difference_nodes(a_less, b_left);
+        let right = self.symmetric_difference_nodes(a_greater, b_right);
+        match a_found {
+            Some(_) => self.join_without_node(left, right),
+            None => Some(self.join(left, b, right)),
+        }
+    }
+}
+
+/// Joining and splitting of AVLTree, which take O(log n) time
+impl<T: Ord> AVLTree<T> {
+    /// Move the values greater than or equal to the given value into a new tree
+    /// and return it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
+    /// for value in 0..10 {
+    ///     tree.insert(value);
+    /// }
+    /// let greater = tree.split_off(&6);
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 5], tree.iter().collect::<Vec<_>>());
+    /// assert_eq!(vec![6, 7, 8, 9], greater.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn split_off(&mut self, value: &T) -> Self {
+        let root = self.root.take();
+        let (less, found, greater) = self.split(root, value);
+        self.root = less;
+        let root = match found {
+            Some(node) => Some(self.join(None, node, greater)),
+            None => greater,
+        };
+        AVLTree {
+            root,
+            comparator: self.comparator.clone(),
+        }
+    }
+
+    /// Move all values of the other tree into this tree, leaving the other tree empty.
+    /// Trees whose values do not overlap are joined in O(log n) time,
+    /// otherwise the values of both are merged, and a value of the other tree replaces
+    /// an equal one in this tree like [insert](#method.insert) does.
+    ///
+    /// Panics if the trees are ordered by different comparators.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
+    /// let mut other = AVLTree::new();
+    /// tree.insert(1);
+    /// other.insert(2);
+    /// tree.append(&mut other);
+    /// assert_eq!(vec![1, 2], tree.iter().collect::<Vec<_>>());
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        self.comparator.assert_same(&other.comparator);
+        let (a, b) = match (self.root.take(), other.root.take()) {
+            (Some(a), Some(b)) => (a, b),
+            (a, b) => {
+                self.root = a.or(b);
+                return;
+            }
+        };
+        self.root = if self.is_before(&a, &b) {
+            self.join_without_node(Some(a), Some(b))
+        } else if self.is_before(&b, &a) {
+            self.join_without_node(Some(b), Some(a))
+        } else {
+            // the values of the other tree are kept, as if they were inserted
+            self.union_nodes(Some(b), Some(a))
+        };
+    }
+
+    // whether all values of the left tree are less than the values of the right tree
+    fn is_before(&self, left: &AVLTreeNode<T>, right: &AVLTreeNode<T>) -> bool {
+        let mut max_node = left.clone();
+        loop {
+            let next = max_node.borrow().right.clone();
+            match next {
+                Some(next) => max_node = next,
+                None => break,
+            }
+        }
+        let min_node = Self::get_min_node(right.clone());
+        let ordering = self
+            .comparator
+            .compare(&max_node.borrow().value, &min_node.borrow().value);
+        ordering == Ordering::Less
+    }
+}
+
+/// Building AVLTree from sorted values in O(n) time
+impl<T: Ord> AVLTree<T> {
+    /// Build a balanced tree from values in ascending order without any rotation,
+    /// keeping the last one of repeated values
+    ///
+    /// Panics if the values are not in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let tree = AVLTree::from_sorted_iter(0..100);
+    /// assert_eq!(100, tree.len());
+    /// assert_eq!(7, tree.height());
+    /// ```
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted_values(collect_sorted(iter), Comparator::default())
+    }
+
+    // build a balanced tree from values in strictly ascending order of the comparator
+    fn from_sorted_values(values: Vec<T>, comparator: Comparator<T>) -> Self {
+        let len = values.len();
+        let root = Self::build_sorted(&mut values.into_iter(), len);
+        AVLTree { root, comparator }
+    }
+
+    // build a subtree of the next len values, splitting them in the middle so that
+    // the heights of the children differ by at most one
+    fn build_sorted(values: &mut std::vec::IntoIter<T>, len: usize) -> OptionAVLTreeNode<T> {
+        if len == 0 {
+            return None;
+        }
+        let left_len = (len - 1) / 2;
+        let left = Self::build_sorted(values, left_len);
+        let value = values.next().unwrap();
+        let right = Self::build_sorted(values, len - 1 - left_len);
+        // the right child holds at least as many values, so it is at least as tall
+        let height = right.as_ref().map_or(0, |n| n.borrow().height) + 1;
+        Some(Rc::new(RefCell::new(TreeNode {
+            value,
+            left,
+            right,
+            height,
+            size: len,
+        })))
+    }
+}
+
+/// Consume two AVLTrees and return the tree of the values in either of them
+///
+/// The operators on two trees panic if the trees are ordered by different comparators.
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::prelude::*;
+/// let mut a = AVLTree::new();
+/// let mut b = AVLTree::new();
+/// a.insert(1);
+/// b.insert(2);
+/// let c = &a | &b;
+/// assert_eq!(vec![1, 2], c.iter().collect::<Vec<_>>());
+/// assert_eq!(vec![1, 2], (a | b).iter().collect::<Vec<_>>());
+/// ```
+impl<T: Ord> BitOr for AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn bitor(mut self, mut other: AVLTree<T>) -> AVLTree<T> {
+        self.comparator.assert_same(&other.comparator);
+        let (a, b) = (self.root.take(), other.root.take());
+        AVLTree {
+            root: self.union_nodes(a, b),
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+/// Consume two AVLTrees and return the tree of the values in both of them
+impl<T: Ord> BitAnd for AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn bitand(mut self, mut other: AVLTree<T>) -> AVLTree<T> {
+        self.comparator.assert_same(&other.comparator);
+        let (a, b) = (self.root.take(), other.root.take());
+        AVLTree {
+            root: self.intersection_nodes(a, b),
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+/// Consume two AVLTrees and return the tree of the values in the first one but not in
+/// the second one
+impl<T: Ord> Sub for AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn sub(mut self, mut other: AVLTree<T>) -> AVLTree<T> {
+        self.comparator.assert_same(&other.comparator);
+        let (a, b) = (self.root.take(), other.root.take());
+        AVLTree {
+            root: self.difference_nodes(a, b),
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+/// Consume two AVLTrees and return the tree of the values in exactly one of them
+impl<T: Ord> BitXor for AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn bitxor(mut self, mut other: AVLTree<T>) -> AVLTree<T> {
+        self.comparator.assert_same(&other.comparator);
+        let (a, b) = (self.root.take(), other.root.take());
+        AVLTree {
+            root: self.symmetric_difference_nodes(a, b),
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+// the borrowed versions merge the values of both trees in O(m + n) time
+// and only copy the values of the result
+impl<T: Ord + Clone> BitOr<&AVLTree<T>> for &AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn bitor(self, other: &AVLTree<T>) -> AVLTree<T> {
+        AVLTree::from_sorted_values(self.union(other).collect(), self.get_comparator())
+    }
+}
+
+impl<T: Ord + Clone> BitAnd<&AVLTree<T>> for &AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn bitand(self, other: &AVLTree<T>) -> AVLTree<T> {
+        AVLTree::from_sorted_values(self.intersection(other).collect(), self.get_comparator())
+    }
+}
+
+impl<T: Ord + Clone> Sub<&AVLTree<T>> for &AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn sub(self, other: &AVLTree<T>) -> AVLTree<T> {
+        AVLTree::from_sorted_values(self.difference(other).collect(), self.get_comparator())
+    }
+}
+
+impl<T: Ord + Clone> BitXor<&AVLTree<T>> for &AVLTree<T> {
+    type Output = AVLTree<T>;
+
+    fn bitxor(self, other: &AVLTree<T>) -> AVLTree<T> {
+        let values = self.symmetric_difference(other).collect();
+        AVLTree::from_sorted_values(values, self.get_comparator())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fixtures;
+    use crate::nodeHandle::Order;
 
     #[test]
     fn tree_traversal() {
@@ -402,11 +1015,10 @@ mod test {
         vec![16, 16, 8, 24, 20, 22].iter().for_each(|v| {
             tree.insert(*v);
         });
-        let root = tree.root.clone().unwrap();
         let mut pre_container = vec![];
         let mut in_container = vec![];
-        tree.preorder_traverse(root.clone(), &mut pre_container);
-        tree.in_order_traverse(root.clone(), &mut in_container);
+        tree.traverse(Order::PreOrder, |value, _| pre_container.push(*value));
+        tree.traverse(Order::InOrder, |value, _| in_container.push(*value));
         let is_balanced = tree.is_balanced(tree.root.clone());
         // println!("check {:#?}", in_container);
         assert_eq!(pre_container, vec![20, 8, 0, 16, 24, 22]);
@@ -436,13 +1048,230 @@ mod test {
             tree.insert(*v);
         });
 
-        let root = tree.root.clone().unwrap();
-        tree.delete(16);
+        tree.delete(&16);
         let mut container = vec![];
-        tree.preorder_traverse(root.clone(), &mut container);
+        tree.traverse(Order::PreOrder, |value, _| container.push(*value));
         let result = tree.is_balanced(tree.root.clone());
         assert_eq!(result, true);
 
         assert_eq!(container, vec![20, 8, 0, 24, 22]);
     }
+
+    // check the heights, sizes and balance of every node, return the height
+    fn check_node<T: Ord>(node: &OptionAVLTreeNode<T>) -> usize {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return 0,
+        };
+        let left = check_node(&node.left);
+        let right = check_node(&node.right);
+        assert!(left.max(right) - left.min(right) <= 1);
+        assert_eq!(node.height, left.max(right) + 1);
+        let left_size = node.left.as_ref().map_or(0, |n| n.borrow().size);
+        let right_size = node.right.as_ref().map_or(0, |n| n.borrow().size);
+        assert_eq!(node.size, left_size + right_size + 1);
+        node.height
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(|tree: &AVLTree<i32>| {
+            check_node(&tree.root);
+        });
+    }
+
+    #[test]
+    fn test_set_operations() {
+        use std::collections::BTreeSet;
+        // sets of very different sizes and overlaps
+        let sets: Vec<BTreeSet<u32>> = vec![
+            BTreeSet::new(),
+            (0..100).collect(),
+            (0..100).filter(|v| v % 3 == 0).collect(),
+            (50..300).filter(|v| v % 2 == 0).collect(),
+            (1000..1010).collect(),
+            [7, 42].iter().cloned().collect(),
+        ];
+        let to_tree = |set: &BTreeSet<u32>| {
+            let mut tree = AVLTree::new();
+            for value in set.iter() {
+                tree.insert(*value);
+            }
+            tree
+        };
+        for a in sets.iter() {
+            for b in sets.iter() {
+                let (tree_a, tree_b) = (to_tree(a), to_tree(b));
+                let expected = [a | b, a & b, a - b, a ^ b];
+                let lazy: [Vec<u32>; 4] = [
+                    tree_a.union(&tree_b).collect(),
+                    tree_a.intersection(&tree_b).collect(),
+                    tree_a.difference(&tree_b).collect(),
+                    tree_a.symmetric_difference(&tree_b).collect(),
+                ];
+                let trees = [
+                    &tree_a | &tree_b,
+                    &tree_a & &tree_b,
+                    &tree_a - &tree_b,
+                    tree_a.clone() ^ tree_b.clone(),
+                ];
+                for ((set, values), tree) in expected.iter().zip(lazy.iter()).zip(trees.iter()) {
+                    let set: Vec<u32> = set.iter().cloned().collect();
+                    assert_eq!(&set, values);
+                    assert_eq!(set, tree.iter().collect::<Vec<_>>());
+                    check_node(&tree.root);
+                }
+                assert_eq!(tree_a.is_subset(&tree_b), a.is_subset(b));
+                assert_eq!(tree_a.is_disjoint(&tree_b), a.is_disjoint(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_off_append() {
+        for split_at in [0, 1, 37, 50, 99, 100, 150].iter() {
+            let mut tree = AVLTree::new();
+            for value in 0..100 {
+                tree.insert(value * 7 % 100);
+            }
+            let mut greater = tree.split_off(split_at);
+            check_node(&tree.root);
+            check_node(&greater.root);
+            let less_end = *split_at.min(&100);
+            assert_eq!(
+                tree.iter().collect::<Vec<_>>(),
+                (0..less_end).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                greater.iter().collect::<Vec<_>>(),
+                (less_end..100).collect::<Vec<_>>()
+            );
+            // join them back in either order
+            if split_at % 2 == 0 {
+                tree.append(&mut greater);
+            } else {
+                greater.append(&mut tree);
+                std::mem::swap(&mut tree, &mut greater);
+            }
+            assert!(greater.is_empty());
+            check_node(&tree.root);
+            assert_eq!(
+                tree.iter().collect::<Vec<_>>(),
+                (0..100).collect::<Vec<_>>()
+            );
+            for value in 0..100 {
+                assert!(tree.delete(&value));
+            }
+        }
+        // trees of very different heights, and overlapping trees
+        let mut small = AVLTree::new();
+        small.insert(1000);
+        let mut large = AVLTree::new();
+        for value in 0..500 {
+            large.insert(value);
+        }
+        large.append(&mut small);
+        check_node(&large.root);
+        assert_eq!(large.len(), 501);
+        let mut overlapping = AVLTree::new();
+        for value in 250..750 {
+            overlapping.insert(value);
+        }
+        large.append(&mut overlapping);
+        check_node(&large.root);
+        assert_eq!(
+            large.iter().collect::<Vec<_>>(),
+            (0..750).chain(1000..1001).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for len in 0..70usize {
+            let tree = AVLTree::from_sorted_iter(0..len);
+            check_node(&tree.root);
+            // as low as a complete tree of the same size
+            assert_eq!(tree.height(), usize::BITS - len.leading_zeros());
+        }
+    }
+
+    #[test]
+    fn test_extend_replaces() {
+        // values equal in the comparator replace the ones in the tree, sorted or not
+        let mut tree =
+            AVLTree::with_comparator(|a: &&str, b: &&str| a.to_lowercase().cmp(&b.to_lowercase()));
+        tree.extend(vec!["a", "c", "e"]);
+        tree.extend(vec!["A", "b", "C"]);
+        check_node(&tree.root);
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec!["A", "b", "C", "e"]);
+        tree.extend(vec!["E", "a", "D"]);
+        check_node(&tree.root);
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec!["a", "b", "C", "D", "E"]
+        );
+        let mut other = AVLTree::from_comparator(tree.get_comparator());
+        other.extend(vec!["B", "c", "f"]);
+        tree.append(&mut other);
+        check_node(&tree.root);
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec!["a", "B", "c", "D", "E", "f"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "values are not in ascending order")]
+    fn test_from_sorted_iter_unsorted() {
+        AVLTree::from_sorted_iter(vec![2, 1]);
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(|tree: &AVLTree<i32>| {
+            check_node(&tree.root);
+        });
+        // the set operations, split and append follow the comparator too
+        let mut tree = AVLTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        tree.extend((0..50).filter(|v| v % 2 == 1));
+        let mut other = AVLTree::from_comparator(tree.get_comparator());
+        other.extend(vec![60, 50, 3, 1]);
+        let union = (&tree | &other).iter().collect::<Vec<_>>();
+        assert_eq!(union[..3], [60, 50, 49]);
+        assert!(tree.union(&other).eq(union.into_iter()));
+        assert_eq!(tree.intersection(&other).collect::<Vec<_>>(), vec![3, 1]);
+        let greater = tree.split_off(&20);
+        assert_eq!(
+            greater.iter().collect::<Vec<_>>(),
+            vec![19, 17, 15, 13, 11, 9, 7, 5, 3, 1]
+        );
+        tree.append(&mut other);
+        check_node(&tree.root);
+        assert_eq!(
+            tree.iter().take(4).collect::<Vec<_>>(),
+            vec![60, 50, 49, 47]
+        );
+        assert_eq!(tree.iter().last(), Some(1));
+    }
+
+    #[test]
+    fn test_mismatched_comparators() {
+        let tree = || (0..10).collect::<AVLTree<i32>>();
+        let reversed = || {
+            let mut tree = AVLTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+            tree.extend(5..15);
+            tree
+        };
+        assert!(fixtures::panics(|| drop(tree() | reversed())));
+        assert!(fixtures::panics(|| drop(tree() & reversed())));
+        assert!(fixtures::panics(|| drop(tree() - reversed())));
+        assert!(fixtures::panics(|| drop(tree() ^ reversed())));
+        assert!(fixtures::panics(|| drop(&tree() | &reversed())));
+        assert!(fixtures::panics(|| tree().append(&mut reversed())));
+        // neither tree is changed by a refused append
+        let (mut a, mut b) = (tree(), reversed());
+        assert!(fixtures::panics(|| a.append(&mut b)));
+        assert!(a.iter().eq(0..10));
+        assert!(b.iter().eq((5..15).rev()));
+    }
 }
@@ -0,0 +1,378 @@
+//! Arena-backed AVL tree
+//!
+//! You can generate an AVL tree whose nodes are stored in an
+//! [Arena](../arena/struct.Arena.html), and insert or delete nodes.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::arena::{Arena, ArenaHandle, NIL};
+pub use crate::commonTrait::{CommonTreeTrait, OrderedSet};
+use crate::commonTrait::{Comparator, IntoIter};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+
+/// Structure of ArenaAVLTree, every node keeps the height of its subtree
+#[derive(Clone, Debug)]
+pub struct ArenaAVLTree<T: Ord> {
+    nodes: Arena<T, u8>,
+    root: u32,
+    comparator: Comparator<T>,
+}
+
+// extend from common tree trait
+impl<T: Ord> CommonTreeTrait<T> for ArenaAVLTree<T> {
+    type Handle<'a>
+        = ArenaHandle<'a, T, u8>
+    where
+        Self: 'a;
+
+    fn get_root(&self) -> Option<ArenaHandle<'_, T, u8>> {
+        self.nodes.handle(self.root)
+    }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
+}
+
+// construct and mutate ArenaAVLTree in generic code
+impl<T: Ord> OrderedSet<T> for ArenaAVLTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        ArenaAVLTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        ArenaAVLTree {
+            nodes: Arena::new(),
+            root: NIL,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        ArenaAVLTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        ArenaAVLTree::delete(self, value)
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for ArenaAVLTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, u32, Arena<T, u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let root = Some(self.root).filter(|&root| root != NIL);
+        IntoIter::with_storage(root, self.len(), self.nodes)
+    }
+}
+
+// draw ArenaAVLTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for ArenaAVLTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        dot::to_dot(
+            self.get_root(),
+            nil_leaves.then(DotStyle::default),
+            |node| {
+                let node = &self.nodes[node.index()];
+                DotStyle::avl(
+                    self.get_height(node.left).into(),
+                    self.get_height(node.right).into(),
+                )
+            },
+        )
+    }
+}
+
+// draw ArenaAVLTree as text
+impl<T: Ord + fmt::Debug> Pretty for ArenaAVLTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.get_root(), |node| {
+            Some(format!("h={}", self.nodes[node.index()].meta))
+        })
+    }
+}
+
+impl<T: Ord> Default for ArenaAVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implementations of ArenaAVLTree
+impl<T: Ord> ArenaAVLTree<T> {
+    /// Creates a new AVL tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTreeArena::ArenaAVLTree;
+    /// let mut tree: ArenaAVLTree<u32> = ArenaAVLTree::new();
+    /// ```
+    pub fn new() -> Self {
+        ArenaAVLTree {
+            nodes: Arena::new(),
+            root: NIL,
+            comparator: Comparator::default(),
+        }
+    }
+
+    /// Creates a new AVL tree ordered by the comparator instead of `Ord`, which tells how
+    /// the first value compares with the second one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = ArenaAVLTree::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(Some(2), tree.min());
+    /// ```
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        ArenaAVLTree {
+            nodes: Arena::new(),
+            root: NIL,
+            comparator: Comparator::new(cmp),
+        }
+    }
+
+    /// Insert a new value to the AVL tree, return false if the value already exists
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTreeArena::ArenaAVLTree;
+    /// let mut tree = ArenaAVLTree::new();
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
+    /// ```
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        let (root, replaced) = self.node_insert(self.root, insert_value);
+        self.root = root;
+        replaced.is_none()
+    }
+
+    /// Delete a value from the AVL tree, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTreeArena::ArenaAVLTree;
+    /// let mut tree = ArenaAVLTree::new();
+    /// tree.insert(1);
+    /// assert!(tree.delete(&1));
+    /// assert!(!tree.delete(&1));
+    /// ```
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        let (root, removed) = self.node_delete(self.root, delete_value);
+        self.root = root;
+        removed.is_some()
+    }
+
+    /// Inserts a value into the subtree, return the new root of the subtree
+    /// and the replaced equal value
+    fn node_insert(&mut self, node: u32, insert_value: T) -> (u32, Option<T>) {
+        if node == NIL {
+            // default height of a new node is 1, which is a leave
+            return (self.nodes.alloc(insert_value, 1), None);
+        }
+        let replaced = match self
+            .comparator
+            .compare(&insert_value, &self.nodes[node].value)
+        {
+            Ordering::Less => {
+                let (left, replaced) = self.node_insert(self.nodes[node].left, insert_value);
+                self.nodes[node].left = left;
+                replaced
+            }
+            Ordering::Greater => {
+                let (right, replaced) = self.node_insert(self.nodes[node].right, insert_value);
+                self.nodes[node].right = right;
+                replaced
+            }
+            // equal, update value, the shape does not change
+            Ordering::Equal => {
+                let value = std::mem::replace(&mut self.nodes[node].value, insert_value);
+                return (node, Some(value));
+            }
+        };
+        (self.rebalance(node), replaced)
+    }
+
+    /// Deletes a value from the subtree, return the new root of the subtree
+    /// and the removed value
+    fn node_delete(&mut self, node: u32, delete_value: &T) -> (u32, Option<T>) {
+        if node == NIL {
+            // reach the leaves, the value is not found
+            return (NIL, None);
+        }
+        let removed = match self
+            .comparator
+            .compare(delete_value, &self.nodes[node].value)
+        {
+            Ordering::Less => {
+                let (left, removed) = self.node_delete(self.nodes[node].left, delete_value);
+                self.nodes[node].left = left;
+                removed
+            }
+            Ordering::Greater => {
+                let (right, removed) = self.node_delete(self.nodes[node].right, delete_value);
+                self.nodes[node].right = right;
+                removed
+            }
+            // found the node which should be deleted
+            Ordering::Equal => {
+                let left = self.nodes[node].left;
+                let right = self.nodes[node].right;
+                let removed = Some(self.nodes.free(node).value);
+                if left == NIL || right == NIL {
+                    // at most one child, which takes the place of the node
+                    let child = if left == NIL { right } else { left };
+                    return (child, removed);
+                }
+                // two children, the min node of the right subtree takes the place of the node
+                let (right, min_node) = self.delete_min(right);
+                self.nodes[min_node].left = left;
+                self.nodes[min_node].right = right;
+                return (self.rebalance(min_node), removed);
+            }
+        };
+        if removed.is_none() {
+            return (node, None);
+        }
+        (self.rebalance(node), removed)
+    }
+
+    /// Detach the min node of the subtree, return the new root of the subtree
+    /// and the detached node
+    fn delete_min(&mut self, node: u32) -> (u32, u32) {
+        let left = self.nodes[node].left;
+        if left == NIL {
+            return (self.nodes[node].right, node);
+        }
+        let (left, min_node) = self.delete_min(left);
+        self.nodes[node].left = left;
+        (self.rebalance(node), min_node)
+    }
+
+    /// Updates the height and rotates the node if it is unbalanced, return the new root
+    /// of the subtree
+    fn rebalance(&mut self, node: u32) -> u32 {
+        self.update(node);
+        let balance_factor = self.get_balance_factor(node);
+        // case LL and LR
+        if balance_factor > 1 {
+            let left = self.nodes[node].left;
+            if self.get_balance_factor(left) < 0 {
+                self.nodes[node].left = self.left_rotate(left);
+            }
+            return self.right_rotate(node);
+        }
+        // case RR and RL
+        if balance_factor < -1 {
+            let right = self.nodes[node].right;
+            if self.get_balance_factor(right) > 0 {
+                self.nodes[node].right = self.right_rotate(right);
+            }
+            return self.left_rotate(node);
+        }
+        node
+    }
+
+    fn get_height(&self, node: u32) -> i32 {
+        // default height of an empty tree is 0
+        if node == NIL {
+            0
+        } else {
+            self.nodes[node].meta as i32
+        }
+    }
+
+    fn get_balance_factor(&self, node: u32) -> i32 {
+        self.get_height(self.nodes[node].left) - self.get_height(self.nodes[node].right)
+    }
+
+    // recount the height and size of the node from its children
+    fn update(&mut self, node: u32) {
+        let left = self.nodes[node].left;
+        let right = self.nodes[node].right;
+        self.nodes[node].meta = (self.get_height(left).max(self.get_height(right)) + 1) as u8;
+        self.nodes.update_size(node);
+    }
+
+    // rotate the subtree to the right and return its new root
+    fn right_rotate(&mut self, y: u32) -> u32 {
+        let x = self.nodes[y].left;
+        self.nodes[y].left = self.nodes[x].right;
+        self.nodes[x].right = y;
+        self.update(y);
+        self.update(x);
+        x
+    }
+
+    // rotate the subtree to the left and return its new root
+    fn left_rotate(&mut self, y: u32) -> u32 {
+        let x = self.nodes[y].right;
+        self.nodes[y].right = self.nodes[x].left;
+        self.nodes[x].left = y;
+        self.update(y);
+        self.update(x);
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures;
+
+    // check the AVL property and the stored heights and sizes, return the height
+    fn check_node<T: Ord>(tree: &ArenaAVLTree<T>, node: u32) -> i32 {
+        if node == NIL {
+            return 0;
+        }
+        let left = check_node(tree, tree.nodes[node].left);
+        let right = check_node(tree, tree.nodes[node].right);
+        assert!((left - right).abs() <= 1);
+        assert_eq!(tree.get_height(node), left.max(right) + 1);
+        let size = tree.nodes.size(tree.nodes[node].left) + tree.nodes.size(tree.nodes[node].right);
+        assert_eq!(tree.nodes[node].size, size + 1);
+        left.max(right) + 1
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(|tree: &ArenaAVLTree<i32>| {
+            check_node(tree, tree.root);
+        });
+    }
+
+    #[test]
+    fn test_reuse_slots() {
+        let mut tree = ArenaAVLTree::new();
+        for value in 0..100 {
+            tree.insert(value);
+        }
+        for value in 0..100 {
+            tree.delete(&value);
+        }
+        assert!(tree.is_empty());
+        for value in 100..200 {
+            tree.insert(value);
+        }
+        // the freed slots are used again
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.nodes.capacity(), 100);
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(|tree: &ArenaAVLTree<i32>| {
+            check_node(tree, tree.root);
+        });
+    }
+}
@@ -0,0 +1,366 @@
+//! Box-based AVL tree
+//!
+//! You can generate an AVL tree whose nodes own their children through `Box`,
+//! and insert or delete nodes.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::boxTree::{BoxNode, OptionBoxNode};
+pub use crate::commonTrait::{CommonTreeTrait, OrderedSet};
+use crate::commonTrait::{Comparator, IntoIter};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+
+type AVLNode<T> = Box<BoxNode<T, u8>>;
+type OptionAVLNode<T> = OptionBoxNode<T, u8>;
+
+/// Structure of BoxAVLTree, every node keeps the height of its subtree
+#[derive(Clone, Debug)]
+pub struct BoxAVLTree<T: Ord> {
+    root: OptionAVLNode<T>,
+    comparator: Comparator<T>,
+}
+
+// extend from common tree trait
+impl<T: Ord> CommonTreeTrait<T> for BoxAVLTree<T> {
+    type Handle<'a>
+        = &'a BoxNode<T, u8>
+    where
+        Self: 'a;
+
+    fn get_root(&self) -> Option<&BoxNode<T, u8>> {
+        self.root.as_deref()
+    }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
+}
+
+// construct and mutate BoxAVLTree in generic code
+impl<T: Ord> OrderedSet<T> for BoxAVLTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        BoxAVLTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        BoxAVLTree {
+            root: None,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        BoxAVLTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        BoxAVLTree::delete(self, value)
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for BoxAVLTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, AVLNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter::new(self.root.take(), len)
+    }
+}
+
+// draw BoxAVLTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for BoxAVLTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        dot::to_dot(
+            self.root.as_deref(),
+            nil_leaves.then(DotStyle::default),
+            |node| {
+                DotStyle::avl(
+                    Self::get_height(&node.left).into(),
+                    Self::get_height(&node.right).into(),
+                )
+            },
+        )
+    }
+}
+
+// draw BoxAVLTree as text
+impl<T: Ord + fmt::Debug> Pretty for BoxAVLTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.root.as_deref(), |node| {
+            Some(format!("h={}", node.meta))
+        })
+    }
+}
+
+impl<T: Ord> Default for BoxAVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implementations of BoxAVLTree
+impl<T: Ord> BoxAVLTree<T> {
+    /// Creates a new AVL tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTreeBox::BoxAVLTree;
+    /// let mut tree: BoxAVLTree<u32> = BoxAVLTree::new();
+    /// ```
+    pub fn new() -> Self {
+        BoxAVLTree {
+            root: None,
+            comparator: Comparator::default(),
+        }
+    }
+
+    /// Creates a new AVL tree ordered by the comparator instead of `Ord`, which tells how
+    /// the first value compares with the second one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = BoxAVLTree::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(Some(2), tree.min());
+    /// ```
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        BoxAVLTree {
+            root: None,
+            comparator: Comparator::new(cmp),
+        }
+    }
+
+    /// Insert a new value to the AVL tree, return false if the value already exists
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTreeBox::BoxAVLTree;
+    /// let mut tree = BoxAVLTree::new();
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
+    /// ```
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        let (root, replaced) = Self::node_insert(self.root.take(), insert_value, &self.comparator);
+        self.root = Some(root);
+        replaced.is_none()
+    }
+
+    /// Delete a value from the AVL tree, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTreeBox::BoxAVLTree;
+    /// let mut tree = BoxAVLTree::new();
+    /// tree.insert(1);
+    /// assert!(tree.delete(&1));
+    /// assert!(!tree.delete(&1));
+    /// ```
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        let (root, removed) = Self::node_delete(self.root.take(), delete_value, &self.comparator);
+        self.root = root;
+        removed.is_some()
+    }
+
+    /// Inserts a value into the subtree, return the new root of the subtree
+    /// and the replaced equal value
+    fn node_insert(
+        node: OptionAVLNode<T>,
+        insert_value: T,
+        comparator: &Comparator<T>,
+    ) -> (AVLNode<T>, Option<T>) {
+        let mut node = match node {
+            Some(node) => node,
+            // default height of a new node is 1, which is a leave
+            None => return (BoxNode::new(insert_value, 1), None),
+        };
+        let replaced = match comparator.compare(&insert_value, &node.value) {
+            Ordering::Less => {
+                let (left, replaced) =
+                    Self::node_insert(node.left.take(), insert_value, comparator);
+                node.left = Some(left);
+                replaced
+            }
+            Ordering::Greater => {
+                let (right, replaced) =
+                    Self::node_insert(node.right.take(), insert_value, comparator);
+                node.right = Some(right);
+                replaced
+            }
+            // equal, update value, the shape does not change
+            Ordering::Equal => {
+                let value = std::mem::replace(&mut node.value, insert_value);
+                return (node, Some(value));
+            }
+        };
+        (Self::rebalance(node), replaced)
+    }
+
+    /// Deletes a value from the subtree, return the new root of the subtree
+    /// and the removed value
+    fn node_delete(
+        node: OptionAVLNode<T>,
+        delete_value: &T,
+        comparator: &Comparator<T>,
+    ) -> (OptionAVLNode<T>, Option<T>) {
+        let mut node = match node {
+            Some(node) => node,
+            // reach the leaves, the value is not found
+            None => return (None, None),
+        };
+        let removed = match comparator.compare(delete_value, &node.value) {
+            Ordering::Less => {
+                let (left, removed) = Self::node_delete(node.left.take(), delete_value, comparator);
+                node.left = left;
+                removed
+            }
+            Ordering::Greater => {
+                let (right, removed) =
+                    Self::node_delete(node.right.take(), delete_value, comparator);
+                node.right = right;
+                removed
+            }
+            // found the node which should be deleted
+            Ordering::Equal => {
+                let BoxNode {
+                    value, left, right, ..
+                } = *node;
+                let (left, right) = match (left, right) {
+                    (Some(left), Some(right)) => (left, right),
+                    // at most one child, which takes the place of the node
+                    (left, right) => return (left.or(right), Some(value)),
+                };
+                // two children, the min node of the right subtree takes the place of the node
+                let (right, mut min_node) = Self::delete_min(right);
+                min_node.left = Some(left);
+                min_node.right = right;
+                return (Some(Self::rebalance(min_node)), Some(value));
+            }
+        };
+        if removed.is_none() {
+            return (Some(node), None);
+        }
+        (Some(Self::rebalance(node)), removed)
+    }
+
+    /// Detach the min node of the subtree, return the new root of the subtree
+    /// and the detached node
+    fn delete_min(mut node: AVLNode<T>) -> (OptionAVLNode<T>, AVLNode<T>) {
+        match node.left.take() {
+            None => (node.right.take(), node),
+            Some(left) => {
+                let (left, min_node) = Self::delete_min(left);
+                node.left = left;
+                (Some(Self::rebalance(node)), min_node)
+            }
+        }
+    }
+
+    /// Updates the height and rotates the node if it is unbalanced, return the new root
+    /// of the subtree
+    fn rebalance(mut node: AVLNode<T>) -> AVLNode<T> {
+        Self::update(&mut node);
+        let balance_factor = Self::get_balance_factor(&node);
+        // case LL and LR
+        if balance_factor > 1 {
+            let left = node.left.take().unwrap();
+            node.left = Some(if Self::get_balance_factor(&left) < 0 {
+                Self::left_rotate(left)
+            } else {
+                left
+            });
+            return Self::right_rotate(node);
+        }
+        // case RR and RL
+        if balance_factor < -1 {
+            let right = node.right.take().unwrap();
+            node.right = Some(if Self::get_balance_factor(&right) > 0 {
+                Self::right_rotate(right)
+            } else {
+                right
+            });
+            return Self::left_rotate(node);
+        }
+        node
+    }
+
+    fn get_height(node: &OptionAVLNode<T>) -> i32 {
+        // default height of an empty tree is 0
+        node.as_ref().map_or(0, |node| node.meta as i32)
+    }
+
+    fn get_balance_factor(node: &AVLNode<T>) -> i32 {
+        Self::get_height(&node.left) - Self::get_height(&node.right)
+    }
+
+    // recount the height and size of the node from its children
+    fn update(node: &mut AVLNode<T>) {
+        let height = Self::get_height(&node.left).max(Self::get_height(&node.right)) + 1;
+        node.meta = height as u8;
+        node.update_size();
+    }
+
+    // rotate the subtree to the right and return its new root
+    fn right_rotate(mut y: AVLNode<T>) -> AVLNode<T> {
+        let mut x = y.left.take().unwrap();
+        y.left = x.right.take();
+        Self::update(&mut y);
+        x.right = Some(y);
+        Self::update(&mut x);
+        x
+    }
+
+    // rotate the subtree to the left and return its new root
+    fn left_rotate(mut y: AVLNode<T>) -> AVLNode<T> {
+        let mut x = y.right.take().unwrap();
+        y.right = x.left.take();
+        Self::update(&mut y);
+        x.left = Some(y);
+        Self::update(&mut x);
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::boxTree::size;
+    use crate::fixtures;
+
+    // check the AVL property and the stored heights and sizes, return the height
+    fn check_node<T: Ord>(node: &OptionAVLNode<T>) -> i32 {
+        let node = match node {
+            Some(node) => node,
+            None => return 0,
+        };
+        let left = check_node(&node.left);
+        let right = check_node(&node.right);
+        assert!((left - right).abs() <= 1);
+        assert_eq!(node.meta as i32, left.max(right) + 1);
+        assert_eq!(node.size, size(&node.left) + size(&node.right) + 1);
+        left.max(right) + 1
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(|tree: &BoxAVLTree<i32>| {
+            check_node(&tree.root);
+        });
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(|tree: &BoxAVLTree<i32>| {
+            check_node(&tree.root);
+        });
+    }
+}
@@ -0,0 +1,315 @@
+//! Persistent AVL tree
+//!
+//! You can generate an AVL tree whose `insert` and `delete` return a new version,
+//! while the older versions stay valid and share their unchanged subtrees with it.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+pub use crate::commonTrait::CommonTreeTrait;
+use crate::commonTrait::Comparator;
+use crate::dot::{self, DotStyle, ToDot};
+use crate::persistentTree::{OptionPersistentNode, PersistentNode};
+use crate::pretty::{self, Pretty};
+
+type AVLNode<T> = Rc<PersistentNode<T, u8>>;
+type OptionAVLNode<T> = OptionPersistentNode<T, u8>;
+
+/// Structure of PersistentAVLTree, one version of the tree
+#[derive(Debug)]
+pub struct PersistentAVLTree<T: Ord> {
+    root: OptionAVLNode<T>,
+}
+
+// cloning a version only shares its root
+impl<T: Ord> Clone for PersistentAVLTree<T> {
+    fn clone(&self) -> Self {
+        PersistentAVLTree {
+            root: self.root.clone(),
+        }
+    }
+}
+
+// extend from common tree trait
+impl<T: Ord> CommonTreeTrait<T> for PersistentAVLTree<T> {
+    type Handle<'a>
+        = &'a PersistentNode<T, u8>
+    where
+        Self: 'a;
+
+    fn get_root(&self) -> Option<&PersistentNode<T, u8>> {
+        self.root.as_deref()
+    }
+
+    // the values are ordered by `Ord`
+    fn get_comparator(&self) -> Comparator<T> {
+        Comparator::default()
+    }
+}
+
+// draw PersistentAVLTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for PersistentAVLTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        // the height helpers need T: Clone, so read the heights here
+        let height = |node: &OptionAVLNode<T>| node.as_ref().map_or(0, |node| node.meta.into());
+        dot::to_dot(
+            self.root.as_deref(),
+            nil_leaves.then(DotStyle::default),
+            |node| DotStyle::avl(height(&node.left), height(&node.right)),
+        )
+    }
+}
+
+// draw PersistentAVLTree as text
+impl<T: Ord + fmt::Debug> Pretty for PersistentAVLTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.root.as_deref(), |node| {
+            Some(format!("h={}", node.meta))
+        })
+    }
+}
+
+impl<T: Ord> Default for PersistentAVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implementations of PersistentAVLTree
+impl<T: Ord> PersistentAVLTree<T> {
+    /// Creates a new empty AVL tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::avlTreePersistent::PersistentAVLTree;
+    /// let tree: PersistentAVLTree<u32> = PersistentAVLTree::new();
+    /// ```
+    pub fn new() -> Self {
+        PersistentAVLTree { root: None }
+    }
+}
+
+impl<T: Ord + Clone> PersistentAVLTree<T> {
+    /// Return a new version with the value inserted, this version is not changed
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let empty = PersistentAVLTree::new();
+    /// let tree = empty.insert(1);
+    /// assert!(tree.contains(&1));
+    /// assert!(empty.is_empty());
+    /// ```
+    pub fn insert(&self, insert_value: T) -> Self {
+        if self.contains(&insert_value) {
+            return self.clone();
+        }
+        PersistentAVLTree {
+            root: Some(Self::node_insert(self.root.clone(), insert_value)),
+        }
+    }
+
+    /// Return a new version with the value deleted, this version is not changed
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let tree = PersistentAVLTree::new().insert(1);
+    /// assert!(tree.delete(&1).is_empty());
+    /// assert!(tree.contains(&1));
+    /// ```
+    pub fn delete(&self, delete_value: &T) -> Self {
+        if !self.contains(delete_value) {
+            return self.clone();
+        }
+        PersistentAVLTree {
+            root: Self::node_delete(self.root.clone().unwrap(), delete_value),
+        }
+    }
+
+    /// Inserts a value which is not in the subtree, return the new root of the subtree
+    fn node_insert(node: OptionAVLNode<T>, insert_value: T) -> AVLNode<T> {
+        let mut node = match node {
+            Some(node) => node,
+            // default height of a new node is 1, which is a leave
+            None => return PersistentNode::new(insert_value, 1),
+        };
+        // copy the node if an older version still refers to it
+        let current = Rc::make_mut(&mut node);
+        if insert_value < current.value {
+            current.left = Some(Self::node_insert(current.left.take(), insert_value));
+        } else {
+            current.right = Some(Self::node_insert(current.right.take(), insert_value));
+        }
+        Self::rebalance(node)
+    }
+
+    /// Deletes a value which is in the subtree, return the new root of the subtree
+    fn node_delete(mut node: AVLNode<T>, delete_value: &T) -> OptionAVLNode<T> {
+        let current = Rc::make_mut(&mut node);
+        match delete_value.cmp(&current.value) {
+            Ordering::Less => {
+                current.left = Self::node_delete(current.left.take().unwrap(), delete_value);
+            }
+            Ordering::Greater => {
+                current.right = Self::node_delete(current.right.take().unwrap(), delete_value);
+            }
+            // found the node which should be deleted
+            Ordering::Equal => {
+                let (left, right) = match (current.left.take(), current.right.take()) {
+                    (Some(left), Some(right)) => (left, right),
+                    // at most one child, which takes the place of the node
+                    (left, right) => return left.or(right),
+                };
+                // two children, the min node of the right subtree takes the place of the node
+                let (right, mut min_node) = Self::delete_min(right);
+                let min = Rc::make_mut(&mut min_node);
+                min.left = Some(left);
+                min.right = right;
+                return Some(Self::rebalance(min_node));
+            }
+        }
+        Some(Self::rebalance(node))
+    }
+
+    /// Detach the min node of the subtree, return the new root of the subtree
+    /// and the detached node
+    fn delete_min(mut node: AVLNode<T>) -> (OptionAVLNode<T>, AVLNode<T>) {
+        let current = Rc::make_mut(&mut node);
+        match current.left.take() {
+            None => (current.right.take(), node),
+            Some(left) => {
+                let (left, min_node) = Self::delete_min(left);
+                current.left = left;
+                (Some(Self::rebalance(node)), min_node)
+            }
+        }
+    }
+
+    /// Updates the height and rotates the node if it is unbalanced, return the new root
+    /// of the subtree
+    fn rebalance(mut node: AVLNode<T>) -> AVLNode<T> {
+        Self::update(&mut node);
+        let balance_factor = Self::get_balance_factor(&node);
+        // case LL and LR
+        if balance_factor > 1 {
+            let current = Rc::make_mut(&mut node);
+            let left = current.left.take().unwrap();
+            current.left = Some(if Self::get_balance_factor(&left) < 0 {
+                Self::left_rotate(left)
+            } else {
+                left
+            });
+            return Self::right_rotate(node);
+        }
+        // case RR and RL
+        if balance_factor < -1 {
+            let current = Rc::make_mut(&mut node);
+            let right = current.right.take().unwrap();
+            current.right = Some(if Self::get_balance_factor(&right) > 0 {
+                Self::right_rotate(right)
+            } else {
+                right
+            });
+            return Self::left_rotate(node);
+        }
+        node
+    }
+
+    fn get_height(node: &OptionAVLNode<T>) -> i32 {
+        // default height of an empty tree is 0
+        node.as_ref().map_or(0, |node| node.meta as i32)
+    }
+
+    fn get_balance_factor(node: &AVLNode<T>) -> i32 {
+        Self::get_height(&node.left) - Self::get_height(&node.right)
+    }
+
+    // recount the height and size of the node from its children
+    fn update(node: &mut AVLNode<T>) {
+        let height = Self::get_height(&node.left).max(Self::get_height(&node.right)) + 1;
+        let current = Rc::make_mut(node);
+        current.meta = height as u8;
+        current.update_size();
+    }
+
+    // rotate the subtree to the right and return its new root
+    fn right_rotate(mut y: AVLNode<T>) -> AVLNode<T> {
+        let mut x = Rc::make_mut(&mut y).left.take().unwrap();
+        Rc::make_mut(&mut y).left = Rc::make_mut(&mut x).right.take();
+        Self::update(&mut y);
+        Rc::make_mut(&mut x).right = Some(y);
+        Self::update(&mut x);
+        x
+    }
+
+    // rotate the subtree to the left and return its new root
+    fn left_rotate(mut y: AVLNode<T>) -> AVLNode<T> {
+        let mut x = Rc::make_mut(&mut y).right.take().unwrap();
+        Rc::make_mut(&mut y).right = Rc::make_mut(&mut x).left.take();
+        Self::update(&mut y);
+        Rc::make_mut(&mut x).left = Some(y);
+        Self::update(&mut x);
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures;
+    use crate::persistentTree::size;
+
+    // check the AVL property and the stored heights and sizes, return the height
+    fn check_node<T: Ord>(node: &OptionAVLNode<T>) -> i32 {
+        let node = match node {
+            Some(node) => node,
+            None => return 0,
+        };
+        let left = check_node(&node.left);
+        let right = check_node(&node.right);
+        assert!((left - right).abs() <= 1);
+        assert_eq!(node.meta as i32, left.max(right) + 1);
+        assert_eq!(node.size, size(&node.left) + size(&node.right) + 1);
+        left.max(right) + 1
+    }
+
+    #[test]
+    fn test_snapshots() {
+        fixtures::check_snapshots(
+            |tree: &PersistentAVLTree<i32>, value| tree.insert(value),
+            |tree, value| tree.delete(value),
+            |tree| {
+                check_node(&tree.root);
+            },
+        );
+    }
+
+    #[test]
+    fn test_structural_sharing() {
+        let mut tree = PersistentAVLTree::new();
+        for value in 0..1000 {
+            tree = tree.insert(value);
+        }
+        let newer = tree.insert(1000);
+        // the new version shares the whole left subtree of the root
+        let old_root = tree.root.as_ref().unwrap();
+        let new_root = newer.root.as_ref().unwrap();
+        assert!(Rc::ptr_eq(
+            old_root.left.as_ref().unwrap(),
+            new_root.left.as_ref().unwrap()
+        ));
+        assert!(!tree.contains(&1000));
+        assert!(newer.contains(&1000));
+        // an insert or delete without effect returns the same version
+        assert!(Rc::ptr_eq(old_root, tree.insert(0).root.as_ref().unwrap()));
+        assert!(Rc::ptr_eq(
+            old_root,
+            tree.delete(&2000).root.as_ref().unwrap()
+        ));
+    }
+}
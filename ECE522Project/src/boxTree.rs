@@ -0,0 +1,75 @@
+//! Owned nodes for trees
+//!
+//! Nodes of the box-based trees own their children through `Option<Box<...>>` instead of
+//! sharing them through `Rc<RefCell<...>>`, so reading or changing a node needs no
+//! reference counting or runtime borrow checks. The trees keep no parent pointers.
+
+use crate::commonTrait::OwnedNode;
+use crate::nodeHandle::NodeHandle;
+
+/// Child or root of a box-based tree, `None` if the subtree is empty
+pub type OptionBoxNode<T, M> = Option<Box<BoxNode<T, M>>>;
+
+/// Node owned by its parent, `M` is the balancing data of the tree
+#[derive(Clone, Debug)]
+pub struct BoxNode<T, M> {
+    /// Data stored in the node
+    pub value: T,
+    pub left: OptionBoxNode<T, M>,
+    pub right: OptionBoxNode<T, M>,
+    /// Number of nodes in the subtree rooted at this node
+    pub size: usize,
+    /// Height for AVL trees or color for red-black trees
+    pub meta: M,
+}
+
+/// Implementations of BoxNode
+impl<T, M> BoxNode<T, M> {
+    /// Create a new leaf node
+    pub fn new(value: T, meta: M) -> Box<Self> {
+        Box::new(BoxNode {
+            value,
+            left: None,
+            right: None,
+            size: 1,
+            meta,
+        })
+    }
+
+    /// Recount the subtree size of the node from its children
+    pub fn update_size(&mut self) {
+        self.size = size(&self.left) + size(&self.right) + 1;
+    }
+}
+
+// a box node is reached through a plain reference
+impl<T, M> NodeHandle<T> for &BoxNode<T, M> {
+    fn get_left(&self) -> Option<Self> {
+        self.left.as_deref()
+    }
+
+    fn get_right(&self) -> Option<Self> {
+        self.right.as_deref()
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn with_value<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        f(&self.value)
+    }
+}
+
+// a box node is moved out of its parent
+impl<T, M> OwnedNode<T> for Box<BoxNode<T, M>> {
+    fn split(self, _: &mut ()) -> (Option<Self>, T, Option<Self>) {
+        let node = *self;
+        (node.left, node.value, node.right)
+    }
+}
+
+/// Size of the subtree, 0 for an empty one
+pub fn size<T, M>(node: &OptionBoxNode<T, M>) -> usize {
+    node.as_ref().map_or(0, |node| node.size)
+}
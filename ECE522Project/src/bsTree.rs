@@ -2,31 +2,35 @@
 //!
 //! You can generate a binary search tree, and insert or delete nodes.
 
-use std::cell::RefCell;
-use std::cmp::max;
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
 use std::fmt;
+use std::iter::FromIterator;
 use std::rc::Rc;
 
-pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait};
-
-#[derive(Clone, Debug, PartialEq)]
-enum NodeDirection {
-    Left,
-    Right,
-}
+use crate::commonTrait::{
+    clone_nodes, collect_sorted, dedup_sorted, drop_nodes, is_sorted, Comparator, IntoIter, Iter,
+};
+pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait, OrderedSet};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+use crate::treeMap::MapBackend;
 
 /// Structure of BSTree
-#[derive(Clone, Debug, PartialEq)]
-pub struct BSTree<T: Ord + Copy + fmt::Debug> {
+#[derive(Debug, PartialEq)]
+pub struct BSTree<T: Ord> {
     root: OptionBSTreeNode<T>,
+    comparator: Comparator<T>,
 }
 
 /// Node struct for [BSTree](struct.BSTree.html) struct
 #[derive(Clone, Debug, PartialEq)]
-pub struct TreeNode<T: Ord + Copy + fmt::Debug> {
+pub struct TreeNode<T: Ord> {
     value: T,
     left: OptionBSTreeNode<T>,
     right: OptionBSTreeNode<T>,
+    /// Number of nodes in the subtree rooted at this node
+    size: usize,
 }
 
 type BSTreeNode<T> = Rc<RefCell<TreeNode<T>>>;
@@ -34,14 +38,23 @@ type OptionBSTreeNode<T> = Option<BSTreeNode<T>>;
 
 
 // extend from common tree trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeTrait<T, TreeNode<T>> for BSTree<T> {
+impl<T: Ord> CommonTreeTrait<T> for BSTree<T> {
+    type Handle<'a>
+        = BSTreeNode<T>
+    where
+        Self: 'a;
+
     fn get_root(&self) -> OptionBSTreeNode<T> {
         return self.root.clone();
     }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
 }
 
 // extend from common tree node trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
+impl<T: Ord> CommonTreeNodeTrait<T> for TreeNode<T> {
     fn get_left(&self) -> OptionBSTreeNode<T> {
         return self.left.clone();
     }
@@ -50,45 +63,228 @@ impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
         return self.right.clone();
     }
 
-    fn get_value(&self) -> T {
-        return self.value;
+    fn get_value(&self) -> &T {
+        &self.value
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn take_left(&mut self) -> OptionBSTreeNode<T> {
+        self.left.take()
+    }
+
+    fn take_right(&mut self) -> OptionBSTreeNode<T> {
+        self.right.take()
+    }
+
+    fn into_value(self) -> T {
+        self.value
+    }
+}
+
+// free the nodes without recursing through them
+impl<T: Ord> Drop for BSTree<T> {
+    fn drop(&mut self) {
+        drop_nodes(self.root.take());
+    }
+}
+
+// copy every node, so that changing the copy leaves the original alone
+impl<T: Ord + Clone> Clone for BSTree<T> {
+    fn clone(&self) -> Self {
+        let root = clone_nodes(&self.root, |node, left, right| {
+            Rc::new(RefCell::new(TreeNode {
+                value: node.value.clone(),
+                left,
+                right,
+                size: node.size,
+            }))
+        });
+        BSTree {
+            root,
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for BSTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, BSTreeNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter::new(self.root.take(), len)
+    }
+}
+
+// iterate over the tree in order
+impl<'a, T: Ord + Clone> IntoIterator for &'a BSTree<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, BSTreeNode<T>>;
+
+    fn into_iter(self) -> Iter<'a, T, BSTreeNode<T>> {
+        self.iter()
+    }
+}
+
+// build the tree from values in ascending order directly, or insert them one by one
+impl<T: Ord> FromIterator<T> for BSTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = BSTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+// values in ascending order are built into the tree directly if it is empty,
+// otherwise they are inserted one by one, since BSTree cannot join two trees
+impl<T: Ord> Extend<T> for BSTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let values: Vec<T> = iter.into_iter().collect();
+        if self.root.is_none() && is_sorted(&values, &self.comparator) {
+            let values = dedup_sorted(values, &self.comparator);
+            *self = Self::from_sorted_values(values, self.comparator.clone());
+        } else {
+            for value in values {
+                self.insert(value);
+            }
+        }
+    }
+}
+
+// construct and mutate BSTree in generic code
+impl<T: Ord> OrderedSet<T> for BSTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        BSTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        BSTree {
+            root: None,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        BSTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        BSTree::delete(self, value)
+    }
+}
+
+// back a TreeMap with BSTree
+impl<T: Ord> MapBackend<T> for BSTree<T> {
+    type Node = TreeNode<T>;
+
+    fn get_root_node(&self) -> OptionBSTreeNode<T> {
+        self.root.clone()
+    }
+
+    fn borrow_value(node: &Rc<RefCell<TreeNode<T>>>) -> Ref<'_, T> {
+        Ref::map(node.borrow(), |node| &node.value)
+    }
+
+    fn borrow_value_mut(node: &Rc<RefCell<TreeNode<T>>>) -> RefMut<'_, T> {
+        RefMut::map(node.borrow_mut(), |node| &mut node.value)
+    }
+
+    fn replace(&mut self, value: T) -> Option<T> {
+        let root = self.root.clone();
+        match root {
+            None => {
+                self.root = Some(Rc::new(RefCell::new(TreeNode::new(value))));
+                None
+            }
+            Some(root) => TreeNode::node_insert(root, value, &self.comparator),
+        }
+    }
+
+    fn remove_node<F: Fn(&T) -> Ordering>(&mut self, cmp: &F) -> OptionBSTreeNode<T> {
+        TreeNode::node_delete(&mut self.root, cmp)
+    }
+}
+
+// draw BSTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for BSTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        dot::to_dot(self.get_root(), nil_leaves.then(DotStyle::default), |_| {
+            DotStyle::default()
+        })
+    }
+}
+
+// draw BSTree as text
+impl<T: Ord + fmt::Debug> Pretty for BSTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.get_root(), |_| None)
+    }
+}
+
+impl<T: Ord> Default for BSTree<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Implementations of BSTree
 // BSTree
-impl<T: Ord + Copy + fmt::Debug> BSTree<T> {
-
+impl<T: Ord> BSTree<T> {
     /// Create a new Binary Search Tree
     ///
     /// # Example
     ///
     /// ```
     /// use tree_collections::bsTree::BSTree;
-    /// let mut bst = BSTree::new();
+    /// let mut bst: BSTree<u32> = BSTree::new();
     /// ```
     pub fn new() -> Self {
-        BSTree { root: None }
+        BSTree {
+            root: None,
+            comparator: Comparator::default(),
+        }
     }
 
-    /// Insert a new value to the BSTree
+    /// Create a new Binary Search Tree ordered by the comparator instead of `Ord`,
+    /// which tells how the first value compares with the second one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut bst = BSTree::with_comparator(|a: &String, b: &String| {
+    ///     a.to_lowercase().cmp(&b.to_lowercase())
+    /// });
+    /// bst.insert("Apple".to_string());
+    /// assert!(!bst.insert("APPLE".to_string()));
+    /// assert!(bst.contains(&"apple".to_string()));
+    /// ```
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        BSTree {
+            root: None,
+            comparator: Comparator::new(cmp),
+        }
+    }
+
+    /// Insert a new value to the BSTree, return false if the value already exists
     ///
     /// # Example
     ///
     /// ```
     /// use tree_collections::bsTree::BSTree;
     /// let mut bst = BSTree::new();
-    /// bst.insert(1);
+    /// assert!(bst.insert(1));
+    /// assert!(!bst.insert(1));
     /// ```
-    pub fn insert(&mut self, insert_value: T) {
-        let root = self.get_root();
-        match root {
-            None => self.root = Some(Rc::new(RefCell::new(TreeNode::new(insert_value)))),
-            Some(root) => TreeNode::node_insert(root, insert_value),
-        }
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        self.replace(insert_value).is_none()
     }
 
-    /// Delete a value from the tree
+    /// Delete a value from the BSTree, return false if the value does not exist
     ///
     /// # Example
     ///
@@ -96,180 +292,187 @@ impl<T: Ord + Copy + fmt::Debug> BSTree<T> {
     /// use tree_collections::bsTree::BSTree;
     /// let mut bst = BSTree::new();
     /// bst.insert(1);
-    /// bst.delete(1);
+    /// assert!(bst.delete(&1));
+    /// assert!(!bst.delete(&1));
     /// ```
-    pub fn delete(&mut self, delete_value: T) {
-        let root = self.get_root();
-        match root {
-            None => (),
-            Some(root) => {
-                if root.borrow().get_value() > delete_value {
-                    TreeNode::node_delete_left(root.clone(), delete_value);
-                } else if root.borrow().get_value() < delete_value {
-                    TreeNode::node_delete_right(root.clone(), delete_value);
-                } else {
-                    let left = root.borrow().get_left();
-                    let right = root.borrow().get_right();
-                    // if delete root and root does not has left or right
-                    if left.is_none() && right.is_none() {
-                        self.root = None;
-                    }
-                    // if delete root and root only has left
-                    else if left.is_some() && right.is_none() {
-                        self.root = left;
-                    }
-                    // if delete root and root only has right
-                    else if left.is_none() && right.is_some() {
-                        self.root = right;
-                    }
-                    // if delete root and root has left and right
-                    else {
-                        let min_of_right =
-                            right.clone().unwrap().borrow().get_min_value_in_children();
-                        self.root.clone().unwrap().borrow_mut().value = min_of_right;
-                        TreeNode::node_delete_right(root, min_of_right);
-                    }
-                }
-            }
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        let comparator = self.comparator.clone();
+        self.remove_node(&|value: &T| comparator.compare(delete_value, value))
+            .is_some()
+    }
+}
+
+/// Building BSTree from sorted values in O(n) time
+impl<T: Ord> BSTree<T> {
+    /// Build a balanced tree from values in ascending order without inserting them
+    /// one by one, keeping the last one of repeated values
+    ///
+    /// Panics if the values are not in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let tree = BSTree::from_sorted_iter(0..100);
+    /// assert_eq!(100, tree.len());
+    /// assert_eq!(7, tree.height());
+    /// ```
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted_values(collect_sorted(iter), Comparator::default())
+    }
+
+    // build a balanced tree from values in strictly ascending order of the comparator
+    fn from_sorted_values(values: Vec<T>, comparator: Comparator<T>) -> Self {
+        let len = values.len();
+        let root = Self::build_sorted(&mut values.into_iter(), len);
+        BSTree { root, comparator }
+    }
+
+    // build a subtree of the next len values, splitting them in the middle
+    fn build_sorted(values: &mut std::vec::IntoIter<T>, len: usize) -> OptionBSTreeNode<T> {
+        if len == 0 {
+            return None;
         }
+        let left_len = (len - 1) / 2;
+        let left = Self::build_sorted(values, left_len);
+        let value = values.next().unwrap();
+        let right = Self::build_sorted(values, len - 1 - left_len);
+        Some(Rc::new(RefCell::new(TreeNode {
+            value,
+            left,
+            right,
+            size: len,
+        })))
     }
 }
 
 /// Implementations of BSTree node
 // TreeNode
-impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
-
-    /// Create an new node, 
+impl<T: Ord> TreeNode<T> {
+    /// Create an new node,
     /// which will be called by [BSTree](struct.BSTree.html)
     fn new(value: T) -> Self {
         TreeNode {
-            value: value,
+            value,
             left: None,
             right: None,
+            size: 1,
         }
     }
 
-    /// Insert a node, which will be called by
+    /// Insert a node and return the replaced equal value, which will be called by
     /// [BSTree.insert](struct.BSTree.html#method.insert)
-    fn node_insert(node: BSTreeNode<T>, insert_value: T) {
-        if node.borrow().get_value() > insert_value {
-            let left = node.borrow().left.clone();
-            match left {
-                Some(left) => {
-                    Self::node_insert(left, insert_value);
+    // the tree may be a long chain, so walk down it in a loop instead of recursing
+    fn node_insert(node: BSTreeNode<T>, insert_value: T, comparator: &Comparator<T>) -> Option<T> {
+        // nodes whose subtree gets the new node
+        let mut path = vec![];
+        let mut current = node;
+        loop {
+            let ordering = comparator.compare(&insert_value, &current.borrow().value);
+            let next = match ordering {
+                Ordering::Less => current.borrow().left.clone(),
+                Ordering::Greater => current.borrow().right.clone(),
+                // equal, update value
+                Ordering::Equal => {
+                    return Some(std::mem::replace(
+                        &mut current.borrow_mut().value,
+                        insert_value,
+                    ))
                 }
+            };
+            match next {
+                Some(next) => path.push(std::mem::replace(&mut current, next)),
                 None => {
-                    node.borrow_mut().left =
-                        Some(Rc::new(RefCell::new(TreeNode::new(insert_value))));
-                }
-            }
-        } else if node.borrow().get_value() < insert_value {
-            let right = node.borrow().right.clone();
-            match right {
-                Some(right) => {
-                    Self::node_insert(right, insert_value);
-                }
-                None => {
-                    node.borrow_mut().right =
-                        Some(Rc::new(RefCell::new(TreeNode::new(insert_value))));
-                    let _right = node.borrow().get_right();
+                    let new_node = Some(Rc::new(RefCell::new(TreeNode::new(insert_value))));
+                    if ordering == Ordering::Less {
+                        current.borrow_mut().left = new_node;
+                    } else {
+                        current.borrow_mut().right = new_node;
+                    }
+                    path.push(current);
+                    break;
                 }
             }
-        } else {
-            return;
         }
+        // a new node is added to the subtrees
+        for node in path {
+            node.borrow_mut().size += 1;
+        }
+        None
     }
 
-    // Helper function for deleting
-    fn node_delete_left(parent: BSTreeNode<T>, delete_value: T) {
-        let curr_node = parent.borrow().get_left();
-        match curr_node {
-            None => (),
-            Some(curr_node) => {
-                if curr_node.borrow().get_value() > delete_value {
-                    Self::node_delete_left(curr_node, delete_value);
-                } else if curr_node.borrow().get_value() < delete_value {
-                    Self::node_delete_right(curr_node, delete_value);
-                } else {
-                    let left_node = curr_node.borrow_mut().get_left();
-                    let right_node = curr_node.borrow_mut().get_right();
-                    // 1. current node has two children
-                    // if current node has two children, then recursively replace it with the min value of right
-                    // delete the min value of right in the right tree
-                    // the goal is to make the problem to be the case where current node has only one child
-                    if left_node.is_some() && right_node.is_some() {
-                        let min_of_right = right_node
-                            .clone()
-                            .unwrap()
-                            .borrow()
-                            .get_min_value_in_children();
-                        curr_node.borrow_mut().value = min_of_right;
-                        Self::node_delete_right(curr_node, min_of_right);
-                    }
-                    // 2. current node has no child
-                    else if left_node.is_none() && right_node.is_none() {
-                        parent.borrow_mut().left = None;
-                    }
-                    // 3.1 current node has one left child
-                    else if left_node.is_some() && right_node.is_none() {
-                        parent.borrow_mut().left = left_node;
-                    }
-                    // 3.2 current node has one right child
-                    else {
-                        parent.borrow_mut().left = right_node;
-                    }
+    /// Delete a node from the tree with the given root and return the detached node,
+    /// which will be called by [BSTree.delete](struct.BSTree.html#method.delete)
+    // the tree may be a long chain, so walk down it in a loop instead of recursing
+    fn node_delete<F: Fn(&T) -> Ordering>(
+        root: &mut OptionBSTreeNode<T>,
+        cmp: &F,
+    ) -> OptionBSTreeNode<T> {
+        // nodes whose subtree loses a node
+        let mut path = vec![];
+        let mut curr_node = root.clone()?;
+        loop {
+            let next = match cmp(&curr_node.borrow().value) {
+                Ordering::Less => curr_node.borrow().left.clone(),
+                Ordering::Greater => curr_node.borrow().right.clone(),
+                Ordering::Equal => break,
+            };
+            path.push(std::mem::replace(&mut curr_node, next?));
+        }
+        let left_node = curr_node.borrow().left.clone();
+        let right_node = curr_node.borrow().right.clone();
+        // 1. current node has two children
+        // if current node has two children, then swap its value with the min value of right
+        // and delete the node of the min value instead, which has no left child
+        if let (Some(_), Some(right_node)) = (left_node, right_node) {
+            path.push(curr_node.clone());
+            let mut min_of_right = right_node;
+            loop {
+                let left = min_of_right.borrow().left.clone();
+                match left {
+                    Some(left) => path.push(std::mem::replace(&mut min_of_right, left)),
+                    None => break,
                 }
             }
+            std::mem::swap(
+                &mut curr_node.borrow_mut().value,
+                &mut min_of_right.borrow_mut().value,
+            );
+            curr_node = min_of_right;
         }
-    }
-
-    // Helper function for deleting
-    fn node_delete_right(parent: BSTreeNode<T>, delete_value: T) {
-        let curr_node = parent.borrow().get_right();
-        match curr_node {
-            None => (),
-            Some(curr_node) => {
-                if curr_node.borrow().get_value() > delete_value {
-                    Self::node_delete_left(curr_node, delete_value);
-                } else if curr_node.borrow().get_value() < delete_value {
-                    Self::node_delete_right(curr_node, delete_value);
+        // 2. current node has at most one child, move the child to the current node place
+        let child = {
+            let mut node = curr_node.borrow_mut();
+            let left = node.left.take();
+            left.or_else(|| node.right.take())
+        };
+        match path.last() {
+            None => *root = child,
+            Some(parent) => {
+                let mut parent = parent.borrow_mut();
+                let is_left = match &parent.left {
+                    Some(left) => Rc::ptr_eq(left, &curr_node),
+                    None => false,
+                };
+                if is_left {
+                    parent.left = child;
                 } else {
-                    let left_node = curr_node.borrow_mut().get_left();
-                    let right_node = curr_node.borrow_mut().get_right();
-                    // 1. current node has two children
-                    // if current node has two children, then recursively replace it with the min value of right
-                    // delete the min value of right in the right tree
-                    // the goal is to make the problem to be the case where current node has only one child
-                    if left_node.is_some() && right_node.is_some() {
-                        let min_of_right = right_node
-                            .clone()
-                            .unwrap()
-                            .borrow()
-                            .get_min_value_in_children();
-                        curr_node.borrow_mut().value = min_of_right;
-                        Self::node_delete_right(curr_node, min_of_right);
-                    }
-                    // 2. current node has no child
-                    else if left_node.is_none() && right_node.is_none() {
-                        parent.borrow_mut().right = None;
-                    }
-                    // 3.1 current node has one left child
-                    else if left_node.is_some() && right_node.is_none() {
-                        parent.borrow_mut().right = left_node;
-                    }
-                    // 3.2 current node has one right child
-                    else {
-                        parent.borrow_mut().right = right_node;
-                    }
+                    parent.right = child;
                 }
             }
         }
+        // a node is removed from the subtrees
+        for node in path {
+            node.borrow_mut().size -= 1;
+        }
+        Some(curr_node)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fixtures;
     #[test]
     fn test_insert() {
         let mut tree = BSTree::new();
@@ -292,7 +495,7 @@ mod test {
         vec![16, 16, 8, 24, 20, 22].iter().for_each(|v| {
             tree.insert(*v);
         });
-        tree.delete(16);
+        tree.delete(&16);
         let mut in_container = vec![];
         let mut pre_container = vec![];
         tree.in_order_traversal_for_test(&mut in_container);
@@ -300,4 +503,71 @@ mod test {
         assert_eq!(in_container, vec![0, 8, 20, 22, 24]);
         assert_eq!(pre_container, vec![0, 20, 8, 24, 22]);
     }
+
+    // check the sizes of every node, return the size of the subtree
+    fn check_size<T: Ord>(node: &OptionBSTreeNode<T>) -> usize {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return 0,
+        };
+        let size = check_size(&node.left) + check_size(&node.right) + 1;
+        assert_eq!(node.size, size);
+        size
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(|tree: &BSTree<i32>| {
+            check_size(&tree.root);
+        });
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        // link a chain of sorted values directly, inserting them one by one takes quadratic time
+        let n = 1_000_000;
+        let mut chain = None;
+        for value in (0..n).rev() {
+            let mut node = TreeNode::new(value);
+            node.size = n - value;
+            node.right = chain;
+            chain = Some(Rc::new(RefCell::new(node)));
+        }
+        let mut tree = BSTree::new();
+        tree.root = chain;
+        assert_eq!(tree.len(), n);
+        assert_eq!(tree.height(), n as u32);
+        assert_eq!(tree.count_leaves(), 1);
+        assert!(tree.contains(&(n - 1)));
+        assert_eq!(tree.max(), Some(n - 1));
+        assert!(tree.insert(n));
+        assert!(tree.delete(&(n / 2)));
+        assert_eq!(tree.len(), n);
+        // copying and dropping the chain must not overflow the stack
+        let copy = tree.clone();
+        assert_eq!(copy.height(), n as u32);
+        drop(tree);
+        // and so must dropping an owning iterator over it
+        let mut values = copy.into_iter();
+        assert_eq!(values.next(), Some(0));
+        assert_eq!(values.len(), n - 1);
+        drop(values);
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for len in 0..70usize {
+            let tree = BSTree::from_sorted_iter(0..len);
+            check_size(&tree.root);
+            // as low as a complete tree of the same size
+            assert_eq!(tree.height(), usize::BITS - len.leading_zeros());
+        }
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(|tree: &BSTree<i32>| {
+            check_size(&tree.root);
+        });
+    }
 }
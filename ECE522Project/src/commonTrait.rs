@@ -3,38 +3,50 @@
 //! Provides common functions for trees and nodes.
 
 use std::cell::RefCell;
-use std::cmp::max;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::fmt;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
 use std::rc::Rc;
+use std::sync::Arc;
 
-/// Provide common functions for trees
+use crate::nodeHandle::{self, LevelOrderIter, NodeHandle, Order};
+
+/// Provide common functions for trees, whatever the storage of their nodes is
 // Common trait for Tree
-pub trait CommonTreeTrait<T: Ord + Copy + fmt::Debug, TreeNode: CommonTreeNodeTrait<T>> {
-    fn get_root(&self) -> Option<Rc<RefCell<TreeNode>>>;
+pub trait CommonTreeTrait<T: Ord> {
+    /// Handle to a node of the tree, like `Rc<RefCell<...>>` or a reference to a `Box`ed
+    /// node, through which the common functions read the tree
+    type Handle<'a>: NodeHandle<T> + Clone
+    where
+        Self: 'a;
+
+    /// Get a handle to the root node, `None` if the tree is empty
+    fn get_root(&self) -> Option<Self::Handle<'_>>;
 
-    /// Counts leaves(None nodes) of the Tree
+    /// Get the comparator ordering the values of the Tree
+    fn get_comparator(&self) -> Comparator<T>;
+
+    /// Counts leaves (nodes without children) of the Tree
     ///
     /// # Example
     ///
     /// ```
-    /// use tree_collections::commonTrait::CommonTreeTrait;
-    /// let mut tree = CommonTreeTrait::new();
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
+    /// assert_eq!(0, tree.count_leaves());
     /// tree.insert(1);
-    /// println!("{}", tree.count_leaves());  // 2
+    /// assert_eq!(1, tree.count_leaves());
     /// tree.insert(2);
-    /// println!("{}", tree.count_leaves());  // 3
+    /// assert_eq!(1, tree.count_leaves());
     /// tree.insert(3);
-    /// println!("{}", tree.count_leaves());  // 4
-    ///
-    /// let mut leaf_number = tree.count_leaves();
-    /// assert_eq!(4, leaf_number);
+    /// assert_eq!(2, tree.count_leaves());
     /// ```
-    // count the leaves (None nodes)
+    // count the leaves (nodes without children)
     fn count_leaves(&self) -> u32 {
-        match self.get_root() {
-            None => 0,
-            Some(node) => node.borrow().count_leaves(),
-        }
+        nodeHandle::count_leaves(self.get_root())
     }
 
     /// Gets height of the Tree (from root to leaves)
@@ -42,20 +54,17 @@ pub trait CommonTreeTrait<T: Ord + Copy + fmt::Debug, TreeNode: CommonTreeNodeTr
     /// # Example
     ///
     /// ```
-    /// use tree_collections::commonTrait::CommonTreeTrait;
-    /// let mut tree = CommonTreeTrait::new();
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::new();
     /// assert_eq!(0, tree.height());
     /// tree.insert(1);
+    /// assert_eq!(1, tree.height());
+    /// tree.insert(2);
     /// assert_eq!(2, tree.height());
-    ///tree.insert(2);
-    /// assert_eq!(3, tree.height());
     /// ```
     // from root to leaves
     fn height(&self) -> u32 {
-        match self.get_root() {
-            None => 0,
-            Some(node) => node.borrow().get_height(),
-        }
+        nodeHandle::height(self.get_root())
     }
 
     /// Prints Tree inorder
@@ -63,88 +72,135 @@ pub trait CommonTreeTrait<T: Ord + Copy + fmt::Debug, TreeNode: CommonTreeNodeTr
     /// # Example
     ///
     /// ```
-    /// use tree_collections::commonTrait::CommonTreeTrait;
-    /// let mut tree = CommonTreeTrait::new();
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
     /// tree.insert(1);
     /// tree.insert(3);
     /// tree.insert(5);
     /// tree.insert(2);
     /// tree.insert(4);
-    /// tree.in_order_traversal(); // Inorder traversal: 1 2 3 4 5
-    ///
-    fn in_order_traversal(&self) {
+    /// tree.in_order_traversal(); // 1 2 3 4 5
+    /// ```
+    fn in_order_traversal(&self)
+    where
+        T: fmt::Debug,
+    {
         match self.get_root() {
             None => println!("There is no node in the tree!"),
-            Some(node) => {
-                node.borrow().in_order_traversal();
+            Some(_) => {
+                self.traverse(Order::InOrder, |value, _| print!("{:?} ", value));
                 println!();
             }
         }
     }
 
-    fn in_order_traversal_for_test(&self, container: &mut Vec<T>) {
+    fn in_order_traversal_for_test(&self, container: &mut Vec<T>)
+    where
+        T: Clone,
+    {
         match self.get_root() {
             None => println!("There is no node in the tree!"),
-            Some(node) => {
-                node.borrow().in_order_traversal_for_test(container);
-            }
+            Some(_) => self.traverse(Order::InOrder, |value, _| container.push(value.clone())),
         }
     }
 
-    /// Prints red-black tree preorder
+    /// Prints Tree preorder
     ///
     /// # Example
     ///
     /// ```
-    /// use tree_collections::commonTrait::CommonTreeTrait;
-    /// let mut tree = CommonTreeTrait::new();
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
     /// tree.insert(1);
     /// tree.insert(3);
     /// tree.insert(5);
     /// tree.insert(2);
     /// tree.insert(4);
-    /// tree.preorder_traversal(); // Preorder traversal: 3 2 1 5 4
-    ///
-    fn pre_order_traversal(&self) {
+    /// tree.pre_order_traversal(); // 3 1 2 5 4
+    /// ```
+    fn pre_order_traversal(&self)
+    where
+        T: fmt::Debug,
+    {
         match self.get_root() {
             None => println!("There is no node in the tree!"),
-            Some(node) => {
-                node.borrow().pre_order_traversal();
+            Some(_) => {
+                self.traverse(Order::PreOrder, |value, _| print!("{:?} ", value));
                 println!();
             }
         }
     }
 
-    fn pre_order_traversal_for_test(&self, container: &mut Vec<T>) {
+    fn pre_order_traversal_for_test(&self, container: &mut Vec<T>)
+    where
+        T: Clone,
+    {
         match self.get_root() {
             None => println!("There is no node in the tree!"),
-            Some(node) => {
-                node.borrow().pre_order_traversal_for_test(container);
-            }
+            Some(_) => self.traverse(Order::PreOrder, |value, _| container.push(value.clone())),
         }
     }
 
+    /// Calls `f` with the value and the depth of every node in the given order without
+    /// printing anything, the depth of the root is 0
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
+    /// for value in 1..6 {
+    ///     tree.insert(value);
+    /// }
+    /// let mut visited = vec![];
+    /// tree.traverse(Order::PostOrder, |value, depth| visited.push((*value, depth)));
+    /// assert_eq!(vec![(1, 1), (3, 2), (5, 2), (4, 1), (2, 0)], visited);
+    /// ```
+    fn traverse<F: FnMut(&T, usize)>(&self, order: Order, f: F) {
+        nodeHandle::traverse(self.get_root(), order, f)
+    }
+
+    /// Gets an iterator over the values of the Tree level by level from the root,
+    /// each level from left to right
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::new();
+    /// for value in 1..6 {
+    ///     tree.insert(value);
+    /// }
+    /// assert_eq!(vec![2, 1, 4, 3, 5], tree.level_order().collect::<Vec<_>>());
+    /// ```
+    fn level_order(&self) -> LevelOrderIter<T, Self::Handle<'_>>
+    where
+        T: Clone,
+    {
+        LevelOrderIter::new(self.get_root())
+    }
+
     /// Determine whether the tree contains given value
     ///
     /// # Example
     ///
     /// ```
-    /// use tree_collections::commonTrait::CommonTreeTrait;
-    /// let mut tree = CommonTreeTrait::new();
+    /// use tree_collections::prelude::*;
+    /// let mut tree = BSTree::new();
     /// tree.insert(1);
-    /// assert_eq!(true, tree.contains(1));
-    /// assert_eq!(false, tree.contains(0));
-    ///
-    fn contains(&self, value: T) -> bool {
-        match self.get_root() {
-            None => false,
-            Some(node) => node.borrow().contains(value),
-        }
+    /// assert_eq!(true, tree.contains(&1));
+    /// assert_eq!(false, tree.contains(&0));
+    /// ```
+    fn contains(&self, value: &T) -> bool {
+        let comparator = self.get_comparator();
+        nodeHandle::contains(self.get_root(), value, &|a: &T, b: &T| {
+            comparator.compare(a, b)
+        })
     }
 
     // judge if the tree is empty
     fn is_tree_empty(&self) -> bool {
-        self.get_root().map(|_| false).unwrap_or(true)
+        self.get_root().is_none()
     }
 
     // judge if the tree is BST
@@ -158,33 +214,607 @@ pub trait CommonTreeTrait<T: Ord + Copy + fmt::Debug, TreeNode: CommonTreeNodeTr
     //     }
     // }
 
-    fn min(&self) -> Option<T> {
-        match self.get_root() {
-            None => None,
-            Some(node) => Some(node.borrow().get_min_value_in_children()),
+    fn min(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        nodeHandle::min(self.get_root())
+    }
+
+    fn max(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        nodeHandle::max(self.get_root())
+    }
+
+    /// Gets the number of values in the Tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
+    /// assert_eq!(0, tree.len());
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(2, tree.len());
+    /// ```
+    fn len(&self) -> usize {
+        nodeHandle::size(&self.get_root())
+    }
+
+    /// Determine whether the Tree has no values
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = BSTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert(1);
+    /// assert!(!tree.is_empty());
+    /// ```
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of values in the Tree less than the given value,
+    /// which is the position of the value if it is in the Tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::new();
+    /// tree.insert(10);
+    /// tree.insert(20);
+    /// tree.insert(30);
+    /// assert_eq!(1, tree.rank(&20));
+    /// assert_eq!(2, tree.rank(&25));
+    /// ```
+    fn rank(&self, value: &T) -> usize {
+        let comparator = self.get_comparator();
+        nodeHandle::rank(self.get_root(), value, &|a: &T, b: &T| {
+            comparator.compare(a, b)
+        })
+    }
+
+    /// Gets the k-th smallest value of the Tree, counting from 0
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = FastRBTree::new();
+    /// tree.insert(10);
+    /// tree.insert(20);
+    /// tree.insert(30);
+    /// assert_eq!(Some(20), tree.select(1));
+    /// assert_eq!(None, tree.select(3));
+    /// ```
+    fn select(&self, k: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        nodeHandle::select(self.get_root(), k)
+    }
+
+    /// Gets the largest value less than or equal to the given value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(10);
+    /// tree.insert(20);
+    /// assert_eq!(Some(10), tree.floor(&15));
+    /// assert_eq!(Some(20), tree.floor(&20));
+    /// assert_eq!(None, tree.floor(&5));
+    /// ```
+    fn floor(&self, value: &T) -> Option<T>
+    where
+        T: Clone,
+    {
+        find_closest(
+            self.get_root(),
+            value,
+            &self.get_comparator(),
+            Ordering::Less,
+            true,
+        )
+    }
+
+    /// Gets the smallest value greater than or equal to the given value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(10);
+    /// tree.insert(20);
+    /// assert_eq!(Some(20), tree.ceiling(&15));
+    /// assert_eq!(Some(10), tree.ceiling(&10));
+    /// assert_eq!(None, tree.ceiling(&25));
+    /// ```
+    fn ceiling(&self, value: &T) -> Option<T>
+    where
+        T: Clone,
+    {
+        find_closest(
+            self.get_root(),
+            value,
+            &self.get_comparator(),
+            Ordering::Greater,
+            true,
+        )
+    }
+
+    /// Gets the largest value less than the given value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::new();
+    /// tree.insert(10);
+    /// tree.insert(20);
+    /// assert_eq!(Some(10), tree.predecessor(&20));
+    /// assert_eq!(None, tree.predecessor(&10));
+    /// ```
+    fn predecessor(&self, value: &T) -> Option<T>
+    where
+        T: Clone,
+    {
+        find_closest(
+            self.get_root(),
+            value,
+            &self.get_comparator(),
+            Ordering::Less,
+            false,
+        )
+    }
+
+    /// Gets the smallest value greater than the given value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = FastRBTree::new();
+    /// tree.insert(10);
+    /// tree.insert(20);
+    /// assert_eq!(Some(20), tree.successor(&10));
+    /// assert_eq!(None, tree.successor(&20));
+    /// ```
+    fn successor(&self, value: &T) -> Option<T>
+    where
+        T: Clone,
+    {
+        find_closest(
+            self.get_root(),
+            value,
+            &self.get_comparator(),
+            Ordering::Greater,
+            false,
+        )
+    }
+
+    /// Gets an iterator over the values of the Tree in ascending order,
+    /// call `rev()` on it to visit them in descending order.
+    /// The values are cloned out of the nodes, use `into_iter` on the tree to move them
+    /// out instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2);
+    /// tree.insert(1);
+    /// tree.insert(3);
+    /// assert_eq!(vec![1, 2, 3], tree.iter().collect::<Vec<_>>());
+    /// assert_eq!(vec![3, 2, 1], tree.iter().rev().collect::<Vec<_>>());
+    /// ```
+    fn iter(&self) -> Iter<'_, T, Self::Handle<'_>>
+    where
+        T: Clone,
+    {
+        Iter::new(self.get_root(), .., &self.get_comparator())
+    }
+
+    /// Gets an iterator over the values of the Tree within the range in ascending order,
+    /// subtrees out of the range are skipped. Like [iter](#method.iter), it clones the values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::new();
+    /// for value in 0..10 {
+    ///     tree.insert(value);
+    /// }
+    /// assert_eq!(vec![3, 4, 5], tree.range(3..6).collect::<Vec<_>>());
+    /// assert_eq!(vec![8, 9], tree.range(8..).collect::<Vec<_>>());
+    /// assert_eq!(vec![2, 1, 0], tree.range(..=2).rev().collect::<Vec<_>>());
+    /// ```
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T, Self::Handle<'_>>
+    where
+        T: Clone,
+        Self: Sized,
+    {
+        Iter::new(self.get_root(), range, &self.get_comparator())
+    }
+
+    /// Gets a lazy iterator over the values in this Tree or the other one in ascending order
+    ///
+    /// Panics if the trees are ordered by different comparators, a tree made by
+    /// [from_comparator](trait.OrderedSet.html#tymethod.from_comparator) can be combined
+    /// with the tree the comparator came from.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut a = AVLTree::new();
+    /// let mut b = AVLTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    /// b.insert(2);
+    /// b.insert(3);
+    /// assert_eq!(vec![1, 2, 3], a.union(&b).collect::<Vec<_>>());
+    /// ```
+    fn union<'a>(&'a self, other: &'a Self) -> Union<Iter<'a, T, Self::Handle<'a>>>
+    where
+        T: Clone,
+    {
+        let comparator = self.get_comparator();
+        comparator.assert_same(&other.get_comparator());
+        Union::new(self.iter(), other.iter(), comparator)
+    }
+
+    /// Gets a lazy iterator over the values in both this Tree and the other one
+    /// in ascending order
+    ///
+    /// Panics if the trees are ordered by different comparators, the same as `union`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut a = RBTree::new();
+    /// let mut b = RBTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    /// b.insert(2);
+    /// b.insert(3);
+    /// assert_eq!(vec![2], a.intersection(&b).collect::<Vec<_>>());
+    /// ```
+    fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<Iter<'a, T, Self::Handle<'a>>>
+    where
+        T: Clone,
+    {
+        let comparator = self.get_comparator();
+        comparator.assert_same(&other.get_comparator());
+        Intersection::new(self.iter(), other.iter(), comparator)
+    }
+
+    /// Gets a lazy iterator over the values in this Tree but not in the other one
+    /// in ascending order
+    ///
+    /// Panics if the trees are ordered by different comparators, the same as `union`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut a = AVLTree::new();
+    /// let mut b = AVLTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    /// b.insert(2);
+    /// b.insert(3);
+    /// assert_eq!(vec![1], a.difference(&b).collect::<Vec<_>>());
+    /// ```
+    fn difference<'a>(&'a self, other: &'a Self) -> Difference<Iter<'a, T, Self::Handle<'a>>>
+    where
+        T: Clone,
+    {
+        let comparator = self.get_comparator();
+        comparator.assert_same(&other.get_comparator());
+        Difference::new(self.iter(), other.iter(), comparator)
+    }
+
+    /// Gets a lazy iterator over the values in exactly one of this Tree and the other one
+    /// in ascending order
+    ///
+    /// Panics if the trees are ordered by different comparators, the same as `union`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut a = RBTree::new();
+    /// let mut b = RBTree::new();
+    /// a.insert(1);
+    /// a.insert(2);
+    /// b.insert(2);
+    /// b.insert(3);
+    /// assert_eq!(vec![1, 3], a.symmetric_difference(&b).collect::<Vec<_>>());
+    /// ```
+    fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifference<Iter<'a, T, Self::Handle<'a>>>
+    where
+        T: Clone,
+    {
+        let comparator = self.get_comparator();
+        comparator.assert_same(&other.get_comparator());
+        SymmetricDifference::new(self.iter(), other.iter(), comparator)
+    }
+
+    /// Determine whether every value of this Tree is in the other one
+    ///
+    /// Panics if the trees are ordered by different comparators, the same as `union`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut a = AVLTree::new();
+    /// let mut b = AVLTree::new();
+    /// a.insert(2);
+    /// b.insert(2);
+    /// b.insert(3);
+    /// assert!(a.is_subset(&b));
+    /// assert!(!b.is_subset(&a));
+    /// ```
+    fn is_subset(&self, other: &Self) -> bool
+    where
+        T: Clone,
+    {
+        self.get_comparator().assert_same(&other.get_comparator());
+        if self.len() > other.len() {
+            return false;
+        }
+        // searching is faster than merging when this Tree is much smaller
+        if self.len() * SEARCH_RATIO <= other.len() {
+            return self.iter().all(|value| other.contains(&value));
         }
+        self.difference(other).next().is_none()
     }
 
-    fn max(&self) -> Option<T> {
-        match self.get_root() {
-            None => None,
-            Some(node) => Some(node.borrow().get_max_value_in_children()),
+    /// Determine whether this Tree and the other one have no value in common
+    ///
+    /// Panics if the trees are ordered by different comparators, the same as `union`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut a = RBTree::new();
+    /// let mut b = RBTree::new();
+    /// a.insert(1);
+    /// b.insert(2);
+    /// assert!(a.is_disjoint(&b));
+    /// b.insert(1);
+    /// assert!(!a.is_disjoint(&b));
+    /// ```
+    fn is_disjoint(&self, other: &Self) -> bool
+    where
+        T: Clone,
+    {
+        self.get_comparator().assert_same(&other.get_comparator());
+        // searching is faster than merging when one Tree is much smaller
+        if self.len() * SEARCH_RATIO <= other.len() {
+            return !self.iter().any(|value| other.contains(&value));
+        }
+        if other.len() * SEARCH_RATIO <= self.len() {
+            return !other.iter().any(|value| self.contains(&value));
+        }
+        self.intersection(other).next().is_none()
+    }
+}
+
+/// Provide construction and mutation of trees, so that code can be generic over the tree
+/// type. Lookups and iteration come from [CommonTreeTrait](trait.CommonTreeTrait.html).
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::prelude::*;
+/// fn sorted_unique<S: OrderedSet<u32>>(values: &[u32]) -> Vec<u32> {
+///     let mut set = S::new();
+///     for value in values {
+///         set.insert(*value);
+///     }
+///     set.delete(&0);
+///     set.iter().collect()
+/// }
+/// let values = [3, 0, 1, 3, 2];
+/// assert_eq!(vec![1, 2, 3], sorted_unique::<AVLTree<u32>>(&values));
+/// assert_eq!(vec![1, 2, 3], sorted_unique::<RBTree<u32>>(&values));
+/// assert_eq!(vec![1, 2, 3], sorted_unique::<FastRBTree<u32>>(&values));
+/// assert_eq!(vec![1, 2, 3], sorted_unique::<BSTree<u32>>(&values));
+/// ```
+pub trait OrderedSet<T: Ord>: CommonTreeTrait<T> + Default {
+    /// Create a new empty tree
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new empty tree ordered by the comparator instead of `Ord`,
+    /// which tells how the first value compares with the second one
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self;
+
+    /// Create a new empty tree ordered by the comparator of another tree, so that the two
+    /// trees can be combined
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut a = AVLTree::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// let mut b = AVLTree::from_comparator(a.get_comparator());
+    /// a.insert(1);
+    /// b.insert(2);
+    /// assert_eq!(vec![2, 1], a.union(&b).collect::<Vec<_>>());
+    /// ```
+    fn from_comparator(comparator: Comparator<T>) -> Self;
+
+    /// Insert a new value to the tree, return false if the value already exists
+    fn insert(&mut self, value: T) -> bool;
+
+    /// Delete a value from the tree, return false if the value does not exist
+    fn delete(&mut self, value: &T) -> bool;
+}
+
+// size ratio of two trees above which is_subset and is_disjoint search the larger one
+// for every value of the smaller one instead of merging them
+const SEARCH_RATIO: usize = 16;
+
+// Find the closest value on the `side` of the given value in the order of the comparator,
+// which will be called by floor, ceiling, predecessor and successor
+fn find_closest<T: Ord + Clone, H: NodeHandle<T>>(
+    root: Option<H>,
+    value: &T,
+    comparator: &Comparator<T>,
+    side: Ordering,
+    inclusive: bool,
+) -> Option<T> {
+    let cmp = |a: &T, b: &T| comparator.compare(a, b);
+    nodeHandle::find_closest(root, value, &cmp, side, inclusive)
+}
+
+// a shared node reads its value and children through a short borrow
+impl<T: Ord, TreeNode: CommonTreeNodeTrait<T>> NodeHandle<T> for Rc<RefCell<TreeNode>> {
+    fn get_left(&self) -> Option<Self> {
+        self.borrow().get_left()
+    }
+
+    fn get_right(&self) -> Option<Self> {
+        self.borrow().get_right()
+    }
+
+    fn get_size(&self) -> usize {
+        self.borrow().get_size()
+    }
+
+    fn with_value<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        f(self.borrow().get_value())
+    }
+}
+
+/// Ordering of the values of a tree, which is the ordering of `Ord` unless the tree is
+/// created by `with_comparator`. Cloning it shares the same comparing function, which
+/// must be `Send + Sync` so that the trees owning their nodes can still be sent to other threads.
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::commonTrait::Comparator;
+/// use std::cmp::Ordering;
+/// let reverse = Comparator::new(|a: &u32, b: &u32| b.cmp(a));
+/// assert_eq!(Ordering::Greater, reverse.compare(&1, &2));
+/// assert_eq!(Ordering::Less, Comparator::default().compare(&1, &2));
+/// ```
+pub struct Comparator<T> {
+    // None for the ordering of `Ord`, which avoids calling through a pointer
+    cmp: Option<Arc<CompareFn<T>>>,
+}
+
+type CompareFn<T> = dyn Fn(&T, &T) -> Ordering + Send + Sync;
+
+impl<T: Ord> Comparator<T> {
+    /// Create a comparator from a function telling how the first value compares with
+    /// the second one, it must be a total order like `Ord::cmp`
+    pub fn new<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        Comparator {
+            cmp: Some(Arc::new(cmp)),
+        }
+    }
+
+    /// Compare two values
+    pub fn compare(&self, a: &T, b: &T) -> Ordering {
+        match &self.cmp {
+            None => a.cmp(b),
+            Some(cmp) => cmp(a, b),
+        }
+    }
+
+    // merging trees ordered differently would give a tree out of order, so the binary
+    // operations on trees refuse it
+    #[track_caller]
+    pub(crate) fn assert_same(&self, other: &Self) {
+        assert!(
+            self == other,
+            "the trees are ordered by different comparators"
+        );
+    }
+}
+
+impl<T> Clone for Comparator<T> {
+    fn clone(&self) -> Self {
+        Comparator {
+            cmp: self.cmp.clone(),
+        }
+    }
+}
+
+// the ordering of `Ord`
+impl<T> Default for Comparator<T> {
+    fn default() -> Self {
+        Comparator { cmp: None }
+    }
+}
+
+impl<T> fmt::Debug for Comparator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cmp {
+            None => write!(f, "Comparator(Ord)"),
+            Some(_) => write!(f, "Comparator(custom)"),
+        }
+    }
+}
+
+// functions cannot be compared, so two comparators are equal only if they are both the
+// ordering of `Ord` or share the same function
+impl<T> PartialEq for Comparator<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.cmp, &other.cmp) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            _ => false,
         }
     }
 }
 
 /// Provide common functions for nodes
 // Common trait for TreeNode
-pub trait CommonTreeNodeTrait<T: Ord + Copy + fmt::Debug> {
-
+pub trait CommonTreeNodeTrait<T: Ord>: Sized {
     /// Get left child node
     fn get_left(&self) -> Option<Rc<RefCell<Self>>>;
 
     /// Get right child node
     fn get_right(&self) -> Option<Rc<RefCell<Self>>>;
 
-    /// Get value from current node
-    fn get_value(&self) -> T;
+    /// Get the number of nodes in the subtree rooted at current node
+    fn get_size(&self) -> usize;
+
+    /// Detach left child node from current node
+    fn take_left(&mut self) -> Option<Rc<RefCell<Self>>>;
+
+    /// Detach right child node from current node
+    fn take_right(&mut self) -> Option<Rc<RefCell<Self>>>;
+
+    /// Move value out of the node
+    fn into_value(self) -> T;
+
+    /// Get a reference to the value of current node
+    fn get_value(&self) -> &T;
 
     /// Return the leaves number of current node, which will be called by
     /// [CommonTreeTrait.count_leaves](trait.CommonTreeTrait.html#method.count_leaves)
@@ -193,116 +823,866 @@ pub trait CommonTreeNodeTrait<T: Ord + Copy + fmt::Debug> {
         let right = self.get_right();
         if left.is_none() && right.is_none() {
             return 1;
-        } else if left.is_none() && right.is_some() {
-            return right.unwrap().borrow().count_leaves();
-        } else if left.is_some() && right.is_none() {
-            return left.unwrap().borrow().count_leaves();
-        } else {
-            return right.unwrap().borrow().count_leaves() + left.unwrap().borrow().count_leaves();
         }
+        nodeHandle::count_leaves(left) + nodeHandle::count_leaves(right)
     }
 
     /// Return the height of current node, which will be called by
     /// [CommonTreeTrait.height](trait.CommonTreeTrait.html#method.height)
     fn get_height(&self) -> u32 {
-        let left = self.get_left();
-        let right = self.get_right();
-        let left_height = match left {
-            None => 0,
-            Some(l) => l.borrow().get_height(),
-        };
-        let right_height = match right {
-            None => 0,
-            Some(r) => r.borrow().get_height(),
-        };
-        return max(left_height, right_height) + 1;
+        1 + nodeHandle::height(self.get_left()).max(nodeHandle::height(self.get_right()))
     }
 
     /// Print nodes inorder, which will be called by
     /// [CommonTreeTrait.in_order_traversal](trait.CommonTreeTrait.html#method.in_order_traversal)
-    fn in_order_traversal(&self) {
-        let left = self.get_left();
-        if left.is_some() {
-            left.unwrap().borrow().in_order_traversal();
+    fn in_order_traversal(&self)
+    where
+        T: fmt::Debug,
+    {
+        let print = |value: &T, _| print!("{:?} ", value);
+        nodeHandle::traverse(self.get_left(), Order::InOrder, print);
+        print(self.get_value(), 0);
+        nodeHandle::traverse(self.get_right(), Order::InOrder, print);
+    }
+
+    fn in_order_traversal_for_test(&self, container: &mut Vec<T>)
+    where
+        T: Clone,
+    {
+        let mut push = |value: &T, _| container.push(value.clone());
+        nodeHandle::traverse(self.get_left(), Order::InOrder, &mut push);
+        push(self.get_value(), 0);
+        nodeHandle::traverse(self.get_right(), Order::InOrder, &mut push);
+    }
+
+    /// Print nodes preorder, which will be called by
+    /// [CommonTreeTrait.pre_order_traversal](trait.CommonTreeTrait.html#method.pre_order_traversal)
+    fn pre_order_traversal(&self)
+    where
+        T: fmt::Debug,
+    {
+        let print = |value: &T, _| print!("{:?} ", value);
+        print(self.get_value(), 0);
+        nodeHandle::traverse(self.get_left(), Order::PreOrder, print);
+        nodeHandle::traverse(self.get_right(), Order::PreOrder, print);
+    }
+
+    fn pre_order_traversal_for_test(&self, container: &mut Vec<T>)
+    where
+        T: Clone,
+    {
+        let mut push = |value: &T, _| container.push(value.clone());
+        push(self.get_value(), 0);
+        nodeHandle::traverse(self.get_left(), Order::PreOrder, &mut push);
+        nodeHandle::traverse(self.get_right(), Order::PreOrder, &mut push);
+    }
+
+    /// Determine whether the node and its successors contains given value
+    fn contains(&self, value: &T) -> bool {
+        self.contains_by(value, &|a: &T, b: &T| a.cmp(b))
+    }
+
+    /// Determine whether the node and its successors contains given value ordered by `cmp`
+    fn contains_by<F: Fn(&T, &T) -> Ordering>(&self, value: &T, cmp: &F) -> bool {
+        match cmp(value, self.get_value()) {
+            Ordering::Equal => true,
+            Ordering::Less => nodeHandle::contains(self.get_left(), value, cmp),
+            Ordering::Greater => nodeHandle::contains(self.get_right(), value, cmp),
         }
-        print!("{:?} ", self.get_value());
-        let right = self.get_right();
-        if right.is_some() {
-            right.unwrap().borrow().in_order_traversal();
+    }
+
+    // find the min value in its children
+    fn get_min_value_in_children(&self) -> T
+    where
+        T: Clone,
+    {
+        nodeHandle::min(self.get_left()).unwrap_or_else(|| self.get_value().clone())
+    }
+
+    // find the max value in its children
+    fn get_max_value_in_children(&self) -> T
+    where
+        T: Clone,
+    {
+        nodeHandle::max(self.get_right()).unwrap_or_else(|| self.get_value().clone())
+    }
+}
+
+/// Iterator over the values of a tree in order, created by
+/// [CommonTreeTrait.iter](trait.CommonTreeTrait.html#method.iter) and
+/// [CommonTreeTrait.range](trait.CommonTreeTrait.html#method.range).
+///
+/// The values are cloned out of the nodes, which are read through the handles `H`.
+pub struct Iter<'a, T, H> {
+    // nodes whose value and right subtree are not visited from the front
+    front: Vec<H>,
+    // nodes whose value and left subtree are not visited from the back
+    back: Vec<H>,
+    // number of values left, the iteration is over when the ends meet
+    remaining: usize,
+    marker: PhantomData<&'a T>,
+}
+
+impl<'a, T: Ord, H: NodeHandle<T> + Clone> Iter<'a, T, H> {
+    fn new<R: RangeBounds<T>>(root: Option<H>, range: R, comparator: &Comparator<T>) -> Self {
+        let cmp = |a: &T, b: &T| comparator.compare(a, b);
+        let start = range.start_bound();
+        let end = range.end_bound();
+        let remaining = nodeHandle::count_until_end(root.clone(), end, &cmp)
+            .saturating_sub(nodeHandle::count_before_start(root.clone(), start, &cmp));
+        let mut iter = Iter {
+            front: vec![],
+            back: vec![],
+            remaining,
+            marker: PhantomData,
+        };
+        // only keep the path to the first value not below start,
+        // the left subtree of a value below start is below start as well
+        let mut current = root.clone();
+        while let Some(node) = current {
+            if node.with_value(|value| nodeHandle::after_start(start, value, &cmp)) {
+                current = node.get_left();
+                iter.front.push(node);
+            } else {
+                current = node.get_right();
+            }
         }
+        // the same for the last value not above end
+        let mut current = root;
+        while let Some(node) = current {
+            if node.with_value(|value| nodeHandle::before_end(end, value, &cmp)) {
+                current = node.get_right();
+                iter.back.push(node);
+            } else {
+                current = node.get_left();
+            }
+        }
+        iter
     }
+}
 
-    fn in_order_traversal_for_test(&self, container: &mut Vec<T>) {
-        let left = self.get_left();
-        if left.is_some() {
-            left.unwrap().borrow().in_order_traversal_for_test(container);
+impl<'a, T: Clone, H: NodeHandle<T>> Iterator for Iter<'a, T, H> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
         }
-        container.push(self.get_value());
-        let right = self.get_right();
-        if right.is_some() {
-            right.unwrap().borrow().in_order_traversal_for_test(container);
+        self.remaining -= 1;
+        let node = self.front.pop()?;
+        let mut current = node.get_right();
+        while let Some(child) = current {
+            current = child.get_left();
+            self.front.push(child);
         }
+        Some(node.with_value(T::clone))
     }
 
-    /// Print nodes preorder, which will be called by
-    /// [CommonTreeTrait.pre_order_traversal](trait.CommonTreeTrait.html#method.pre_order_traversal)
-    fn pre_order_traversal(&self) {
-        print!("{:?} ", self.get_value());
-        let left = self.get_left();
-        if left.is_some() {
-            left.unwrap().borrow().pre_order_traversal();
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: Clone, H: NodeHandle<T>> DoubleEndedIterator for Iter<'a, T, H> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
         }
-        let right = self.get_right();
-        if right.is_some() {
-            right.unwrap().borrow().pre_order_traversal();
+        self.remaining -= 1;
+        let node = self.back.pop()?;
+        let mut current = node.get_left();
+        while let Some(child) = current {
+            current = child.get_right();
+            self.back.push(child);
         }
+        Some(node.with_value(T::clone))
     }
+}
 
-    fn pre_order_traversal_for_test(&self, container: &mut Vec<T>) {
-        container.push(self.get_value());
-        let left = self.get_left();
-        if left.is_some() {
-            left.unwrap().borrow().pre_order_traversal_for_test(container);
+impl<'a, T: Clone, H: NodeHandle<T>> ExactSizeIterator for Iter<'a, T, H> {}
+
+/// Lazy iterator over the values in either of two sorted iterators, created by
+/// [CommonTreeTrait.union](trait.CommonTreeTrait.html#method.union).
+/// A value in both is taken from the first one.
+pub struct Union<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+    comparator: Comparator<I::Item>,
+}
+
+impl<I: Iterator> Union<I> {
+    pub(crate) fn new(a: I, b: I, comparator: Comparator<I::Item>) -> Self {
+        Union {
+            a: a.peekable(),
+            b: b.peekable(),
+            comparator,
         }
-        let right = self.get_right();
-        if right.is_some() {
-            right.unwrap().borrow().pre_order_traversal_for_test(container);
-        }
-    }
-
-    /// Determine whether the node and its successors contains given value,
-    /// which will be called by
-    /// [CommonTreeTrait.contains](trait.CommonTreeTrait.html#method.contains)
-    fn contains(&self, value: T) -> bool {
-        let current_value = self.get_value();
-        return if current_value == value {
-            true
-        } else if current_value > value {
-            match self.get_left() {
-                None => false,
-                Some(node) => node.borrow().contains(value),
+    }
+}
+
+impl<T: Ord, I: Iterator<Item = T>> Iterator for Union<I> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let ordering = match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => self.comparator.compare(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => Ordering::Greater,
+        };
+        match ordering {
+            Ordering::Less => self.a.next(),
+            Ordering::Greater => self.b.next(),
+            Ordering::Equal => {
+                self.b.next();
+                self.a.next()
             }
-        } else {
-            match self.get_right() {
-                None => false,
-                Some(node) => node.borrow().contains(value),
+        }
+    }
+}
+
+/// Lazy iterator over the values in both of two sorted iterators, created by
+/// [CommonTreeTrait.intersection](trait.CommonTreeTrait.html#method.intersection).
+/// The values are taken from the first one.
+pub struct Intersection<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+    comparator: Comparator<I::Item>,
+}
+
+impl<I: Iterator> Intersection<I> {
+    pub(crate) fn new(a: I, b: I, comparator: Comparator<I::Item>) -> Self {
+        Intersection {
+            a: a.peekable(),
+            b: b.peekable(),
+            comparator,
+        }
+    }
+}
+
+impl<T: Ord, I: Iterator<Item = T>> Iterator for Intersection<I> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            match self.comparator.compare(self.a.peek()?, self.b.peek()?) {
+                Ordering::Less => {
+                    self.a.next();
+                }
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.b.next();
+                    return self.a.next();
+                }
+            }
+        }
+    }
+}
+
+/// Lazy iterator over the values in the first of two sorted iterators but not in the second,
+/// created by [CommonTreeTrait.difference](trait.CommonTreeTrait.html#method.difference).
+pub struct Difference<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+    comparator: Comparator<I::Item>,
+}
+
+impl<I: Iterator> Difference<I> {
+    pub(crate) fn new(a: I, b: I, comparator: Comparator<I::Item>) -> Self {
+        Difference {
+            a: a.peekable(),
+            b: b.peekable(),
+            comparator,
+        }
+    }
+}
+
+impl<T: Ord, I: Iterator<Item = T>> Iterator for Difference<I> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let ordering = match (self.a.peek()?, self.b.peek()) {
+                (a, Some(b)) => self.comparator.compare(a, b),
+                (_, None) => Ordering::Less,
+            };
+            match ordering {
+                Ordering::Less => return self.a.next(),
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.a.next();
+                    self.b.next();
+                }
+            }
+        }
+    }
+}
+
+/// Lazy iterator over the values in exactly one of two sorted iterators, created by
+/// [CommonTreeTrait.symmetric_difference](trait.CommonTreeTrait.html#method.symmetric_difference).
+pub struct SymmetricDifference<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+    comparator: Comparator<I::Item>,
+}
+
+impl<I: Iterator> SymmetricDifference<I> {
+    pub(crate) fn new(a: I, b: I, comparator: Comparator<I::Item>) -> Self {
+        SymmetricDifference {
+            a: a.peekable(),
+            b: b.peekable(),
+            comparator,
+        }
+    }
+}
+
+impl<T: Ord, I: Iterator<Item = T>> Iterator for SymmetricDifference<I> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let ordering = match (self.a.peek(), self.b.peek()) {
+                (Some(a), Some(b)) => self.comparator.compare(a, b),
+                (Some(_), None) => Ordering::Less,
+                (None, _) => Ordering::Greater,
+            };
+            match ordering {
+                Ordering::Less => return self.a.next(),
+                Ordering::Greater => return self.b.next(),
+                Ordering::Equal => {
+                    self.a.next();
+                    self.b.next();
+                }
+            }
+        }
+    }
+}
+
+/// Owned node of a tree which can be taken apart, so that
+/// [IntoIter](struct.IntoIter.html) moves the values out of the tree one node at a time.
+/// `S` is where the nodes are stored, like the arena of the arena-backed trees.
+pub trait OwnedNode<T, S = ()>: Sized {
+    /// Take the node out of the tree and return its left subtree, value and right subtree
+    fn split(self, storage: &mut S) -> (Option<Self>, T, Option<Self>);
+}
+
+// a node detached from its parent and children is no longer shared
+impl<T: Ord, TreeNode: CommonTreeNodeTrait<T>> OwnedNode<T> for Rc<RefCell<TreeNode>> {
+    fn split(self, _: &mut ()) -> (Option<Self>, T, Option<Self>) {
+        let left = self.borrow_mut().take_left();
+        let right = self.borrow_mut().take_right();
+        match Rc::try_unwrap(self) {
+            Ok(node) => (left, node.into_inner().into_value(), right),
+            Err(_) => panic!("detached node is still shared"),
+        }
+    }
+}
+
+// a subtree still to visit, or a value whose left subtree is visited from the front
+// or whose right subtree is visited from the back
+enum Pending<T, N> {
+    Node(N),
+    Value(T),
+}
+
+/// Owning iterator over the values of a tree in order, created by `into_iter` of the trees.
+///
+/// The values are moved out lazily: a node is taken apart when the iteration reaches it
+/// from either end, and the nodes left when the iterator is dropped are freed one by one.
+pub struct IntoIter<T, N: OwnedNode<T, S>, S = ()> {
+    pending: VecDeque<Pending<T, N>>,
+    storage: S,
+    remaining: usize,
+}
+
+impl<T, N: OwnedNode<T>> IntoIter<T, N> {
+    /// Move the values out of the tree of `len` values with the given root,
+    /// which will be called by `into_iter` of the trees
+    pub fn new(root: Option<N>, len: usize) -> Self {
+        Self::with_storage(root, len, ())
+    }
+}
+
+impl<T, N: OwnedNode<T, S>, S> IntoIter<T, N, S> {
+    /// Move the values out of the tree of `len` values with the given root,
+    /// whose nodes are kept in the storage
+    pub fn with_storage(root: Option<N>, len: usize, storage: S) -> Self {
+        IntoIter {
+            pending: root.into_iter().map(Pending::Node).collect(),
+            storage,
+            remaining: len,
+        }
+    }
+}
+
+impl<T, N: OwnedNode<T, S>, S> Iterator for IntoIter<T, N, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            match self.pending.pop_front()? {
+                Pending::Value(value) => {
+                    self.remaining -= 1;
+                    return Some(value);
+                }
+                Pending::Node(node) => {
+                    let (left, value, right) = node.split(&mut self.storage);
+                    if let Some(right) = right {
+                        self.pending.push_front(Pending::Node(right));
+                    }
+                    self.pending.push_front(Pending::Value(value));
+                    if let Some(left) = left {
+                        self.pending.push_front(Pending::Node(left));
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T, N: OwnedNode<T, S>, S> DoubleEndedIterator for IntoIter<T, N, S> {
+    fn next_back(&mut self) -> Option<T> {
+        loop {
+            match self.pending.pop_back()? {
+                Pending::Value(value) => {
+                    self.remaining -= 1;
+                    return Some(value);
+                }
+                Pending::Node(node) => {
+                    let (left, value, right) = node.split(&mut self.storage);
+                    if let Some(left) = left {
+                        self.pending.push_back(Pending::Node(left));
+                    }
+                    self.pending.push_back(Pending::Value(value));
+                    if let Some(right) = right {
+                        self.pending.push_back(Pending::Node(right));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T, N: OwnedNode<T, S>, S> ExactSizeIterator for IntoIter<T, N, S> {}
+
+// take the nodes left apart one by one, so that dropping a deep tree does not overflow
+impl<T, N: OwnedNode<T, S>, S> Drop for IntoIter<T, N, S> {
+    fn drop(&mut self) {
+        while let Some(pending) = self.pending.pop_back() {
+            if let Pending::Node(node) = pending {
+                let (left, _, right) = node.split(&mut self.storage);
+                self.pending
+                    .extend(left.into_iter().chain(right).map(Pending::Node));
+            }
+        }
+    }
+}
+
+/// Free the nodes of the tree with the given root one by one instead of recursively,
+/// so that dropping a deep tree does not overflow the stack,
+/// which will be called by `drop` of the trees
+pub(crate) fn drop_nodes<T: Ord, TreeNode: CommonTreeNodeTrait<T>>(
+    root: Option<Rc<RefCell<TreeNode>>>,
+) {
+    let mut stack: Vec<Rc<RefCell<TreeNode>>> = root.into_iter().collect();
+    while let Some(node) = stack.pop() {
+        // a subtree shared with another tree is left to the last tree holding it
+        if let Ok(node) = Rc::try_unwrap(node) {
+            let mut node = node.into_inner();
+            stack.extend(node.take_left());
+            stack.extend(node.take_right());
+        }
+    }
+}
+
+/// Copy the nodes of the tree with the given root one by one instead of recursively,
+/// `copy_node` makes the copy of a node from the copies of its children,
+/// which will be called by `clone` of the trees
+pub(crate) fn clone_nodes<T, TreeNode, F>(
+    root: &Option<Rc<RefCell<TreeNode>>>,
+    copy_node: F,
+) -> Option<Rc<RefCell<TreeNode>>>
+where
+    T: Ord,
+    TreeNode: CommonTreeNodeTrait<T>,
+    F: Fn(
+        &TreeNode,
+        Option<Rc<RefCell<TreeNode>>>,
+        Option<Rc<RefCell<TreeNode>>>,
+    ) -> Rc<RefCell<TreeNode>>,
+{
+    // a node is visited again once the copies of both children are made
+    let mut stack = vec![(root.clone(), false)];
+    let mut copies = vec![];
+    while let Some((node, children_copied)) = stack.pop() {
+        let node = match node {
+            Some(node) => node,
+            None => {
+                copies.push(None);
+                continue;
             }
         };
+        if children_copied {
+            let right = copies.pop().unwrap();
+            let left = copies.pop().unwrap();
+            copies.push(Some(copy_node(&node.borrow(), left, right)));
+        } else {
+            let (left, right) = (node.borrow().get_left(), node.borrow().get_right());
+            stack.push((Some(node), true));
+            stack.push((right, false));
+            stack.push((left, false));
+        }
     }
+    copies.pop().unwrap()
+}
 
-    // find the min value in its children
-    fn get_min_value_in_children(&self) -> T {
-        match self.get_left() {
-            Some(left) => left.borrow().get_min_value_in_children(),
-            None => self.get_value(),
+/// Whether the values are in ascending order of the comparator, repeated values allowed,
+/// which will be called by `from_iter` and `extend` of the trees to choose building from
+/// sorted values
+pub(crate) fn is_sorted<T: Ord>(values: &[T], comparator: &Comparator<T>) -> bool {
+    values
+        .windows(2)
+        .all(|pair| comparator.compare(&pair[0], &pair[1]) != Ordering::Greater)
+}
+
+/// Collect the values in ascending order, keeping the last one of repeated values like
+/// `insert` does, which will be called by `from_sorted_iter` of the trees
+///
+/// Panics if the values are not in ascending order.
+pub(crate) fn collect_sorted<T: Ord, I: IntoIterator<Item = T>>(iter: I) -> Vec<T> {
+    let comparator = Comparator::default();
+    let values: Vec<T> = iter.into_iter().collect();
+    assert!(
+        is_sorted(&values, &comparator),
+        "values are not in ascending order"
+    );
+    dedup_sorted(values, &comparator)
+}
+
+/// Remove the repeated values of sorted values, keeping the last one of them
+pub(crate) fn dedup_sorted<T: Ord>(mut values: Vec<T>, comparator: &Comparator<T>) -> Vec<T> {
+    values.dedup_by(|next, kept| {
+        let is_repeated = comparator.compare(next, kept) == Ordering::Equal;
+        if is_repeated {
+            std::mem::swap(next, kept);
         }
+        is_repeated
+    });
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fixtures;
+    use crate::prelude::*;
+    use std::collections::BTreeSet;
+    use std::iter::FromIterator;
+
+    // tree of the even values 0..100 inserted in a shuffled order
+    fn evens<S: OrderedSet<i32>>() -> S {
+        let mut tree = S::new();
+        for i in 0..50 {
+            tree.insert(i * 37 % 50 * 2);
+        }
+        tree
     }
 
-    // find the max value in its children
-    fn get_max_value_in_children(&self) -> T {
-        match self.get_right() {
-            Some(right) => right.borrow().get_max_value_in_children(),
-            None => self.get_value(),
+    fn check_range<S: OrderedSet<i32>>() {
+        let tree = evens::<S>();
+        let evens = |lo: i32, hi: i32| (lo..hi).filter(|v| v % 2 == 0).collect::<Vec<_>>();
+        assert_eq!(tree.range(10..20).collect::<Vec<_>>(), evens(10, 20));
+        assert_eq!(tree.range(11..=20).collect::<Vec<_>>(), evens(11, 21));
+        assert_eq!(tree.range(..7).collect::<Vec<_>>(), evens(0, 7));
+        assert_eq!(tree.range(91..).collect::<Vec<_>>(), evens(91, 100));
+        assert_eq!(tree.range(..).count(), 50);
+        assert_eq!(tree.range(41..42).count(), 0);
+        assert_eq!(tree.range(200..).count(), 0);
+        assert_eq!(
+            tree.range(30..=40).rev().collect::<Vec<_>>(),
+            vec![40, 38, 36, 34, 32, 30]
+        );
+        // both ends of the iterator meet in the middle
+        let mut iter = tree.iter();
+        for i in 0..25 {
+            assert_eq!(iter.next(), Some(i * 2));
+            assert_eq!(iter.next_back(), Some(98 - i * 2));
+        }
+        assert_eq!(iter.next(), None);
+    }
+
+    fn check_floor_ceiling<S: OrderedSet<i32>>() {
+        let tree = evens::<S>();
+        let values: Vec<i32> = (0..50).map(|v| v * 2).collect();
+        for x in -1..101 {
+            let below = values.iter().rev();
+            let above = values.iter();
+            assert_eq!(tree.floor(&x), below.clone().find(|v| **v <= x).cloned());
+            assert_eq!(
+                tree.predecessor(&x),
+                below.clone().find(|v| **v < x).cloned()
+            );
+            assert_eq!(tree.ceiling(&x), above.clone().find(|v| **v >= x).cloned());
+            assert_eq!(tree.successor(&x), above.clone().find(|v| **v > x).cloned());
+        }
+        assert_eq!(S::new().floor(&0), None);
+    }
+
+    fn check_rank_select<S: OrderedSet<i32>>() {
+        let mut tree = S::new();
+        let mut expected = BTreeSet::new();
+        // insert 0..60 in a shuffled order, then delete half of them
+        let operations = (0..60).map(|i| (true, i * 37 % 60));
+        let operations = operations.chain((0..30).map(|i| (false, i * 53 % 60)));
+        for (is_insert, value) in operations {
+            if is_insert {
+                assert!(tree.insert(value));
+                // inserting an existing value keeps the size
+                assert!(!tree.insert(value));
+                expected.insert(value);
+            } else {
+                assert_eq!(tree.delete(&value), expected.remove(&value));
+            }
+            assert_eq!(tree.len(), expected.len());
+            assert_eq!(tree.is_empty(), expected.is_empty());
+            for (k, value) in expected.iter().enumerate() {
+                assert_eq!(tree.select(k), Some(*value));
+                assert_eq!(tree.rank(value), k);
+            }
+            assert_eq!(tree.select(expected.len()), None);
+        }
+    }
+
+    fn check_iter<S>()
+    where
+        S: OrderedSet<i32> + IntoIterator<Item = i32>,
+        S::IntoIter: DoubleEndedIterator + ExactSizeIterator,
+    {
+        let mut tree = S::new();
+        for i in 0..100 {
+            tree.insert(i * 37 % 100);
+        }
+        assert!(tree.iter().eq(0..100));
+        assert!(tree.iter().rev().eq((0..100).rev()));
+        // both ends of the consuming iterator meet in the middle
+        let mut iter = tree.into_iter();
+        for i in 0..50 {
+            assert_eq!(iter.next(), Some(i));
+            assert_eq!(iter.next_back(), Some(99 - i));
+            assert_eq!(iter.len(), 98 - 2 * i as usize);
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    fn check_clone<S: OrderedSet<i32> + Clone>() {
+        let mut tree = S::new();
+        for value in 0..20 {
+            tree.insert(value);
+        }
+        let mut copy = tree.clone();
+        assert!(tree.iter().eq(copy.iter()));
+        // changing the copy leaves the original alone, and the other way round
+        for value in 0..10 {
+            copy.delete(&value);
+        }
+        copy.insert(100);
+        tree.insert(-1);
+        assert!(tree.iter().eq(-1..20));
+        assert!(copy.iter().eq((10..20).chain(100..101)));
+        assert_eq!(tree.len(), 21);
+        assert_eq!(copy.len(), 11);
+        // the copy keeps working after the original is gone
+        drop(tree);
+        for value in 20..40 {
+            copy.insert(value);
+        }
+        for value in 10..30 {
+            copy.delete(&value);
+        }
+        assert!(copy.iter().eq((30..40).chain(100..101)));
+    }
+
+    fn check_from_sorted_iter<S, F>(from_sorted_iter: F)
+    where
+        S: OrderedSet<usize>,
+        F: Fn(Vec<usize>) -> S,
+    {
+        for len in 0..70usize {
+            let tree = from_sorted_iter((0..len).collect());
+            assert!(tree.iter().eq(0..len));
+            // at most one level lower than a complete tree of the same size
+            assert!(tree.height() <= usize::BITS - len.leading_zeros() + 1);
         }
+        let tree = from_sorted_iter(vec![1, 1, 2, 3, 3, 3]);
+        assert!(tree.iter().eq(1..4));
+        // the built tree keeps working with inserts and deletes
+        let mut tree = from_sorted_iter((0..100).collect());
+        for value in (0..100).filter(|value| value % 3 == 0) {
+            assert!(tree.delete(&value));
+        }
+        assert!(tree.insert(1000));
+        assert_eq!(tree.len(), 67);
+    }
+
+    fn check_from_iter_extend<S>()
+    where
+        S: OrderedSet<u32> + FromIterator<u32> + Extend<u32>,
+    {
+        let sorted: S = (0..50).collect();
+        let unsorted: S = (0..50).rev().collect();
+        assert!(sorted.iter().eq(unsorted.iter()));
+        let mut tree = sorted;
+        tree.extend(vec![60, 55, 70]);
+        tree.extend(80..90);
+        tree.extend(vec![10, 20, 95]);
+        let expected = (0..50)
+            .chain(vec![55, 60, 70])
+            .chain(80..90)
+            .chain(vec![95]);
+        assert!(tree.iter().eq(expected));
+        assert_eq!(tree.len(), 64);
+    }
+
+    // values without Copy are moved into the tree and cloned out of it
+    fn check_string_values<S: OrderedSet<String>>() {
+        let mut tree = S::new();
+        for name in ["delta", "alpha", "echo", "charlie", "bravo"].iter() {
+            assert!(tree.insert(name.to_string()));
+        }
+        assert!(tree.delete(&"delta".to_string()));
+        assert!(tree.contains(&"alpha".to_string()));
+        assert!(!tree.contains(&"delta".to_string()));
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec!["alpha", "bravo", "charlie", "echo"]
+        );
+        assert_eq!(tree.min(), Some("alpha".to_string()));
+        assert_eq!(tree.max(), Some("echo".to_string()));
+    }
+
+    fn check_mismatched_comparators<S: OrderedSet<i32>>() {
+        let a = evens::<S>();
+        let b = S::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        assert!(fixtures::panics(|| drop(a.union(&b))));
+        assert!(fixtures::panics(|| drop(a.intersection(&b))));
+        assert!(fixtures::panics(|| drop(a.difference(&b))));
+        assert!(fixtures::panics(|| drop(a.symmetric_difference(&b))));
+        assert!(fixtures::panics(|| {
+            a.is_subset(&b);
+        }));
+        assert!(fixtures::panics(|| {
+            b.is_disjoint(&a);
+        }));
+        // a tree sharing the comparator can be combined
+        let mut c = S::from_comparator(b.get_comparator());
+        c.insert(1);
+        assert!(b.union(&c).eq(1..2));
+        assert!(b.is_disjoint(&c));
+    }
+
+    #[test]
+    fn test_range() {
+        check_range::<AVLTree<i32>>();
+        check_range::<RBTree<i32>>();
+        check_range::<FastRBTree<i32>>();
+        check_range::<BSTree<i32>>();
+        check_range::<BoxAVLTree<i32>>();
+        check_range::<BoxRBTree<i32>>();
+        check_range::<ArenaAVLTree<i32>>();
+        check_range::<ArenaRBTree<i32>>();
+    }
+
+    #[test]
+    fn test_floor_ceiling() {
+        check_floor_ceiling::<AVLTree<i32>>();
+        check_floor_ceiling::<RBTree<i32>>();
+        check_floor_ceiling::<FastRBTree<i32>>();
+        check_floor_ceiling::<BSTree<i32>>();
+        check_floor_ceiling::<BoxAVLTree<i32>>();
+        check_floor_ceiling::<BoxRBTree<i32>>();
+        check_floor_ceiling::<ArenaAVLTree<i32>>();
+        check_floor_ceiling::<ArenaRBTree<i32>>();
+    }
+
+    #[test]
+    fn test_rank_select() {
+        check_rank_select::<AVLTree<i32>>();
+        check_rank_select::<RBTree<i32>>();
+        check_rank_select::<FastRBTree<i32>>();
+        check_rank_select::<BSTree<i32>>();
+        check_rank_select::<BoxAVLTree<i32>>();
+        check_rank_select::<BoxRBTree<i32>>();
+        check_rank_select::<ArenaAVLTree<i32>>();
+        check_rank_select::<ArenaRBTree<i32>>();
+    }
+    #[test]
+    fn test_iter() {
+        check_iter::<AVLTree<i32>>();
+        check_iter::<RBTree<i32>>();
+        check_iter::<FastRBTree<i32>>();
+        check_iter::<BSTree<i32>>();
+        check_iter::<BoxAVLTree<i32>>();
+        check_iter::<BoxRBTree<i32>>();
+        check_iter::<ArenaAVLTree<i32>>();
+        check_iter::<ArenaRBTree<i32>>();
+    }
+
+    #[test]
+    fn test_clone() {
+        check_clone::<AVLTree<i32>>();
+        check_clone::<RBTree<i32>>();
+        check_clone::<FastRBTree<i32>>();
+        check_clone::<BSTree<i32>>();
+        check_clone::<BoxAVLTree<i32>>();
+        check_clone::<BoxRBTree<i32>>();
+        check_clone::<ArenaAVLTree<i32>>();
+        check_clone::<ArenaRBTree<i32>>();
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        check_from_sorted_iter(AVLTree::from_sorted_iter);
+        check_from_sorted_iter(RBTree::from_sorted_iter);
+        check_from_sorted_iter(FastRBTree::from_sorted_iter);
+        check_from_sorted_iter(BSTree::from_sorted_iter);
+    }
+
+    #[test]
+    fn test_from_iter_extend() {
+        check_from_iter_extend::<AVLTree<u32>>();
+        check_from_iter_extend::<RBTree<u32>>();
+        check_from_iter_extend::<FastRBTree<u32>>();
+        check_from_iter_extend::<BSTree<u32>>();
+    }
+
+    #[test]
+    fn test_mismatched_comparators() {
+        check_mismatched_comparators::<AVLTree<i32>>();
+        check_mismatched_comparators::<RBTree<i32>>();
+        check_mismatched_comparators::<FastRBTree<i32>>();
+        check_mismatched_comparators::<BSTree<i32>>();
+        check_mismatched_comparators::<BoxAVLTree<i32>>();
+        check_mismatched_comparators::<BoxRBTree<i32>>();
+        check_mismatched_comparators::<ArenaAVLTree<i32>>();
+        check_mismatched_comparators::<ArenaRBTree<i32>>();
+    }
+
+    #[test]
+    fn test_string_values() {
+        check_string_values::<AVLTree<String>>();
+        check_string_values::<RBTree<String>>();
+        check_string_values::<FastRBTree<String>>();
+        check_string_values::<BSTree<String>>();
+        check_string_values::<BoxAVLTree<String>>();
+        check_string_values::<BoxRBTree<String>>();
+        check_string_values::<ArenaAVLTree<String>>();
+        check_string_values::<ArenaRBTree<String>>();
     }
 }
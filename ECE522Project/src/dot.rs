@@ -0,0 +1,229 @@
+//! Graphviz export of trees
+//!
+//! [ToDot](trait.ToDot.html) writes the shape of a tree in the DOT language, render it
+//! with `dot -Tpng tree.dot -o tree.png`. Nodes are labelled with their value and what
+//! the tree balances on: AVL nodes with their height and balance factor, red-black nodes
+//! are filled with their color.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+
+use crate::nodeHandle::NodeHandle;
+
+/// Write a tree as a Graphviz graph
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::prelude::*;
+/// let mut tree = AVLTree::new();
+/// tree.insert(1);
+/// tree.insert(2);
+/// let dot = tree.to_dot();
+/// assert!(dot.starts_with("digraph {"));
+/// assert!(dot.contains("n0 [label=\"1\\nh=2 bf=-1\"]"));
+/// assert!(dot.contains("n0 -> n1;"));
+/// assert!(tree.to_dot_with_nil_leaves().contains("label=\"nil\""));
+/// ```
+pub trait ToDot {
+    /// Write the graph, with a nil leaf in place of every missing child if `nil_leaves`
+    fn write_dot(&self, nil_leaves: bool) -> String;
+
+    /// Write the graph of the nodes of the tree
+    fn to_dot(&self) -> String {
+        self.write_dot(false)
+    }
+
+    /// Write the graph with a nil leaf in place of every missing child
+    fn to_dot_with_nil_leaves(&self) -> String {
+        self.write_dot(true)
+    }
+}
+
+/// How a node is drawn, besides its value
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DotStyle {
+    /// Line written under the value
+    pub details: Option<String>,
+    /// Color the node is filled with, its text is white then
+    pub fill: Option<&'static str>,
+}
+
+/// Implementations of DotStyle
+impl DotStyle {
+    /// Node showing the details under its value
+    pub fn details(details: String) -> Self {
+        DotStyle {
+            details: Some(details),
+            fill: None,
+        }
+    }
+
+    /// Node filled with the color
+    pub fn filled(fill: &'static str) -> Self {
+        DotStyle {
+            details: None,
+            fill: Some(fill),
+        }
+    }
+
+    /// Height and balance factor of an AVL node from the heights of its children
+    pub fn avl(left_height: i64, right_height: i64) -> Self {
+        let height = 1 + left_height.max(right_height);
+        Self::details(format!("h={} bf={}", height, left_height - right_height))
+    }
+
+    // the attributes of a node labelled with the text
+    fn attributes(&self, label: &str) -> String {
+        let mut label = label.replace('\\', "\\\\").replace('"', "\\\"");
+        if let Some(details) = &self.details {
+            label = format!("{}\\n{}", label, details);
+        }
+        match self.fill {
+            Some(fill) => format!(
+                "label=\"{}\", style=filled, fillcolor={}, fontcolor=white",
+                label, fill
+            ),
+            None => format!("label=\"{}\"", label),
+        }
+    }
+}
+
+/// Write the subtree as a Graphviz graph, `style` tells how each node is drawn and
+/// `nil_leaf` how the missing children are, which are left out if it is `None`
+pub fn to_dot<T, H, F>(root: Option<H>, nil_leaf: Option<DotStyle>, style: F) -> String
+where
+    T: fmt::Debug,
+    H: NodeHandle<T>,
+    F: Fn(&H) -> DotStyle,
+{
+    let mut dot = String::from("digraph {\n    node [shape=circle];\n");
+    // nodes are numbered in level order, nil leaves separately
+    let mut next_id = 0;
+    let mut nil_count = 0;
+    let mut queue = VecDeque::new();
+    if let Some(root) = root {
+        queue.push_back((root, next_id));
+        next_id += 1;
+    }
+    while let Some((node, id)) = queue.pop_front() {
+        let label = node.with_value(|value| format!("{:?}", value));
+        writeln!(dot, "    n{} [{}];", id, style(&node).attributes(&label)).unwrap();
+        for child in [node.get_left(), node.get_right()] {
+            match child {
+                Some(child) => {
+                    writeln!(dot, "    n{} -> n{};", id, next_id).unwrap();
+                    queue.push_back((child, next_id));
+                    next_id += 1;
+                }
+                None => {
+                    if let Some(nil_leaf) = &nil_leaf {
+                        let attributes = nil_leaf.attributes("nil");
+                        writeln!(dot, "    nil{} [{}, shape=box];", nil_count, attributes).unwrap();
+                        writeln!(dot, "    n{} -> nil{};", id, nil_count).unwrap();
+                        nil_count += 1;
+                    }
+                }
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_to_dot() {
+        let mut tree = BSTree::new();
+        for value in [2, 1, 3] {
+            tree.insert(value);
+        }
+        let expected = "digraph {\n    node [shape=circle];\n    n0 [label=\"2\"];\n    \
+                        n0 -> n1;\n    n0 -> n2;\n    n1 [label=\"1\"];\n    \
+                        n2 [label=\"3\"];\n}\n";
+        assert_eq!(expected, tree.to_dot());
+        let dot = tree.to_dot_with_nil_leaves();
+        assert_eq!(4, dot.matches("label=\"nil\"").count());
+        assert!(dot.contains("n2 -> nil3;"));
+        assert_eq!(
+            "digraph {\n    node [shape=circle];\n}\n",
+            BSTree::<u32>::new().to_dot()
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        let mut tree = AVLTree::new();
+        tree.insert(String::from("say \"hi\""));
+        assert!(tree
+            .to_dot()
+            .contains("[label=\"\\\"say \\\\\\\"hi\\\\\\\"\\\"\\nh=1 bf=0\"]"));
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut trees: Vec<Box<dyn ToDot>> = vec![];
+        let mut rb_tree = RBTree::new();
+        let mut fast_tree = FastRBTree::new();
+        let mut box_tree = BoxRBTree::new();
+        let mut arena_tree = ArenaRBTree::new();
+        let mut persistent_tree = PersistentRBTree::new();
+        for value in 0..10 {
+            rb_tree.insert(value);
+            fast_tree.insert(value);
+            box_tree.insert(value);
+            arena_tree.insert(value);
+            persistent_tree = persistent_tree.insert(value);
+        }
+        trees.push(Box::new(rb_tree));
+        trees.push(Box::new(fast_tree));
+        trees.push(Box::new(box_tree));
+        trees.push(Box::new(arena_tree));
+        trees.push(Box::new(persistent_tree));
+        for tree in trees {
+            let dot = tree.to_dot();
+            assert_eq!(10, dot.matches("[label=").count());
+            assert!(dot.contains("fillcolor=red"));
+            // the root is black
+            assert!(dot.contains("n0 [label=\"3\", style=filled, fillcolor=black"));
+            let dot = tree.to_dot_with_nil_leaves();
+            assert_eq!(
+                11,
+                dot.matches("label=\"nil\", style=filled, fillcolor=black")
+                    .count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_heights() {
+        let mut trees: Vec<Box<dyn ToDot>> = vec![];
+        let mut avl_tree = AVLTree::new();
+        let mut box_tree = BoxAVLTree::new();
+        let mut arena_tree = ArenaAVLTree::new();
+        let mut persistent_tree = PersistentAVLTree::new();
+        for value in 0..4 {
+            avl_tree.insert(value);
+            box_tree.insert(value);
+            arena_tree.insert(value);
+            persistent_tree = persistent_tree.insert(value);
+        }
+        trees.push(Box::new(avl_tree));
+        trees.push(Box::new(box_tree));
+        trees.push(Box::new(arena_tree));
+        trees.push(Box::new(persistent_tree));
+        for tree in trees {
+            let dot = tree.to_dot();
+            assert!(dot.contains("n0 [label=\"1\\nh=3 bf=-1\"]"));
+            assert!(dot.contains("n2 [label=\"2\\nh=2 bf=-1\"]"));
+            assert!(dot.contains("n3 [label=\"3\\nh=1 bf=0\"]"));
+            assert_eq!(
+                5,
+                tree.to_dot_with_nil_leaves().matches("shape=box").count()
+            );
+        }
+    }
+}
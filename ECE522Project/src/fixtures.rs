@@ -0,0 +1,117 @@
+//! Fixtures shared by the tests of the trees
+//!
+//! Every tree is checked against a `BTreeSet` holding the same values, and calls back
+//! into its own module to check the balancing invariants after every change.
+
+use std::collections::BTreeSet;
+use std::ops::Bound;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::commonTrait::{CommonTreeTrait, OrderedSet};
+
+/// Insert 0..200 in a shuffled order, then delete most of them, `true` for an insert
+fn operations() -> impl Iterator<Item = (bool, i32)> {
+    let inserts = (0..200).map(|i| (true, i * 37 % 200));
+    inserts.chain((0..150).map(|i| (false, i * 53 % 200)))
+}
+
+/// Run the operations on a tree and a `BTreeSet`, the tree must agree with the set
+/// after every change and when it is consumed from either end
+pub fn check_insert_delete<S, F>(check: F)
+where
+    S: OrderedSet<i32> + Clone + IntoIterator<Item = i32>,
+    S::IntoIter: DoubleEndedIterator,
+    F: Fn(&S),
+{
+    let mut tree = S::new();
+    let mut expected = BTreeSet::new();
+    for (is_insert, value) in operations() {
+        if is_insert {
+            assert_eq!(tree.insert(value), expected.insert(value));
+        } else {
+            assert_eq!(tree.delete(&value), expected.remove(&value));
+            assert!(!tree.delete(&value));
+        }
+        check(&tree);
+        assert_eq!(tree.len(), expected.len());
+    }
+    assert_eq!(
+        tree.iter().collect::<Vec<_>>(),
+        expected.iter().cloned().collect::<Vec<_>>()
+    );
+    let reversed = tree.clone().into_iter().rev();
+    assert!(reversed.eq(expected.iter().rev().cloned()));
+    assert!(tree.into_iter().eq(expected.into_iter()));
+}
+
+/// Fill a tree ordered by a reversed comparator, then delete the even values
+pub fn check_comparator<S, F>(check: F)
+where
+    S: OrderedSet<i32> + IntoIterator<Item = i32>,
+    F: Fn(&S),
+{
+    let mut tree = S::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    for i in 0..50 {
+        assert!(tree.insert(i * 37 % 50));
+        check(&tree);
+    }
+    assert!(!tree.insert(7));
+    assert_eq!(
+        tree.iter().collect::<Vec<_>>(),
+        (0..50).rev().collect::<Vec<_>>()
+    );
+    assert!(tree.contains(&7));
+    assert!(!tree.contains(&50));
+    assert_eq!(tree.rank(&40), 9);
+    assert_eq!(tree.floor(&60), None);
+    assert_eq!(tree.ceiling(&60), Some(49));
+    assert_eq!(tree.successor(&10), Some(9));
+    // the range runs from 30 down to 20 in the reversed order
+    let range = (Bound::Included(30), Bound::Excluded(20));
+    assert!(tree.range(range).eq((21..=30).rev()));
+    for i in (0..50).step_by(2) {
+        assert!(tree.delete(&i));
+        check(&tree);
+    }
+    assert!(!tree.delete(&0));
+    assert!(tree.into_iter().eq((0..50).rev().filter(|v| v % 2 == 1)));
+}
+
+/// Run the operations on a persistent tree, keeping every version together with a
+/// `BTreeSet` of its values, none of the older versions may change
+pub fn check_snapshots<S, I, D, F>(insert: I, delete: D, check: F)
+where
+    S: CommonTreeTrait<i32> + Default,
+    I: Fn(&S, i32) -> S,
+    D: Fn(&S, &i32) -> S,
+    F: Fn(&S),
+{
+    let mut versions = vec![S::default()];
+    let mut expected = vec![BTreeSet::new()];
+    for (is_insert, value) in operations() {
+        let mut set = expected.last().unwrap().clone();
+        let tree = if is_insert {
+            set.insert(value);
+            insert(versions.last().unwrap(), value)
+        } else {
+            set.remove(&value);
+            delete(versions.last().unwrap(), &value)
+        };
+        check(&tree);
+        versions.push(tree);
+        expected.push(set);
+    }
+    for (tree, set) in versions.iter().zip(expected.iter()) {
+        check(tree);
+        assert_eq!(tree.len(), set.len());
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            set.iter().cloned().collect::<Vec<_>>()
+        );
+    }
+}
+
+/// Whether the operation panics, for checking several operations in one test
+pub fn panics<F: FnOnce()>(operation: F) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(operation)).is_err()
+}
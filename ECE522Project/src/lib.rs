@@ -1,6 +1,23 @@
+pub mod arena;
 pub mod avlTree;
+pub mod avlTreeArena;
+pub mod avlTreeBox;
+pub mod avlTreePersistent;
+pub mod boxTree;
 pub mod bsTree;
 pub mod commonTrait;
+pub mod dot;
+#[cfg(test)]
+mod fixtures;
+pub mod nodeHandle;
+pub mod persistentTree;
 pub mod prelude;
+pub mod pretty;
 pub mod rbTree;
+pub mod rbTreeArena;
+pub mod rbTreeBox;
 pub mod rbTreeFast;
+pub mod rbTreePersistent;
+pub mod sharedTree;
+pub mod treeMap;
+pub mod treeMultiSet;
@@ -38,19 +38,15 @@ pub fn avl_promote() {
         match operation.to_lowercase().trim() {
             "insert" => {
                 let val = get_val("insert");
-                // if tree.contains(val) {
-                //     println!("This value already exists");
-                // } else {
-                //     tree.insert(val);
-                // }
+                if !tree.insert(val) {
+                    println!("This value already exists");
+                }
             }
             "delete" => {
                 let val = get_val("delete");
-                // if tree.contains(val) {
-                //     tree.delete(val);
-                // } else {
-                //     println!("This value does not exist");
-                // }
+                if !tree.delete(&val) {
+                    println!("This value does not exist");
+                }
             }
 
             "count" => println!("Number of leaves: {:?}", tree.count_leaves()),
@@ -71,10 +67,16 @@ pub fn avl_promote() {
 
             "search" => {
                 let val = get_val("search");
-                println!("values found? {:?}", tree.contains(val));
+                println!("values found? {:?}", tree.contains(&val));
             }
 
-            "print tree" => println!("Your tree: "),
+            "print tree" => {
+                if tree.is_tree_empty() {
+                    println!("There is no node in the tree!");
+                } else {
+                    print!("Your tree:\n{}", tree.pretty());
+                }
+            }
             "exit" => return,
             _ => println!("Command not recognized. Try 'help' for valid operations"),
         }
@@ -92,17 +94,13 @@ fn rbt_promote() {
         match operation.to_lowercase().trim() {
             "insert" => {
                 let val = get_val("insert");
-                if tree.contains(val) {
+                if !tree.insert(val) {
                     println!("This value already exists");
-                } else {
-                    tree.insert(val);
                 }
             }
             "delete" => {
                 let val = get_val("delete");
-                if tree.contains(val) {
-                    tree.delete(val);
-                } else {
+                if !tree.delete(&val) {
                     println!("This value does not exist");
                 }
             }
@@ -124,10 +122,16 @@ fn rbt_promote() {
 
             "search" => {
                 let val = get_val("search");
-                println!("values found? {:?}", tree.contains(val));
+                println!("values found? {:?}", tree.contains(&val));
             }
 
-            "print tree" => println!("Your tree: "),
+            "print tree" => {
+                if tree.is_tree_empty() {
+                    println!("There is no node in the tree!");
+                } else {
+                    print!("Your tree:\n{}", tree.pretty());
+                }
+            }
             "exit" => return,
             _ => println!("Command not recognized. Try 'help' for valid operations"),
         }
@@ -145,20 +149,16 @@ fn rbt_fast_promote() {
         match operation.to_lowercase().trim() {
             "insert" => {
                 let val = get_val("insert");
-                if tree.contains(val) {
+                if !tree.insert(val) {
                     println!("This value already exists");
-                } else {
-                    tree.insert(val);
                 }
             }
-            // "delete" => {
-            //     let val = get_val("delete");
-            //     if tree.contains(val) {
-            //         tree.delete(val);
-            //     } else {
-            //         println!("This value does not exist");
-            //     }
-            // }
+            "delete" => {
+                let val = get_val("delete");
+                if !tree.delete(&val) {
+                    println!("This value does not exist");
+                }
+            }
             "count" => println!("Number of leaves: {:?}", tree.count_leaves()),
 
             "height" => println!("Height of tree: {:?}", tree.height()),
@@ -177,10 +177,16 @@ fn rbt_fast_promote() {
 
             "search" => {
                 let val = get_val("search");
-                println!("values found? {:?}", tree.contains(val));
+                println!("values found? {:?}", tree.contains(&val));
             }
 
-            "print tree" => println!("Your tree: "),
+            "print tree" => {
+                if tree.is_tree_empty() {
+                    println!("There is no node in the tree!");
+                } else {
+                    print!("Your tree:\n{}", tree.pretty());
+                }
+            }
             "exit" => return,
             _ => println!("Command not recognized. Try 'help' for valid operations"),
         }
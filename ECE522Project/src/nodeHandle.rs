@@ -0,0 +1,431 @@
+//! Handles to the nodes of any tree
+//!
+//! The trees keep their nodes behind `Rc<RefCell<...>>`, in owned `Box`es or as indices
+//! into an arena. A [NodeHandle](trait.NodeHandle.html) reads a node the same way whatever
+//! the storage is, so the read-only algorithms below are written once and shared by all
+//! trees through [CommonTreeTrait](../commonTrait/trait.CommonTreeTrait.html).
+//!
+//! The algorithms walk the tree with loops or explicit stacks, so deep trees do not
+//! overflow the call stack.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+/// Read access to a node and its children, whatever the storage of the tree is
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::nodeHandle;
+/// use tree_collections::prelude::*;
+/// let mut tree = BoxAVLTree::new();
+/// for value in 1..4 {
+///     tree.insert(value);
+/// }
+/// let root = tree.get_root();
+/// assert_eq!(Some(2), root.map(|root| root.with_value(|value| *value)));
+/// assert_eq!(Some(3), root.map(|root| root.get_size()));
+/// assert_eq!(2, nodeHandle::height(root));
+/// ```
+pub trait NodeHandle<T>: Sized {
+    /// Get a handle to the left child
+    fn get_left(&self) -> Option<Self>;
+
+    /// Get a handle to the right child
+    fn get_right(&self) -> Option<Self>;
+
+    /// Get the number of nodes in the subtree rooted at the node
+    fn get_size(&self) -> usize;
+
+    /// Call `f` with a reference to the value of the node
+    fn with_value<R, F: FnOnce(&T) -> R>(&self, f: F) -> R;
+}
+
+/// Size of the subtree, 0 for an empty one
+pub fn size<T, H: NodeHandle<T>>(node: &Option<H>) -> usize {
+    node.as_ref().map_or(0, |node| node.get_size())
+}
+
+/// Count the nodes without children of the subtree
+pub fn count_leaves<T, H: NodeHandle<T>>(root: Option<H>) -> u32 {
+    let mut leaves = 0;
+    let mut stack: Vec<H> = root.into_iter().collect();
+    while let Some(node) = stack.pop() {
+        let left = node.get_left();
+        let right = node.get_right();
+        if left.is_none() && right.is_none() {
+            leaves += 1;
+        }
+        stack.extend(left.into_iter().chain(right));
+    }
+    leaves
+}
+
+/// Number of nodes on the longest path from the root to a leaf, 0 for an empty subtree
+pub fn height<T, H: NodeHandle<T>>(root: Option<H>) -> u32 {
+    let mut height = 0;
+    let mut stack: Vec<(H, u32)> = root.into_iter().map(|root| (root, 1)).collect();
+    while let Some((node, depth)) = stack.pop() {
+        height = height.max(depth);
+        for child in node.get_left().into_iter().chain(node.get_right()) {
+            stack.push((child, depth + 1));
+        }
+    }
+    height
+}
+
+/// Order in which a traversal visits the nodes of a tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// Each node before its left and right subtrees
+    PreOrder,
+    /// Each node between its left and right subtrees, which is ascending order
+    InOrder,
+    /// Each node after its left and right subtrees
+    PostOrder,
+    /// Level by level from the root, each level from left to right
+    LevelOrder,
+}
+
+/// Call `f` with the value and the depth of every node of the subtree in the given order,
+/// the depth of the root is 0
+pub fn traverse<T, H, F>(root: Option<H>, order: Order, mut f: F)
+where
+    H: NodeHandle<T>,
+    F: FnMut(&T, usize),
+{
+    if order == Order::LevelOrder {
+        for (node, depth) in LevelOrder::new(root) {
+            node.with_value(|value| f(value, depth));
+        }
+        return;
+    }
+    // a node is pushed again as visited once its children are pushed around it,
+    // in the reverse of the order they are visited in
+    let mut stack: Vec<(H, usize, bool)> = root.into_iter().map(|root| (root, 0, false)).collect();
+    while let Some((node, depth, visited)) = stack.pop() {
+        if visited {
+            node.with_value(|value| f(value, depth));
+            continue;
+        }
+        let left = node.get_left().map(|left| (left, depth + 1, false));
+        let right = node.get_right().map(|right| (right, depth + 1, false));
+        match order {
+            Order::PreOrder => {
+                stack.extend(right);
+                stack.extend(left);
+                stack.push((node, depth, true));
+            }
+            Order::InOrder => {
+                stack.extend(right);
+                stack.push((node, depth, true));
+                stack.extend(left);
+            }
+            // level order is walked with a queue above
+            Order::PostOrder | Order::LevelOrder => {
+                stack.push((node, depth, true));
+                stack.extend(right);
+                stack.extend(left);
+            }
+        }
+    }
+}
+
+/// Iterator over the nodes of a subtree level by level with their depths, the depth of
+/// the root is 0
+pub struct LevelOrder<T, H> {
+    queue: VecDeque<(H, usize)>,
+    marker: PhantomData<T>,
+}
+
+impl<T, H: NodeHandle<T>> LevelOrder<T, H> {
+    /// Start from the root of the subtree
+    pub fn new(root: Option<H>) -> Self {
+        LevelOrder {
+            queue: root.into_iter().map(|root| (root, 0)).collect(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, H: NodeHandle<T>> Iterator for LevelOrder<T, H> {
+    type Item = (H, usize);
+
+    fn next(&mut self) -> Option<(H, usize)> {
+        let (node, depth) = self.queue.pop_front()?;
+        self.queue
+            .extend(node.get_left().map(|left| (left, depth + 1)));
+        self.queue
+            .extend(node.get_right().map(|right| (right, depth + 1)));
+        Some((node, depth))
+    }
+}
+
+/// Iterator over the values of a tree level by level from the root, each level from left
+/// to right, created by the `level_order` method of the trees
+pub struct LevelOrderIter<T, H> {
+    nodes: LevelOrder<T, H>,
+}
+
+impl<T, H: NodeHandle<T>> LevelOrderIter<T, H> {
+    /// Start from the root of the tree
+    pub fn new(root: Option<H>) -> Self {
+        LevelOrderIter {
+            nodes: LevelOrder::new(root),
+        }
+    }
+}
+
+impl<T: Clone, H: NodeHandle<T>> Iterator for LevelOrderIter<T, H> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let (node, _) = self.nodes.next()?;
+        Some(node.with_value(T::clone))
+    }
+}
+
+/// Determine whether the subtree ordered by `cmp` contains the given value
+pub fn contains<T, H, F>(root: Option<H>, value: &T, cmp: &F) -> bool
+where
+    H: NodeHandle<T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut current = root;
+    while let Some(node) = current {
+        current = match node.with_value(|node_value| cmp(value, node_value)) {
+            Ordering::Equal => return true,
+            Ordering::Less => node.get_left(),
+            Ordering::Greater => node.get_right(),
+        };
+    }
+    false
+}
+
+/// Smallest value of the subtree
+pub fn min<T: Clone, H: NodeHandle<T>>(root: Option<H>) -> Option<T> {
+    let mut node = root?;
+    while let Some(left) = node.get_left() {
+        node = left;
+    }
+    Some(node.with_value(T::clone))
+}
+
+/// Largest value of the subtree
+pub fn max<T: Clone, H: NodeHandle<T>>(root: Option<H>) -> Option<T> {
+    let mut node = root?;
+    while let Some(right) = node.get_right() {
+        node = right;
+    }
+    Some(node.with_value(T::clone))
+}
+
+/// Number of values of the subtree less than the given value
+pub fn rank<T, H, F>(root: Option<H>, value: &T, cmp: &F) -> usize
+where
+    H: NodeHandle<T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    count_before_start(root, Bound::Included(value), cmp)
+}
+
+/// The k-th smallest value of the subtree, counting from 0
+pub fn select<T: Clone, H: NodeHandle<T>>(root: Option<H>, k: usize) -> Option<T> {
+    let mut k = k;
+    let mut current = root;
+    while let Some(node) = current {
+        let left = node.get_left();
+        let left_size = size(&left);
+        current = match k.cmp(&left_size) {
+            Ordering::Less => left,
+            Ordering::Equal => return Some(node.with_value(T::clone)),
+            Ordering::Greater => {
+                k -= left_size + 1;
+                node.get_right()
+            }
+        };
+    }
+    None
+}
+
+/// Descend from the root to find the closest value on the `side` of the given value,
+/// which is the value itself as well if `inclusive`
+pub fn find_closest<T, H, F>(
+    root: Option<H>,
+    value: &T,
+    cmp: &F,
+    side: Ordering,
+    inclusive: bool,
+) -> Option<T>
+where
+    T: Clone,
+    H: NodeHandle<T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut closest = None;
+    let mut current = root;
+    while let Some(node) = current {
+        let ordering = node.with_value(|node_value| cmp(node_value, value));
+        if ordering == Ordering::Equal && inclusive {
+            return Some(node.with_value(T::clone));
+        }
+        // the node is on the wanted side, look for a closer one towards the value
+        let go_left = if ordering == side {
+            closest = Some(node.with_value(T::clone));
+            side == Ordering::Greater
+        } else {
+            side == Ordering::Less
+        };
+        current = if go_left {
+            node.get_left()
+        } else {
+            node.get_right()
+        };
+    }
+    closest
+}
+
+/// Determine whether the value is not below the start bound
+pub fn after_start<T, F: Fn(&T, &T) -> Ordering>(start: Bound<&T>, value: &T, cmp: &F) -> bool {
+    match start {
+        Bound::Included(start) => cmp(value, start) != Ordering::Less,
+        Bound::Excluded(start) => cmp(value, start) == Ordering::Greater,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Determine whether the value is not above the end bound
+pub fn before_end<T, F: Fn(&T, &T) -> Ordering>(end: Bound<&T>, value: &T, cmp: &F) -> bool {
+    match end {
+        Bound::Included(end) => cmp(value, end) != Ordering::Greater,
+        Bound::Excluded(end) => cmp(value, end) == Ordering::Less,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Count the values of the subtree before the start bound
+pub fn count_before_start<T, H, F>(root: Option<H>, start: Bound<&T>, cmp: &F) -> usize
+where
+    H: NodeHandle<T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut count = 0;
+    let mut current = root;
+    while let Some(node) = current {
+        let left = node.get_left();
+        current = if node.with_value(|value| after_start(start, value, cmp)) {
+            left
+        } else {
+            count += size(&left) + 1;
+            node.get_right()
+        };
+    }
+    count
+}
+
+/// Count the values of the subtree not after the end bound
+pub fn count_until_end<T, H, F>(root: Option<H>, end: Bound<&T>, cmp: &F) -> usize
+where
+    H: NodeHandle<T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut count = 0;
+    let mut current = root;
+    while let Some(node) = current {
+        current = if node.with_value(|value| before_end(end, value, cmp)) {
+            count += size(&node.get_left()) + 1;
+            node.get_right()
+        } else {
+            node.get_left()
+        };
+    }
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    // run the shared algorithms on the root of the tree and collect what they return
+    fn summary<S: CommonTreeTrait<u32>>(tree: &S) -> Vec<Option<usize>> {
+        let root = tree.get_root();
+        let mut in_order_values = vec![];
+        traverse(root.clone(), Order::InOrder, |value, _| {
+            in_order_values.push(*value as usize)
+        });
+        let mut pre_order_len = 0;
+        traverse(root.clone(), Order::PreOrder, |_, _| pre_order_len += 1);
+        let mut post_order_depths = 0;
+        traverse(root.clone(), Order::PostOrder, |_, depth| {
+            post_order_depths += depth
+        });
+        let mut level_depths = vec![];
+        traverse(root.clone(), Order::LevelOrder, |_, depth| {
+            level_depths.push(depth)
+        });
+        assert!(level_depths.windows(2).all(|pair| pair[0] <= pair[1]));
+        vec![
+            Some(size(&root)),
+            Some(in_order_values.iter().sum()),
+            Some(height(root.clone()) as usize),
+            Some(contains(root.clone(), &40, &u32::cmp) as usize),
+            Some(contains(root.clone(), &41, &u32::cmp) as usize),
+            min(root.clone()).map(|value| value as usize),
+            max(root.clone()).map(|value| value as usize),
+            Some(rank(root.clone(), &41, &u32::cmp)),
+            select(root.clone(), 7).map(|value| value as usize),
+            find_closest(root.clone(), &41, &u32::cmp, Ordering::Less, true).map(|v| v as usize),
+            find_closest(root.clone(), &40, &u32::cmp, Ordering::Greater, false)
+                .map(|v| v as usize),
+            // the rest depends on the shape of the tree
+            Some(count_leaves(root.clone()) as usize),
+            Some(pre_order_len),
+            Some(post_order_depths),
+            LevelOrderIter::new(root)
+                .last()
+                .map(|value: u32| value as usize),
+        ]
+    }
+
+    #[test]
+    fn test_backends() {
+        let mut rc_tree = AVLTree::new();
+        let mut box_tree = BoxAVLTree::new();
+        let mut arena_tree = ArenaAVLTree::new();
+        let mut persistent_tree = PersistentAVLTree::new();
+        for value in (0..100).map(|value| value * 7 % 100 * 2) {
+            rc_tree.insert(value);
+            box_tree.insert(value);
+            arena_tree.insert(value);
+            persistent_tree = persistent_tree.insert(value);
+        }
+        let expected = summary(&rc_tree);
+        let values = vec![100, 9900, 7, 1, 0, 0, 198, 21, 14, 40, 42];
+        assert_eq!(
+            values.into_iter().map(Some).collect::<Vec<_>>(),
+            expected[..11]
+        );
+        // the same inserts build the same shape whatever the storage of the nodes is
+        assert_eq!(expected, summary(&box_tree));
+        assert_eq!(expected, summary(&arena_tree));
+        assert_eq!(expected, summary(&persistent_tree));
+    }
+
+    #[test]
+    fn test_empty() {
+        let tree: BoxRBTree<u32> = BoxRBTree::new();
+        let root = tree.get_root();
+        assert_eq!(0, size(&root));
+        assert_eq!(0, count_leaves(root));
+        assert_eq!(0, height(root));
+        assert!(!contains(root, &1, &u32::cmp));
+        assert_eq!(None, min(root));
+        assert_eq!(None, select(root, 0));
+        assert_eq!(0, rank(root, &1, &u32::cmp));
+        let arena_tree: ArenaRBTree<u32> = ArenaRBTree::new();
+        assert!(arena_tree.get_root().is_none());
+    }
+}
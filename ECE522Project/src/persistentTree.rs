@@ -0,0 +1,69 @@
+//! Nodes shared between versions of persistent trees
+//!
+//! `insert` and `delete` of a persistent tree return a new version and leave the old one
+//! untouched. Nodes are reached through `Rc` and changed with `Rc::make_mut`, so a node
+//! is copied only while another version still refers to it: a new version copies the
+//! nodes on the changed path and shares every other subtree with the old version.
+
+use std::rc::Rc;
+
+use crate::nodeHandle::NodeHandle;
+
+/// Child or root of a persistent tree, `None` if the subtree is empty
+pub type OptionPersistentNode<T, M> = Option<Rc<PersistentNode<T, M>>>;
+
+/// Node of a persistent tree, `M` is the balancing data of the tree
+#[derive(Clone, Debug)]
+pub struct PersistentNode<T, M> {
+    /// Data stored in the node
+    pub value: T,
+    pub left: OptionPersistentNode<T, M>,
+    pub right: OptionPersistentNode<T, M>,
+    /// Number of nodes in the subtree rooted at this node
+    pub size: usize,
+    /// Height for AVL trees or color for red-black trees
+    pub meta: M,
+}
+
+/// Implementations of PersistentNode
+impl<T, M> PersistentNode<T, M> {
+    /// Create a new leaf node
+    pub fn new(value: T, meta: M) -> Rc<Self> {
+        Rc::new(PersistentNode {
+            value,
+            left: None,
+            right: None,
+            size: 1,
+            meta,
+        })
+    }
+
+    /// Recount the subtree size of the node from its children
+    pub fn update_size(&mut self) {
+        self.size = size(&self.left) + size(&self.right) + 1;
+    }
+}
+
+// a persistent node is reached through a plain reference, as versions only read it
+impl<T, M> NodeHandle<T> for &PersistentNode<T, M> {
+    fn get_left(&self) -> Option<Self> {
+        self.left.as_deref()
+    }
+
+    fn get_right(&self) -> Option<Self> {
+        self.right.as_deref()
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn with_value<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        f(&self.value)
+    }
+}
+
+/// Size of the subtree, 0 for an empty one
+pub fn size<T, M>(node: &OptionPersistentNode<T, M>) -> usize {
+    node.as_ref().map_or(0, |node| node.size)
+}
@@ -1,5 +1,17 @@
 pub use crate::avlTree::AVLTree;
+pub use crate::avlTreeArena::ArenaAVLTree;
+pub use crate::avlTreeBox::BoxAVLTree;
+pub use crate::avlTreePersistent::PersistentAVLTree;
 pub use crate::bsTree::BSTree;
-pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait};
+pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait, OrderedSet};
+pub use crate::dot::ToDot;
+pub use crate::nodeHandle::{NodeHandle, Order};
+pub use crate::pretty::Pretty;
 pub use crate::rbTree::RBTree;
+pub use crate::rbTreeArena::ArenaRBTree;
+pub use crate::rbTreeBox::BoxRBTree;
 pub use crate::rbTreeFast::FastRBTree;
+pub use crate::rbTreePersistent::PersistentRBTree;
+pub use crate::sharedTree::SharedTree;
+pub use crate::treeMap::{AVLMap, BSMap, FastRBMap, RBMap};
+pub use crate::treeMultiSet::{AVLMultiSet, BSMultiSet, FastRBMultiSet, RBMultiSet};
@@ -0,0 +1,182 @@
+//! Text drawings of trees
+//!
+//! [Pretty](trait.Pretty.html) draws a tree sideways with box-drawing characters: the
+//! root is on the left, right subtrees above their parent and left subtrees below it, so
+//! the values read in descending order from top to bottom. AVL nodes show their height,
+//! red-black nodes their color, `r` or `b`.
+
+use std::fmt;
+
+use crate::nodeHandle::NodeHandle;
+
+/// Draw a tree as text
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::prelude::*;
+/// let mut tree = RBTree::new();
+/// for value in 1..5 {
+///     tree.insert(value);
+/// }
+/// let expected = "        ┌── 4 (r)\n    ┌── 3 (b)\n2 (b)\n    └── 1 (b)\n";
+/// assert_eq!(expected, tree.pretty());
+/// assert_eq!("", RBTree::<u32>::new().pretty());
+/// ```
+pub trait Pretty {
+    /// Draw the tree sideways, one node per line, an empty tree is an empty string
+    fn pretty(&self) -> String;
+}
+
+// where a node hangs from its parent
+#[derive(Clone, Copy, PartialEq)]
+enum Side {
+    Root,
+    Right,
+    Left,
+}
+
+// the nodes still to draw, and the lines of nodes whose right subtree is drawn
+enum Step<H> {
+    Node(H, String, Side),
+    Line(String),
+}
+
+/// Draw the subtree sideways, `tag` tells what is written after the value of each node
+pub fn pretty<T, H, F>(root: Option<H>, tag: F) -> String
+where
+    T: fmt::Debug,
+    H: NodeHandle<T>,
+    F: Fn(&H) -> Option<String>,
+{
+    let mut text = String::new();
+    let mut stack: Vec<Step<H>> = root
+        .into_iter()
+        .map(|root| Step::Node(root, String::new(), Side::Root))
+        .collect();
+    // walk in reverse order with an explicit stack, so that deep trees do not overflow
+    while let Some(step) = stack.pop() {
+        let (node, prefix, side) = match step {
+            Step::Node(node, prefix, side) => (node, prefix, side),
+            Step::Line(line) => {
+                text.push_str(&line);
+                continue;
+            }
+        };
+        let mut line = prefix.clone();
+        line.push_str(match side {
+            Side::Root => "",
+            Side::Right => "┌── ",
+            Side::Left => "└── ",
+        });
+        line.push_str(&node.with_value(|value| format!("{:?}", value)));
+        if let Some(tag) = tag(&node) {
+            line.push_str(&format!(" ({})", tag));
+        }
+        line.push('\n');
+        // the vertical bar joins a subtree to its parent across the other subtree
+        let (right_indent, left_indent) = match side {
+            Side::Root => ("    ", "    "),
+            Side::Right => ("    ", "│   "),
+            Side::Left => ("│   ", "    "),
+        };
+        if let Some(left) = node.get_left() {
+            stack.push(Step::Node(left, prefix.clone() + left_indent, Side::Left));
+        }
+        stack.push(Step::Line(line));
+        if let Some(right) = node.get_right() {
+            stack.push(Step::Node(right, prefix + right_indent, Side::Right));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_pretty() {
+        let mut tree = BSTree::new();
+        for value in [4, 2, 6, 1, 3, 5, 7] {
+            tree.insert(value);
+        }
+        let expected = "        ┌── 7\n\
+                        \x20   ┌── 6\n\
+                        \x20   │   └── 5\n\
+                        4\n\
+                        \x20   │   ┌── 3\n\
+                        \x20   └── 2\n\
+                        \x20       └── 1\n";
+        assert_eq!(expected, tree.pretty());
+    }
+
+    #[test]
+    fn test_heights() {
+        let expected = "        ┌── 3 (h=1)\n    ┌── 2 (h=2)\n1 (h=3)\n    └── 0 (h=1)\n";
+        let mut trees: Vec<Box<dyn Pretty>> = vec![];
+        let mut avl_tree = AVLTree::new();
+        let mut box_tree = BoxAVLTree::new();
+        let mut arena_tree = ArenaAVLTree::new();
+        let mut persistent_tree = PersistentAVLTree::new();
+        for value in 0..4 {
+            avl_tree.insert(value);
+            box_tree.insert(value);
+            arena_tree.insert(value);
+            persistent_tree = persistent_tree.insert(value);
+        }
+        trees.push(Box::new(avl_tree));
+        trees.push(Box::new(box_tree));
+        trees.push(Box::new(arena_tree));
+        trees.push(Box::new(persistent_tree));
+        for tree in trees {
+            assert_eq!(expected, tree.pretty());
+        }
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut trees: Vec<Box<dyn Pretty>> = vec![];
+        let mut rb_tree = RBTree::new();
+        let mut fast_tree = FastRBTree::new();
+        let mut box_tree = BoxRBTree::new();
+        let mut arena_tree = ArenaRBTree::new();
+        let mut persistent_tree = PersistentRBTree::new();
+        for value in 0..10 {
+            rb_tree.insert(value);
+            fast_tree.insert(value);
+            box_tree.insert(value);
+            arena_tree.insert(value);
+            persistent_tree = persistent_tree.insert(value);
+        }
+        trees.push(Box::new(rb_tree));
+        trees.push(Box::new(fast_tree));
+        trees.push(Box::new(box_tree));
+        trees.push(Box::new(arena_tree));
+        trees.push(Box::new(persistent_tree));
+        for tree in trees {
+            let text = tree.pretty();
+            assert_eq!(10, text.lines().count());
+            // the root is black and the values read in descending order
+            assert!(text.contains("\n3 (b)\n"));
+            let values: Vec<u32> = text
+                .lines()
+                .map(|line| line.trim_start_matches(|c| " │┌└─".contains(c)))
+                .map(|line| line.split(' ').next().unwrap().parse().unwrap())
+                .collect();
+            assert_eq!((0..10).rev().collect::<Vec<_>>(), values);
+            assert!(text.contains(" (r)"));
+        }
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let mut tree = BSTree::new();
+        for value in 0..2000 {
+            tree.insert(value);
+        }
+        let text = tree.pretty();
+        assert_eq!(2000, text.lines().count());
+        assert!(text.ends_with("0\n"));
+    }
+}
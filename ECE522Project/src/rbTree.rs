@@ -2,12 +2,20 @@
 //!
 //! You can generate a red-black tree, and insert or delete nodes.
 
-use std::cell::RefCell;
-use std::cmp::max;
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
 use std::fmt;
-use std::rc::Rc;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+use std::rc::{Rc, Weak};
 
-pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait};
+use crate::commonTrait::{
+    clone_nodes, collect_sorted, dedup_sorted, drop_nodes, is_sorted, Comparator, IntoIter, Iter,
+};
+pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait, OrderedSet};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+use crate::treeMap::MapBackend;
 
 /// Color representation for the [TreeNode](struct.TreeNode.html)
 /// of [RBTree](struct.RBTree.html) struct
@@ -26,25 +34,63 @@ enum NodeDirection {
 }
 
 /// Structure of RBTree
-#[derive(Clone, Debug, PartialEq)]
-pub struct RBTree<T: Ord + Copy + fmt::Debug> {
+#[derive(Debug, PartialEq)]
+pub struct RBTree<T: Ord> {
     root: OptionRBTreeNode<T>,
+    comparator: Comparator<T>,
 }
 
 /// Node struct for [RBTree](struct.RBTree.html) struct
-#[derive(Clone, Debug, PartialEq)]
-pub struct TreeNode<T: Ord + Copy + fmt::Debug> {
+#[derive(Clone)]
+pub struct TreeNode<T: Ord> {
     /// The color of the node
     color: NodeColor,
     /// Data stored in the node
     value: T,
-    parent: OptionRBTreeNode<T>,
+    /// Weak back-link to the parent, so that parents and children do not keep each other alive
+    parent: Option<Weak<RefCell<TreeNode<T>>>>,
     left: OptionRBTreeNode<T>,
     right: OptionRBTreeNode<T>,
+    /// Number of nodes in the subtree rooted at this node
+    size: usize,
 }
 
 type RBTreeNode<T> = Rc<RefCell<TreeNode<T>>>;
 type OptionRBTreeNode<T> = Option<RBTreeNode<T>>;
+// a tree with its black height
+type SubTree<T> = (OptionRBTreeNode<T>, usize);
+// the trees less and greater than a value with their black heights, and the node of the value
+type SplitTrees<T> = (
+    OptionRBTreeNode<T>,
+    usize,
+    OptionRBTreeNode<T>,
+    OptionRBTreeNode<T>,
+    usize,
+);
+
+// the parent link is skipped, following it would loop back to this node
+impl<T: Ord> PartialEq for TreeNode<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.color == other.color
+            && self.value == other.value
+            && self.left == other.left
+            && self.right == other.right
+            && self.size == other.size
+    }
+}
+
+// the parent link is skipped, following it would loop back to this node
+impl<T: Ord + fmt::Debug> fmt::Debug for TreeNode<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeNode")
+            .field("color", &self.color)
+            .field("value", &self.value)
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .field("size", &self.size)
+            .finish()
+    }
+}
 
 /// Implementations of NodeColor
 impl NodeColor {
@@ -55,17 +101,35 @@ impl NodeColor {
             "b"
         }
     }
+
+    // the color of the node in a Graphviz graph
+    fn fill(&self) -> &'static str {
+        if self == &NodeColor::Red {
+            "red"
+        } else {
+            "black"
+        }
+    }
 }
 
 // extend from common tree trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeTrait<T, TreeNode<T>> for RBTree<T> {
+impl<T: Ord> CommonTreeTrait<T> for RBTree<T> {
+    type Handle<'a>
+        = RBTreeNode<T>
+    where
+        Self: 'a;
+
     fn get_root(&self) -> OptionRBTreeNode<T> {
         return self.root.clone();
     }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
 }
 
 // extend from common tree node trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
+impl<T: Ord> CommonTreeNodeTrait<T> for TreeNode<T> {
     fn get_left(&self) -> OptionRBTreeNode<T> {
         return self.left.clone();
     }
@@ -74,106 +138,267 @@ impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
         return self.right.clone();
     }
 
-    fn get_value(&self) -> T {
-        return self.value;
+    fn get_value(&self) -> &T {
+        &self.value
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn take_left(&mut self) -> OptionRBTreeNode<T> {
+        let left = self.left.take();
+        if let Some(left) = &left {
+            left.borrow_mut().parent = None;
+        }
+        left
+    }
+
+    fn take_right(&mut self) -> OptionRBTreeNode<T> {
+        let right = self.right.take();
+        if let Some(right) = &right {
+            right.borrow_mut().parent = None;
+        }
+        right
+    }
+
+    fn into_value(self) -> T {
+        self.value
+    }
+}
+
+// free the nodes without recursing through them
+impl<T: Ord> Drop for RBTree<T> {
+    fn drop(&mut self) {
+        drop_nodes(self.root.take());
+    }
+}
+
+// copy every node, so that changing the copy leaves the original alone
+impl<T: Ord + Clone> Clone for RBTree<T> {
+    fn clone(&self) -> Self {
+        let root = clone_nodes(&self.root, |node, left, right| {
+            let copy = Rc::new(RefCell::new(TreeNode {
+                color: node.color.clone(),
+                value: node.value.clone(),
+                parent: None,
+                left: left.clone(),
+                right: right.clone(),
+                size: node.size,
+            }));
+            // link the copied children back to the copy
+            for child in left.iter().chain(right.iter()) {
+                child.borrow_mut().parent = Some(Rc::downgrade(&copy));
+            }
+            copy
+        });
+        RBTree {
+            root,
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for RBTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, RBTreeNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter::new(self.root.take(), len)
+    }
+}
+
+// iterate over the tree in order
+impl<'a, T: Ord + Clone> IntoIterator for &'a RBTree<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, RBTreeNode<T>>;
+
+    fn into_iter(self) -> Iter<'a, T, RBTreeNode<T>> {
+        self.iter()
+    }
+}
+
+// build the tree from values in ascending order directly, or insert them one by one
+impl<T: Ord> FromIterator<T> for RBTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = RBTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+// values in ascending order are built into a tree and appended, joining it in O(log n)
+// time when the two do not overlap, values in any other order are inserted one by one
+impl<T: Ord> Extend<T> for RBTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let values: Vec<T> = iter.into_iter().collect();
+        if is_sorted(&values, &self.comparator) {
+            let values = dedup_sorted(values, &self.comparator);
+            let mut other = Self::from_sorted_values(values, self.comparator.clone());
+            self.append(&mut other);
+        } else {
+            for value in values {
+                self.insert(value);
+            }
+        }
+    }
+}
+
+// construct and mutate RBTree in generic code
+impl<T: Ord> OrderedSet<T> for RBTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        RBTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        RBTree {
+            root: None,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        RBTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        RBTree::delete(self, value)
+    }
+}
+
+// back a TreeMap with RBTree
+impl<T: Ord> MapBackend<T> for RBTree<T> {
+    type Node = TreeNode<T>;
+
+    fn get_root_node(&self) -> OptionRBTreeNode<T> {
+        self.root.clone()
+    }
+
+    fn borrow_value(node: &Rc<RefCell<TreeNode<T>>>) -> Ref<'_, T> {
+        Ref::map(node.borrow(), |node| &node.value)
+    }
+
+    fn borrow_value_mut(node: &Rc<RefCell<TreeNode<T>>>) -> RefMut<'_, T> {
+        RefMut::map(node.borrow_mut(), |node| &mut node.value)
+    }
+
+    fn replace(&mut self, value: T) -> Option<T> {
+        match self.root.clone() {
+            None => {
+                self.root = Some(TreeNode::set_black(Rc::new(RefCell::new(TreeNode::new(
+                    value,
+                )))));
+                None
+            }
+            Some(root) => TreeNode::node_insert(&mut self.root, root, value, &self.comparator),
+        }
+    }
+
+    fn remove_node<F: Fn(&T) -> Ordering>(&mut self, cmp: &F) -> OptionRBTreeNode<T> {
+        let root = self.root.clone()?;
+        TreeNode::node_delete(&mut self.root, root, cmp)
+    }
+}
+
+// draw RBTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for RBTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        let nil_leaf = nil_leaves.then(|| DotStyle::filled(NodeColor::Black.fill()));
+        dot::to_dot(self.get_root(), nil_leaf, |node| {
+            DotStyle::filled(node.borrow().color.fill())
+        })
+    }
+}
+
+// draw RBTree as text
+impl<T: Ord + fmt::Debug> Pretty for RBTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.get_root(), |node| {
+            Some(node.borrow().color.to_string().to_owned())
+        })
+    }
+}
+
+impl<T: Ord> Default for RBTree<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Implementations of RBTree
 // RBTree
-impl<T: Ord + Copy + fmt::Debug> RBTree<T> {
-
+impl<T: Ord> RBTree<T> {
     /// Create a new red-black Tree
     ///
     /// # Example
     ///
     /// ```
     /// use tree_collections::rbTree::RBTree;
-    /// let mut tree = RBTree::new();
+    /// let mut tree: RBTree<u32> = RBTree::new();
     /// ```
     pub fn new() -> Self {
-        RBTree { root: None }
+        RBTree {
+            root: None,
+            comparator: Comparator::default(),
+        }
     }
 
-    /// Insert a new value to the red-black Tree
+    /// Create a new red-black Tree ordered by the comparator instead of `Ord`,
+    /// which tells how the first value compares with the second one
     ///
     /// # Example
     ///
     /// ```
-    /// use tree_collections::rbTree::RBTree;
-    /// let mut tree = RBTree::new();
-    /// tree.insert(1);
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    /// for value in 0..5 {
+    ///     tree.insert(value);
+    /// }
+    /// assert_eq!(vec![4, 3, 2, 1, 0], tree.iter().collect::<Vec<_>>());
+    /// assert_eq!(Some(2), tree.floor(&2));
     /// ```
-    pub fn insert(&mut self, insert_value: T) {
-        let root = self.root.clone();
-        self.root = match root {
-            None => Some(TreeNode::set_black(Rc::new(RefCell::new(TreeNode::new(
-                insert_value,
-            ))))),
-            Some(root) => TreeNode::node_insert(root, insert_value),
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        RBTree {
+            root: None,
+            comparator: Comparator::new(cmp),
         }
     }
 
-    /// Delete a value from the red-black Tree
+    /// Insert a new value to the red-black Tree, return false if the value already exists
     ///
     /// # Example
     ///
     /// ```
     /// use tree_collections::rbTree::RBTree;
     /// let mut tree = RBTree::new();
-    /// tree.delete(1);
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
     /// ```
-    pub fn delete(&mut self, delete_value: T) {
-        let root = self.root.clone();
-        match root {
-            None => (),
-            Some(root) => {
-                let result = TreeNode::node_delete(root, delete_value);
-                self.root = result;
-            }
-        }
-    }
-
-    pub fn pre_order_traverse(&self, node: RBTreeNode<T>, container: &mut Vec<T>) {
-        container.push(node.borrow().value);
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.pre_order_traverse(left.unwrap(), container);
-        }
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.pre_order_traverse(right.unwrap(), container);
-        }
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        self.replace(insert_value).is_none()
     }
 
-    // 下面这三个之后会不要，用上面的in_order_traversal
-    pub fn debug_preorder_traverse(&self, node: RBTreeNode<T>, container: &mut Vec<T>) {
-        container.push(node.borrow().value);
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.debug_preorder_traverse(left.unwrap(), container);
-        }
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.debug_preorder_traverse(right.unwrap(), container);
-        }
-    }
-
-    // for testing
-    pub fn inorder_traverse(&self, node: RBTreeNode<T>, container: &mut Vec<T>) {
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.inorder_traverse(left.unwrap(), container);
-        }
-        container.push(node.borrow().value);
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.inorder_traverse(right.unwrap(), container);
-        }
+    /// Delete a value from the red-black Tree, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTree::RBTree;
+    /// let mut tree = RBTree::new();
+    /// tree.insert(1);
+    /// assert!(tree.delete(&1));
+    /// assert!(!tree.delete(&1));
+    /// ```
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        let comparator = self.comparator.clone();
+        self.remove_node(&|value: &T| comparator.compare(delete_value, value))
+            .is_some()
     }
 
     /// Return true if the tree match propertity 5
-    /// Propertity 5. Every path from any node to all of its descendent Nil nodes 
+    /// Propertity 5. Every path from any node to all of its descendent Nil nodes
     /// has the same number of black nodes.
     pub fn is_valid_red_black_tree(root: OptionRBTreeNode<T>) -> bool {
         let result = TreeNode::calculate_black_height(root);
@@ -183,16 +408,13 @@ impl<T: Ord + Copy + fmt::Debug> RBTree<T> {
         }
     }
 
-    
     pub fn is_equal(left: OptionRBTreeNode<T>, right: OptionRBTreeNode<T>) -> bool {
         match (left, right) {
             (None, None) => true,
             (Some(_), None) | (None, Some(_)) => false,
             (Some(left), Some(right)) => {
-                let left_data = left.borrow().value;
-                let right_data = right.borrow().value;
                 //Test if 2 trees are equal
-                if left_data == right_data {
+                if left.borrow().value == right.borrow().value {
                     let left_left = left.borrow().left.clone();
                     let left_right = left.borrow().right.clone();
                     let right_left = right.borrow().left.clone();
@@ -208,8 +430,7 @@ impl<T: Ord + Copy + fmt::Debug> RBTree<T> {
 
 /// Implementations of TreeNode
 // TreeNode
-impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
-
+impl<T: Ord> TreeNode<T> {
     /// Create a new node
     /// ,which will be called by [RBTree](struct.RBTree.html)
     fn new(value: T) -> Self {
@@ -219,6 +440,7 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
             parent: None,
             left: None,
             right: None,
+            size: 1,
         }
     }
 
@@ -227,9 +449,10 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         TreeNode {
             color: NodeColor::Red,
             value: value,
-            parent: parent,
+            parent: parent.as_ref().map(Rc::downgrade),
             left: None,
             right: None,
+            size: 1,
         }
     }
 
@@ -238,61 +461,75 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         TreeNode {
             color: NodeColor::Black,
             value: value,
-            parent: parent,
+            parent: parent.as_ref().map(Rc::downgrade),
             left: None,
             right: None,
+            size: 1,
         }
     }
 
     /// Insert data into the subtree, performs any rotations
-    /// necessary to maintain banlance, and then returns the new root to this subtree
-    /// , which will be called by
+    /// necessary to maintain banlance, updates `root` if it changes,
+    /// and then returns the replaced equal value, which will be called by
     /// [RBTree.insert](struct.RBTree.html#method.insert)
-    fn node_insert(node: RBTreeNode<T>, insert_value: T) -> OptionRBTreeNode<T> {
-        if node.borrow().value == insert_value {
-            return Some(node);
-        } else if node.borrow().value > insert_value {
-            let left = node.borrow().left.clone();
-            match left {
-                Some(left) => {
-                    Self::node_insert(left, insert_value);
-                }
-                None => {
-                    node.borrow_mut().left = Some(Rc::new(RefCell::new(
-                        TreeNode::new_with_parent(insert_value, Some(node.clone())),
-                    )));
-                    let left = node.borrow().left.clone();
-                    Self::insert_maintain_rb(left.unwrap());
+    fn node_insert(
+        root: &mut OptionRBTreeNode<T>,
+        node: RBTreeNode<T>,
+        insert_value: T,
+        comparator: &Comparator<T>,
+    ) -> Option<T> {
+        let ordering = comparator.compare(&insert_value, &node.borrow().value);
+        match ordering {
+            // equal, update value
+            Ordering::Equal => Some(std::mem::replace(
+                &mut node.borrow_mut().value,
+                insert_value,
+            )),
+            Ordering::Less => {
+                let left = node.borrow().left.clone();
+                match left {
+                    Some(left) => Self::node_insert(root, left, insert_value, comparator),
+                    None => {
+                        node.borrow_mut().left = Some(Rc::new(RefCell::new(
+                            TreeNode::new_with_parent(insert_value, Some(node.clone())),
+                        )));
+                        Self::update_size_upward(node.clone());
+                        let left = node.borrow().left.clone();
+                        Self::insert_maintain_rb(root, left.unwrap());
+                        None
+                    }
                 }
             }
-        } else {
-            let right = node.borrow().right.clone();
-            match right {
-                Some(right) => {
-                    Self::node_insert(right, insert_value);
-                }
-                None => {
-                    node.borrow_mut().right = Some(Rc::new(RefCell::new(
-                        TreeNode::new_with_parent(insert_value, Some(node.clone())),
-                    )));
-                    let right = node.borrow().right.clone();
-                    Self::insert_maintain_rb(right.unwrap());
+            Ordering::Greater => {
+                let right = node.borrow().right.clone();
+                match right {
+                    Some(right) => Self::node_insert(root, right, insert_value, comparator),
+                    None => {
+                        node.borrow_mut().right = Some(Rc::new(RefCell::new(
+                            TreeNode::new_with_parent(insert_value, Some(node.clone())),
+                        )));
+                        Self::update_size_upward(node.clone());
+                        let right = node.borrow().right.clone();
+                        Self::insert_maintain_rb(root, right.unwrap());
+                        None
+                    }
                 }
             }
         }
-        // return the root
-        return Self::get_root(node);
     }
 
-    /// Repair the coloring from inserting into a tree.
-    fn insert_maintain_rb(node: RBTreeNode<T>) {
-        let parent = node.borrow().parent.clone();
+    /// Repair the coloring from inserting into a tree, return whether the black height
+    /// of the tree grows, which happens when the root turns from red to black.
+    fn insert_maintain_rb(root: &mut OptionRBTreeNode<T>, node: RBTreeNode<T>) -> bool {
+        let parent = Self::get_parent(&node);
+        let mut grows = false;
 
         match parent {
             None => {
                 // insert case 3: node is root, no parent
                 // println!("insert case 3: node is root, no parent");
                 Self::set_black(node);
+                grows = true;
             }
             // Check parent color
             Some(parent) => {
@@ -308,12 +545,13 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                 }
                 // parent is red, need maintain
                 else {
-                    let grand_parent = parent.borrow().parent.clone();
+                    let grand_parent = Self::get_parent(&parent);
                     match grand_parent {
                         None => {
                             // insert case 4: grandparent is None, then parent goes to black
                             // println!("insert case 4");
                             Self::set_black(parent);
+                            grows = true;
                         }
                         Some(grand_parent) => {
                             // println!(
@@ -331,8 +569,8 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                 if Self::get_color(option_uncle.clone()) == NodeColor::Black {
                                     // insert case 6.1: left left && uncle is None or black
                                     // println!("insert case 6.1: left left && uncle is None");
-                                    Self::right_rotate(grand_parent.clone());
-                                    let parent = node.borrow().parent.clone().unwrap();
+                                    Self::right_rotate(root, grand_parent.clone());
+                                    let parent = Self::get_parent(&node).unwrap();
                                     Self::set_black(parent.clone());
                                     let right = parent.borrow().right.clone().unwrap();
                                     Self::set_red(right.clone());
@@ -348,7 +586,8 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                         Self::set_black(parent.clone());
                                         Self::set_black(uncle.clone());
                                         Self::set_red(grand_parent.clone());
-                                        Self::insert_maintain_rb(grand_parent.clone());
+                                        grows =
+                                            Self::insert_maintain_rb(root, grand_parent.clone());
                                     }
                                 }
                             } else if Self::is_right(parent.clone()) && Self::is_right(node.clone())
@@ -363,8 +602,8 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                 if Self::get_color(option_uncle.clone()) == NodeColor::Black {
                                     // insert case 6.2: right right && uncle is None or black
                                     // println!("insert case 6.2: right right && uncle is None");
-                                    Self::left_rotate(grand_parent.clone());
-                                    let parent = node.borrow().parent.clone().unwrap();
+                                    Self::left_rotate(root, grand_parent.clone());
+                                    let parent = Self::get_parent(&node).unwrap();
                                     Self::set_black(parent.clone());
                                     let left = parent.borrow().left.clone().unwrap();
                                     Self::set_red(left.clone());
@@ -379,7 +618,7 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                     Self::set_black(parent.clone());
                                     Self::set_black(uncle.clone());
                                     Self::set_red(grand_parent.clone());
-                                    Self::insert_maintain_rb(grand_parent.clone());
+                                    grows = Self::insert_maintain_rb(root, grand_parent.clone());
                                 }
                             } else if Self::is_left(parent.clone()) && Self::is_right(node.clone())
                             {
@@ -393,9 +632,9 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                     //     parent.clone().borrow().value,
                                     //     grand_parent.clone().borrow().value
                                     // );
-                                    Self::left_rotate(parent.clone());
+                                    Self::left_rotate(root, parent.clone());
                                     let left_child = node.borrow().left.clone().unwrap();
-                                    Self::insert_maintain_rb(left_child.clone());
+                                    grows = Self::insert_maintain_rb(root, left_child.clone());
                                 } else {
                                     let uncle = option_uncle.clone().unwrap();
                                     // println!(
@@ -407,7 +646,7 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                     Self::set_black(parent.clone());
                                     Self::set_black(uncle.clone());
                                     Self::set_red(grand_parent.clone());
-                                    Self::insert_maintain_rb(grand_parent.clone());
+                                    grows = Self::insert_maintain_rb(root, grand_parent.clone());
                                 }
                             } else if Self::is_right(parent.clone()) && Self::is_left(node.clone())
                             {
@@ -421,9 +660,9 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                 if Self::get_color(option_uncle.clone()) == NodeColor::Black {
                                     // insert case 5.2: right left && uncle is None or black
                                     // println!("insert case 5.2: right left && uncle is None");
-                                    Self::right_rotate(parent.clone());
+                                    Self::right_rotate(root, parent.clone());
                                     let right_child = node.borrow().right.clone().unwrap();
-                                    Self::insert_maintain_rb(right_child.clone());
+                                    grows = Self::insert_maintain_rb(root, right_child.clone());
                                 } else {
                                     let uncle = option_uncle.clone().unwrap();
                                     // println!(
@@ -435,7 +674,7 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                     Self::set_black(parent.clone());
                                     Self::set_black(uncle.clone());
                                     Self::set_red(grand_parent.clone());
-                                    Self::insert_maintain_rb(grand_parent.clone());
+                                    grows = Self::insert_maintain_rb(root, grand_parent.clone());
                                 }
                             }
                         }
@@ -443,128 +682,129 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                 }
             }
         }
+        grows
     }
 
     /// Delete data from the subtree, performs any rotations
-    /// necessary to maintain banlance, and then returns the new root to this subtree
-    /// , which will be called by
+    /// necessary to maintain banlance, updates `root` if it changes,
+    /// and then returns the detached node holding the deleted value, which will be called by
     /// [RBTree.delete](struct.RBTree.html#method.delete)
-    fn node_delete(node: RBTreeNode<T>, delete_value: T) -> OptionRBTreeNode<T> {
-        if node.borrow().value > delete_value {
-            let left_child = node.borrow().left.clone();
-            if left_child.is_some() {
-                Self::node_delete(left_child.unwrap(), delete_value);
+    fn node_delete<F: Fn(&T) -> Ordering>(
+        root: &mut OptionRBTreeNode<T>,
+        node: RBTreeNode<T>,
+        cmp: &F,
+    ) -> OptionRBTreeNode<T> {
+        let ordering = cmp(&node.borrow().value);
+        match ordering {
+            Ordering::Less => {
+                let left_child = node.borrow().left.clone();
+                match left_child {
+                    Some(left_child) => Self::node_delete(root, left_child, cmp),
+                    None => None,
+                }
             }
-        } else if node.borrow().value < delete_value {
-            let right_child = node.borrow_mut().right.clone();
-            if right_child.is_some() {
-                Self::node_delete(right_child.unwrap(), delete_value);
+            Ordering::Greater => {
+                let right_child = node.borrow().right.clone();
+                match right_child {
+                    Some(right_child) => Self::node_delete(root, right_child, cmp),
+                    None => None,
+                }
             }
-        } else {
-            let left = node.borrow_mut().left.clone();
-            let right = node.borrow_mut().right.clone();
-            // 1. Two children case: current node has two children
-            // if current node has two children, then recursively replace it with the min value of right
-            // delete the min value of right in the right tree
-            // the goal is to make the problem to be the case where current node has only one child
-            if left.is_some() && right.is_some() {
-                let min_of_right = right.clone().unwrap().borrow().get_min_value_in_children();
-                node.borrow_mut().value = min_of_right;
-                Self::node_delete(right.unwrap(), min_of_right);
-            }
-            // current node has one child or no child
-            else {
-                // 2. Red case: current node is red
-                // it means that current node has no child, just delete this node
-                if node.borrow().color == NodeColor::Red {
-                    let parent = node.borrow().parent.clone().unwrap();
+            Ordering::Equal => {
+                let left = node.borrow().left.clone();
+                let right = node.borrow().right.clone();
+                // 1. Two children case: current node has two children
+                // if current node has two children, then swap its value with the min value of right
+                // and delete the swapped value in the right tree, which is smaller than all others there
+                // the goal is to make the problem to be the case where current node has only one child
+                if let (Some(_), Some(right)) = (left, right) {
+                    let min_of_right = Self::get_min_node(right.clone());
+                    std::mem::swap(
+                        &mut node.borrow_mut().value,
+                        &mut min_of_right.borrow_mut().value,
+                    );
+                    Self::node_delete(root, right, cmp)
+                }
+                // current node has one child or no child
+                else {
+                    Self::detach_node(root, node.clone());
+                    Some(node)
+                }
+            }
+        }
+    }
+
+    /// Unlink a node with at most one child from the tree, repair the coloring
+    /// and update `root` if it changes
+    fn detach_node(root: &mut OptionRBTreeNode<T>, node: RBTreeNode<T>) {
+        let left = node.borrow().left.clone();
+        let right = node.borrow().right.clone();
+        let parent = Self::get_parent(&node);
+        // 2. Red case: current node is red
+        // it means that current node has no child, just delete this node
+        if node.borrow().color == NodeColor::Red {
+            let parent = parent.unwrap();
+            if Self::is_left(node.clone()) {
+                parent.borrow_mut().left = None;
+            } else {
+                parent.borrow_mut().right = None;
+            }
+            Self::update_size_upward(parent);
+        }
+        // 3. Black + red child case: current node is black and its only child is red
+        // delete the current black node and move the child to the current node place
+        else if let Some(child) = left.or(right) {
+            child.borrow_mut().color = NodeColor::Black;
+            child.borrow_mut().parent = parent.as_ref().map(Rc::downgrade);
+            match parent {
+                None => *root = Some(child),
+                Some(parent) => {
                     if Self::is_left(node.clone()) {
-                        parent.borrow_mut().left = None;
+                        parent.borrow_mut().left = Some(child);
                     } else {
-                        parent.borrow_mut().right = None;
+                        parent.borrow_mut().right = Some(child);
                     }
+                    Self::update_size_upward(parent);
                 }
-                // current node is black
-                else {
-                    // 3.1 Black + left red case: current node is black and left child is red, right child is None
-                    // delete the current black node and move the left child to the current node place
-                    if left.is_some() && right.is_none() {
-                        let left = left.unwrap();
-                        // TODO
-                        // node.parent.child = left, left.parent = node.parent
-                        let parent = node.borrow().parent.clone();
-                        match parent {
-                            None => {
-                                left.borrow_mut().color = node.borrow().color.clone();
-                                left.borrow_mut().parent = None;
-                                return Some(left);
-                            }
-                            Some(parent) => {
-                                if Self::is_left(node.clone()) {
-                                    parent.borrow_mut().left = Some(left.clone());
-                                    left.borrow_mut().parent = Some(parent.clone());
-                                    left.borrow_mut().color = node.borrow().color.clone();
-                                } else {
-                                    parent.borrow_mut().right = Some(left.clone());
-                                    left.borrow_mut().parent = Some(parent.clone());
-                                    left.borrow_mut().color = node.borrow().color.clone();
-                                }
-                            }
-                        }
-                    }
-                    // 3.2 Black + right red case: current node is black and right child is red, left child is None
-                    // delete the current black node and move the right child to the current node place
-                    else if left.is_none() && right.is_some() {
-                        let right = right.unwrap();
-                        let parent = node.borrow().parent.clone();
-                        match parent {
-                            None => {
-                                right.borrow_mut().color = node.borrow().color.clone();
-                                right.borrow_mut().parent = None;
-                                return Some(right);
-                            }
-                            Some(parent) => {
-                                if Self::is_left(node.clone()) {
-                                    parent.borrow_mut().left = Some(right.clone());
-                                    right.borrow_mut().parent = Some(parent.clone());
-                                    right.borrow_mut().color = node.borrow().color.clone();
-                                } else {
-                                    parent.borrow_mut().right = Some(right.clone());
-                                    right.borrow_mut().parent = Some(parent.clone());
-                                    right.borrow_mut().color = node.borrow().color.clone();
-                                }
-                            }
-                        }
-                    }
-                    // 4. Black + no children case: current node is black and has no children
-                    else {
-                        let parent = node.borrow().parent.clone();
-                        match parent {
-                            // 4.1 current node is the root, then return None
-                            None => return None,
-                            // 4.2 current node has parent, then call delete_maintain_rb
-                            // and then delete the link between current node and its parent
-                            Some(parent) => {
-                                Self::delete_maintain_rb(node.clone());
-                                if Self::is_left(node.clone()) {
-                                    parent.borrow_mut().left = None;
-                                } else {
-                                    parent.borrow_mut().right = None;
-                                }
-                                node.borrow_mut().parent = None;
-                            }
-                        }
+            }
+        }
+        // 4. Black + no children case: current node is black and has no children
+        else {
+            match parent {
+                // 4.1 current node is the root, then the tree becomes empty
+                None => *root = None,
+                // 4.2 current node has parent, then call delete_maintain_rb
+                // and then delete the link between current node and its parent
+                Some(_) => {
+                    Self::delete_maintain_rb(root, node.clone());
+                    let parent = Self::get_parent(&node).unwrap();
+                    if Self::is_left(node.clone()) {
+                        parent.borrow_mut().left = None;
+                    } else {
+                        parent.borrow_mut().right = None;
                     }
+                    Self::update_size_upward(parent);
                 }
             }
         }
-        // return the root
-        return Self::get_root(node);
+        let mut node = node.borrow_mut();
+        node.parent = None;
+        node.left = None;
+        node.right = None;
+    }
+
+    /// Get the node holding the min value in the subtree
+    fn get_min_node(node: RBTreeNode<T>) -> RBTreeNode<T> {
+        let left = node.borrow().left.clone();
+        match left {
+            Some(left) => Self::get_min_node(left),
+            None => node,
+        }
     }
 
     /// Repair the coloring of the remaining nodes in the tree
-    fn delete_maintain_rb(node: RBTreeNode<T>) {
-        let parent = node.borrow().parent.clone();
+    fn delete_maintain_rb(root: &mut OptionRBTreeNode<T>, node: RBTreeNode<T>) {
+        let parent = Self::get_parent(&node);
         match parent {
             // delete case 2: parent is None
             // it means current node is the new root, just return
@@ -584,14 +824,14 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                         if sibling.borrow().color == NodeColor::Red {
                             // RotateDirRoot(T, P, dir);
                             if direction == NodeDirection::Left {
-                                Self::left_rotate(parent.clone());
+                                Self::left_rotate(root, parent.clone());
                             } else {
-                                Self::right_rotate(parent.clone());
+                                Self::right_rotate(root, parent.clone());
                             }
                             Self::set_red(parent.clone());
                             Self::set_black(sibling.clone());
                             // recursive
-                            Self::delete_maintain_rb(node.clone());
+                            Self::delete_maintain_rb(root, node.clone());
                         }
                         // sibling is black
                         else {
@@ -611,7 +851,7 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                                 // delete case 1: parent, sibling, close, distant are all black
                                 if parent.borrow().color == NodeColor::Black {
                                     Self::set_red(sibling.clone());
-                                    Self::delete_maintain_rb(parent.clone());
+                                    Self::delete_maintain_rb(root, parent.clone());
                                 }
                                 // delete case 4: parent is red; sibling, close, distant are black
                                 else {
@@ -625,21 +865,21 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                             {
                                 // RotateDir(S,1-dir);  S is never the root
                                 if direction == NodeDirection::Left {
-                                    Self::right_rotate(sibling.clone());
+                                    Self::right_rotate(root, sibling.clone());
                                 } else {
-                                    Self::left_rotate(sibling.clone());
+                                    Self::left_rotate(root, sibling.clone());
                                 }
                                 Self::set_red(sibling.clone());
                                 Self::set_black(option_close.clone().unwrap());
-                                Self::delete_maintain_rb(node.clone());
+                                Self::delete_maintain_rb(root, node.clone());
                             }
                             // delete case 6: sibling is black, distant is red
                             else if Self::get_color(option_distant.clone()) == NodeColor::Red {
                                 // RotateDirRoot(T,P,dir);  P may be the root
                                 if direction == NodeDirection::Left {
-                                    Self::left_rotate(parent.clone());
+                                    Self::left_rotate(root, parent.clone());
                                 } else {
-                                    Self::right_rotate(parent.clone());
+                                    Self::right_rotate(root, parent.clone());
                                 }
                                 sibling.borrow_mut().color = parent.borrow().color.clone();
                                 Self::set_black(parent.clone());
@@ -655,18 +895,18 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
     // left and right rotate
     // node is the root of the subtree
 
-    /// Rotate the subtree rooted at this node to the left and
-    /// return the new root to this subtree.
-    fn left_rotate(node: RBTreeNode<T>) {
-        let parent = node.borrow().parent.clone();
+    /// Rotate the subtree rooted at this node to the left,
+    /// and update `root` if the node was the root of the tree.
+    fn left_rotate(root: &mut OptionRBTreeNode<T>, node: RBTreeNode<T>) {
+        let parent = Self::get_parent(&node);
         let right = node.borrow().right.clone();
 
         node.borrow_mut().right = right.clone().unwrap().borrow().left.clone();
         if node.borrow().right.is_some() {
             let right = node.borrow().right.clone().unwrap();
-            right.borrow_mut().parent = Some(node.clone());
+            right.borrow_mut().parent = Some(Rc::downgrade(&node));
         }
-        node.borrow_mut().parent = right.clone();
+        node.borrow_mut().parent = right.as_ref().map(Rc::downgrade);
         right.clone().unwrap().borrow_mut().left = Some(node.clone());
         if parent.is_some() {
             let left = parent.clone().unwrap().borrow().left.clone();
@@ -676,23 +916,28 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                 }
                 _ => parent.clone().unwrap().borrow_mut().right = right.clone(),
             }
+        } else {
+            // the node was the root, the tree now owns the new root
+            *root = right.clone();
         }
 
-        right.clone().unwrap().borrow_mut().parent = parent;
+        right.clone().unwrap().borrow_mut().parent = parent.as_ref().map(Rc::downgrade);
+        Self::update_size(node);
+        Self::update_size(right.unwrap());
     }
 
-    /// Rotate the subtree rooted at this node to the right and
-    /// returns the new root to this subtree.
-    fn right_rotate(node: RBTreeNode<T>) {
-        let parent = node.borrow().parent.clone();
+    /// Rotate the subtree rooted at this node to the right,
+    /// and update `root` if the node was the root of the tree.
+    fn right_rotate(root: &mut OptionRBTreeNode<T>, node: RBTreeNode<T>) {
+        let parent = Self::get_parent(&node);
         let left = node.borrow().left.clone();
 
         node.borrow_mut().left = left.clone().unwrap().borrow().right.clone();
         if node.borrow().left.is_some() {
             let left = node.borrow().left.clone().unwrap();
-            left.borrow_mut().parent = Some(node.clone());
+            left.borrow_mut().parent = Some(Rc::downgrade(&node));
         }
-        node.borrow_mut().parent = left.clone();
+        node.borrow_mut().parent = left.as_ref().map(Rc::downgrade);
         left.clone().unwrap().borrow_mut().right = Some(node.clone());
         if parent.is_some() {
             let right = parent.clone().unwrap().borrow().right.clone();
@@ -702,15 +947,20 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
                 }
                 _ => parent.clone().unwrap().borrow_mut().left = left.clone(),
             }
+        } else {
+            // the node was the root, the tree now owns the new root
+            *root = left.clone();
         }
 
-        left.clone().unwrap().borrow_mut().parent = parent;
+        left.clone().unwrap().borrow_mut().parent = parent.as_ref().map(Rc::downgrade);
+        Self::update_size(node);
+        Self::update_size(left.unwrap());
     }
 
     // Helper function for maintaining
     fn is_left(node: RBTreeNode<T>) -> bool {
         // Return true if the node is the left child of its parent.
-        match node.borrow().parent.clone() {
+        match Self::get_parent(&node) {
             Some(parent) => match parent.borrow().left.clone() {
                 Some(left) => Rc::ptr_eq(&left, &node),
                 None => false,
@@ -722,7 +972,7 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
     // Helper function for maintaining
     fn is_right(node: RBTreeNode<T>) -> bool {
         // Return true if the node is the right child of its parent.
-        match node.borrow().parent.clone() {
+        match Self::get_parent(&node) {
             Some(parent) => match parent.borrow().right.clone() {
                 Some(right) => Rc::ptr_eq(&right, &node),
                 None => false,
@@ -753,23 +1003,21 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         }
     }
 
-    fn get_root(node: RBTreeNode<T>) -> OptionRBTreeNode<T> {
-        let parent = node.borrow().parent.clone();
-        match parent {
-            Some(p) => Self::get_root(p),
-            None => Some(node),
-        }
+    // Helper function for maintaining
+    // upgrade the weak link to the parent, which is alive as long as the tree is
+    fn get_parent(node: &RBTreeNode<T>) -> OptionRBTreeNode<T> {
+        node.borrow().parent.as_ref().and_then(Weak::upgrade)
     }
 
     // Helper function for maintaining
     // get uncle
     fn get_uncle(node: RBTreeNode<T>) -> OptionRBTreeNode<T> {
-        let parent = node.borrow().parent.clone();
+        let parent = Self::get_parent(&node);
         match parent {
             // self is root
             None => None,
             Some(parent) => {
-                let grand_parent = parent.borrow().parent.clone();
+                let grand_parent = Self::get_parent(&parent);
                 match grand_parent {
                     // parent is root
                     None => None,
@@ -788,7 +1036,7 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
     // Helper function for maintaining
     fn get_sibling(node: RBTreeNode<T>) -> OptionRBTreeNode<T> {
         // Get the current node's sibling, or None if it does not exist.
-        match node.borrow().parent.clone() {
+        match Self::get_parent(&node) {
             None => None,
             Some(parent) => {
                 if Self::is_left(node.clone()) {
@@ -800,6 +1048,31 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         }
     }
 
+    fn get_size(node: OptionRBTreeNode<T>) -> usize {
+        match node {
+            None => 0,
+            Some(node) => node.borrow().size,
+        }
+    }
+
+    // Helper function for rotating
+    // recount the subtree size from the children
+    fn update_size(node: RBTreeNode<T>) {
+        let left = node.borrow().left.clone();
+        let right = node.borrow().right.clone();
+        node.borrow_mut().size = Self::get_size(left) + Self::get_size(right) + 1;
+    }
+
+    // Helper function for inserting and deleting
+    // recount the subtree sizes from the node up to the root
+    fn update_size_upward(node: RBTreeNode<T>) {
+        Self::update_size(node.clone());
+        let parent = Self::get_parent(&node);
+        if let Some(parent) = parent {
+            Self::update_size_upward(parent);
+        }
+    }
+
     // Helper function for maintaining
     // make None to be real leaves with black color
     fn get_color(node: OptionRBTreeNode<T>) -> NodeColor {
@@ -837,9 +1110,538 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
     }
 }
 
+/// Joining and splitting of RBTree, which take O(log n) time
+impl<T: Ord> RBTree<T> {
+    /// Move the values greater than or equal to the given value into a new tree
+    /// and return it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::new();
+    /// for value in 0..10 {
+    ///     tree.insert(value);
+    /// }
+    /// let greater = tree.split_off(&6);
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 5], tree.iter().collect::<Vec<_>>());
+    /// assert_eq!(vec![6, 7, 8, 9], greater.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn split_off(&mut self, value: &T) -> Self {
+        let root = self.root.take();
+        let height = TreeNode::black_height(root.clone());
+        let (less, _, found, greater, greater_height) =
+            TreeNode::split(root, height, value, &self.comparator);
+        self.root = less;
+        let root = match found {
+            Some(node) => TreeNode::join(None, 1, node, greater, greater_height).0,
+            None => greater,
+        };
+        RBTree {
+            root,
+            comparator: self.comparator.clone(),
+        }
+    }
+
+    /// Move all values of the other tree into this tree, leaving the other tree empty.
+    /// Trees whose values do not overlap are joined in O(log n) time,
+    /// otherwise the values of both are merged, and a value of the other tree replaces
+    /// an equal one in this tree like [insert](#method.insert) does.
+    ///
+    /// Panics if the trees are ordered by different comparators.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = RBTree::new();
+    /// let mut other = RBTree::new();
+    /// tree.insert(1);
+    /// other.insert(2);
+    /// tree.append(&mut other);
+    /// assert_eq!(vec![1, 2], tree.iter().collect::<Vec<_>>());
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        self.comparator.assert_same(&other.comparator);
+        let (a, b) = match (self.root.take(), other.root.take()) {
+            (None, b) => {
+                self.root = b;
+                return;
+            }
+            (a, None) => {
+                self.root = a;
+                return;
+            }
+            (Some(a), Some(b)) => (a, b),
+        };
+        let a_before_b = TreeNode::is_before(&a, &b, &self.comparator);
+        let b_before_a = TreeNode::is_before(&b, &a, &self.comparator);
+        self.root = if a_before_b {
+            TreeNode::join_trees(Some(a), Some(b))
+        } else if b_before_a {
+            TreeNode::join_trees(Some(b), Some(a))
+        } else {
+            // the values of the other tree are kept, as if they were inserted
+            let a_height = TreeNode::black_height(Some(a.clone()));
+            let b_height = TreeNode::black_height(Some(b.clone()));
+            TreeNode::union_nodes((Some(b), b_height), (Some(a), a_height), &self.comparator).0
+        };
+    }
+
+    // run a set operation on the trees of both roots, which are taken out of the trees
+    fn combine<F>(mut self, mut other: Self, operation: F) -> Self
+    where
+        F: FnOnce(SubTree<T>, SubTree<T>, &Comparator<T>) -> SubTree<T>,
+    {
+        self.comparator.assert_same(&other.comparator);
+        let (a, b) = (self.root.take(), other.root.take());
+        let a_height = TreeNode::black_height(a.clone());
+        let b_height = TreeNode::black_height(b.clone());
+        RBTree {
+            root: operation((a, a_height), (b, b_height), &self.comparator).0,
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+/// Joining and splitting of TreeNode, which will be called by
+/// [RBTree.split_off](struct.RBTree.html#method.split_off),
+/// [RBTree.append](struct.RBTree.html#method.append) and the set operators of RBTree
+impl<T: Ord> TreeNode<T> {
+    /// Join the trees of the values less and greater than the value of the detached node
+    /// through the node, given the black heights of both trees, and return the root and
+    /// the black height of the joined tree.
+    /// The node is hung from the taller tree where the black heights match,
+    /// so only the nodes on one path are visited, which takes O(|left_height - right_height| + 1) time.
+    fn join(
+        left: OptionRBTreeNode<T>,
+        left_height: usize,
+        node: RBTreeNode<T>,
+        right: OptionRBTreeNode<T>,
+        right_height: usize,
+    ) -> (OptionRBTreeNode<T>, usize) {
+        node.borrow_mut().parent = None;
+        // the node becomes a black root over two trees of the same black height
+        if left_height == right_height {
+            node.borrow_mut().color = NodeColor::Black;
+            Self::link_children(&node, left, right);
+            return (Some(node), left_height + 1);
+        }
+        // descend the taller tree along the side facing the shorter tree
+        // to a black subtree as tall as the shorter tree
+        let taller_is_left = left_height > right_height;
+        let (mut root, shorter, taller_height, shorter_height) = if taller_is_left {
+            (left, right, left_height, right_height)
+        } else {
+            (right, left, right_height, left_height)
+        };
+        let mut height = taller_height;
+        let mut parent = root.clone().unwrap();
+        let mut current = root.clone();
+        loop {
+            let is_black = Self::get_color(current.clone()) == NodeColor::Black;
+            if is_black && height == shorter_height {
+                break;
+            }
+            let next = current.unwrap();
+            if is_black {
+                height -= 1;
+            }
+            current = if taller_is_left {
+                next.borrow().right.clone()
+            } else {
+                next.borrow().left.clone()
+            };
+            parent = next;
+        }
+        // the node takes the place of the subtree, with the subtree and the shorter tree
+        // as its children, then it is repaired like a newly inserted red node
+        node.borrow_mut().color = NodeColor::Red;
+        if taller_is_left {
+            Self::link_children(&node, current, shorter);
+            parent.borrow_mut().right = Some(node.clone());
+        } else {
+            Self::link_children(&node, shorter, current);
+            parent.borrow_mut().left = Some(node.clone());
+        }
+        node.borrow_mut().parent = Some(Rc::downgrade(&parent));
+        Self::update_size_upward(parent);
+        let grows = Self::insert_maintain_rb(&mut root, node);
+        (root, taller_height + grows as usize)
+    }
+
+    /// Join the trees of the values less and greater than each other
+    fn join_trees(left: OptionRBTreeNode<T>, right: OptionRBTreeNode<T>) -> OptionRBTreeNode<T> {
+        let left_height = Self::black_height(left.clone());
+        Self::join_without_node(left, left_height, right).0
+    }
+
+    // Helper function for joining
+    // join the trees of the values less and greater than each other, given the black
+    // height of the left tree, and return the root and the black height of the joined tree
+    fn join_without_node(
+        left: OptionRBTreeNode<T>,
+        left_height: usize,
+        right: OptionRBTreeNode<T>,
+    ) -> (OptionRBTreeNode<T>, usize) {
+        let mut right = right;
+        match right.clone() {
+            None => (left, left_height),
+            Some(node) => {
+                // take the min node out of the right tree to join the trees through it
+                let min_node = Self::get_min_node(node);
+                Self::detach_node(&mut right, min_node.clone());
+                let right_height = Self::black_height(right.clone());
+                Self::join(left, left_height, min_node, right, right_height)
+            }
+        }
+    }
+
+    // Helper function for the set operations
+    // take the children out of a root, making them trees of their own with black heights
+    fn detach_children(node: &RBTreeNode<T>, height: usize) -> (SubTree<T>, SubTree<T>) {
+        let child_height = height - (node.borrow().color == NodeColor::Black) as usize;
+        let left = Self::detach_subtree(node.borrow_mut().left.take(), child_height);
+        let right = Self::detach_subtree(node.borrow_mut().right.take(), child_height);
+        (left, right)
+    }
+
+    /// Merge two trees with their black heights into the tree of the values in either of
+    /// them, a value in both trees is kept from the first one.
+    /// The first tree is split by the root of the second one, and the halves are merged
+    /// with its children, which takes O(m log(n / m + 1)) time for trees of m <= n values.
+    fn union_nodes(a: SubTree<T>, b: SubTree<T>, comparator: &Comparator<T>) -> SubTree<T> {
+        let ((a, a_height), (b, b_height)) = match (a, b) {
+            ((None, _), b) => return b,
+            (a, (None, _)) => return a,
+            ((Some(a), a_height), (Some(b), b_height)) => ((a, a_height), (b, b_height)),
+        };
+        let (b_left, b_right) = Self::detach_children(&b, b_height);
+        let (less, less_height, found, greater, greater_height) =
+            Self::split(Some(a), a_height, &b.borrow().value, comparator);
+        let (left, left_height) = Self::union_nodes((less, less_height), b_left, comparator);
+        let (right, right_height) =
+            Self::union_nodes((greater, greater_height), b_right, comparator);
+        Self::join(left, left_height, found.unwrap_or(b), right, right_height)
+    }
+
+    /// Merge two trees with their black heights into the tree of the values in both of
+    /// them, the values are kept from the first one
+    fn intersection_nodes(a: SubTree<T>, b: SubTree<T>, comparator: &Comparator<T>) -> SubTree<T> {
+        let ((a, a_height), (b, b_height)) = match (a, b) {
+            ((Some(a), a_height), (Some(b), b_height)) => ((a, a_height), (b, b_height)),
+            _ => return (None, 1),
+        };
+        let (b_left, b_right) = Self::detach_children(&b, b_height);
+        let (less, less_height, found, greater, greater_height) =
+            Self::split(Some(a), a_height, &b.borrow().value, comparator);
+        let (left, left_height) = Self::intersection_nodes((less, less_height), b_left, comparator);
+        let (right, right_height) =
+            Self::intersection_nodes((greater, greater_height), b_right, comparator);
+        match found {
+            Some(node) => Self::join(left, left_height, node, right, right_height),
+            None => Self::join_without_node(left, left_height, right),
+        }
+    }
+
+    /// Merge two trees with their black heights into the tree of the values in the first
+    /// one but not in the second one
+    fn difference_nodes(a: SubTree<T>, b: SubTree<T>, comparator: &Comparator<T>) -> SubTree<T> {
+        let ((a, a_height), (b, b_height)) = match (a, b) {
+            ((None, _), _) => return (None, 1),
+            (a, (None, _)) => return a,
+            ((Some(a), a_height), (Some(b), b_height)) => ((a, a_height), (b, b_height)),
+        };
+        let (b_left, b_right) = Self::detach_children(&b, b_height);
+        let (less, less_height, _, greater, greater_height) =
+            Self::split(Some(a), a_height, &b.borrow().value, comparator);
+        let (left, left_height) = Self::difference_nodes((less, less_height), b_left, comparator);
+        let (right, _) = Self::difference_nodes((greater, greater_height), b_right, comparator);
+        Self::join_without_node(left, left_height, right)
+    }
+
+    /// Merge two trees with their black heights into the tree of the values in exactly
+    /// one of them
+    fn symmetric_difference_nodes(
+        a: SubTree<T>,
+        b: SubTree<T>,
+        comparator: &Comparator<T>,
+    ) -> SubTree<T> {
+        let ((a, a_height), (b, b_height)) = match (a, b) {
+            ((None, _), b) => return b,
+            (a, (None, _)) => return a,
+            ((Some(a), a_height), (Some(b), b_height)) => ((a, a_height), (b, b_height)),
+        };
+        let (b_left, b_right) = Self::detach_children(&b, b_height);
+        let (less, less_height, found, greater, greater_height) =
+            Self::split(Some(a), a_height, &b.borrow().value, comparator);
+        let (left, left_height) =
+            Self::symmetric_difference_nodes((less, less_height), b_left, comparator);
+        let (right, right_height) =
+            Self::symmetric_difference_nodes((greater, greater_height), b_right, comparator);
+        match found {
+            Some(_) => Self::join_without_node(left, left_height, right),
+            None => Self::join(left, left_height, b, right, right_height),
+        }
+    }
+
+    /// Split the tree of the given black height into the trees of the values less and
+    /// greater than the given value with their black heights, and the detached node of
+    /// the value if it is in the tree.
+    /// The black heights are passed down instead of counted again, so that the joins on
+    /// the way back up take O(log n) time in total.
+    fn split(
+        node: OptionRBTreeNode<T>,
+        height: usize,
+        value: &T,
+        comparator: &Comparator<T>,
+    ) -> SplitTrees<T> {
+        let node = match node {
+            Some(node) => node,
+            None => return (None, 1, None, None, 1),
+        };
+        // the children are one black node lower than a black node
+        let child_height = height - (node.borrow().color == NodeColor::Black) as usize;
+        let (left, left_height) = Self::detach_subtree(node.borrow_mut().left.take(), child_height);
+        let (right, right_height) =
+            Self::detach_subtree(node.borrow_mut().right.take(), child_height);
+        let ordering = comparator.compare(value, &node.borrow().value);
+        match ordering {
+            Ordering::Less => {
+                let (less, less_height, found, greater, greater_height) =
+                    Self::split(left, left_height, value, comparator);
+                let (greater, greater_height) =
+                    Self::join(greater, greater_height, node, right, right_height);
+                (less, less_height, found, greater, greater_height)
+            }
+            Ordering::Greater => {
+                let (less, less_height, found, greater, greater_height) =
+                    Self::split(right, right_height, value, comparator);
+                let (less, less_height) = Self::join(left, left_height, node, less, less_height);
+                (less, less_height, found, greater, greater_height)
+            }
+            Ordering::Equal => (left, left_height, Some(node), right, right_height),
+        }
+    }
+
+    // Helper function for splitting
+    // make a child subtree of the given black height a tree of its own, a black root
+    // keeps it valid, return the subtree and its black height
+    fn detach_subtree(node: OptionRBTreeNode<T>, height: usize) -> (OptionRBTreeNode<T>, usize) {
+        let mut height = height;
+        if let Some(node) = &node {
+            let mut node = node.borrow_mut();
+            node.parent = None;
+            if node.color == NodeColor::Red {
+                node.color = NodeColor::Black;
+                height += 1;
+            }
+        }
+        (node, height)
+    }
+
+    // Helper function for joining
+    fn link_children(node: &RBTreeNode<T>, left: OptionRBTreeNode<T>, right: OptionRBTreeNode<T>) {
+        for child in left.iter().chain(right.iter()) {
+            child.borrow_mut().parent = Some(Rc::downgrade(node));
+        }
+        node.borrow_mut().left = left;
+        node.borrow_mut().right = right;
+        Self::update_size(node.clone());
+    }
+
+    // Helper function for splitting and appending
+    // count the black nodes on a path from the node to a None leaf, the leaf counted as one
+    // like calculate_black_height does, every path gives the same count in a valid tree
+    fn black_height(node: OptionRBTreeNode<T>) -> usize {
+        let mut height = 1;
+        let mut current = node;
+        while let Some(node) = current {
+            if node.borrow().color == NodeColor::Black {
+                height += 1;
+            }
+            current = node.borrow().left.clone();
+        }
+        height
+    }
+
+    // Helper function for appending
+    // whether all values of the left tree are less than the values of the right tree
+    fn is_before(left: &RBTreeNode<T>, right: &RBTreeNode<T>, comparator: &Comparator<T>) -> bool {
+        let max_node = Self::get_max_node(left.clone());
+        let min_node = Self::get_min_node(right.clone());
+        let ordering = comparator.compare(&max_node.borrow().value, &min_node.borrow().value);
+        ordering == Ordering::Less
+    }
+
+    /// Get the node holding the max value in the subtree
+    fn get_max_node(node: RBTreeNode<T>) -> RBTreeNode<T> {
+        let right = node.borrow().right.clone();
+        match right {
+            Some(right) => Self::get_max_node(right),
+            None => node,
+        }
+    }
+}
+
+/// Building RBTree from sorted values in O(n) time, which is also how the set operations
+/// of RBTree build their result from the merged values of both trees
+impl<T: Ord> RBTree<T> {
+    /// Build a balanced tree from values in ascending order without any rotation,
+    /// keeping the last one of repeated values
+    ///
+    /// Panics if the values are not in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let tree = RBTree::from_sorted_iter(0..100);
+    /// assert_eq!(100, tree.len());
+    /// assert_eq!(7, tree.height());
+    /// ```
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted_values(collect_sorted(iter), Comparator::default())
+    }
+
+    // build a balanced tree from values in strictly ascending order of the comparator
+    fn from_sorted_values(values: Vec<T>, comparator: Comparator<T>) -> Self {
+        let len = values.len();
+        // the levels above this depth are full, nodes below it are red
+        let red_depth = (usize::BITS - (len + 1).leading_zeros() - 1) as usize;
+        let root = Self::build_sorted(&mut values.into_iter(), len, 0, red_depth);
+        RBTree { root, comparator }
+    }
+
+    // build a subtree of the next len values, splitting them in the middle so that every
+    // empty child is at depth red_depth or below a red leaf at red_depth, which gives
+    // every path the same number of black nodes
+    fn build_sorted(
+        values: &mut std::vec::IntoIter<T>,
+        len: usize,
+        depth: usize,
+        red_depth: usize,
+    ) -> OptionRBTreeNode<T> {
+        if len == 0 {
+            return None;
+        }
+        let left_len = (len - 1) / 2;
+        let left = Self::build_sorted(values, left_len, depth + 1, red_depth);
+        let value = values.next().unwrap();
+        let right = Self::build_sorted(values, len - 1 - left_len, depth + 1, red_depth);
+        let color = if depth >= red_depth {
+            NodeColor::Red
+        } else {
+            NodeColor::Black
+        };
+        let node = Rc::new(RefCell::new(TreeNode {
+            color,
+            value,
+            parent: None,
+            left: left.clone(),
+            right: right.clone(),
+            size: len,
+        }));
+        for child in left.iter().chain(right.iter()) {
+            child.borrow_mut().parent = Some(Rc::downgrade(&node));
+        }
+        Some(node)
+    }
+}
+
+/// Consume two RBTrees and return the tree of the values in either of them
+///
+/// The operators on two trees panic if the trees are ordered by different comparators.
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::prelude::*;
+/// let mut a = RBTree::new();
+/// let mut b = RBTree::new();
+/// a.insert(1);
+/// b.insert(2);
+/// let c = &a | &b;
+/// assert_eq!(vec![1, 2], c.iter().collect::<Vec<_>>());
+/// assert_eq!(vec![1, 2], (a | b).iter().collect::<Vec<_>>());
+/// ```
+impl<T: Ord> BitOr for RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn bitor(self, other: RBTree<T>) -> RBTree<T> {
+        self.combine(other, TreeNode::union_nodes)
+    }
+}
+
+/// Consume two RBTrees and return the tree of the values in both of them
+impl<T: Ord> BitAnd for RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn bitand(self, other: RBTree<T>) -> RBTree<T> {
+        self.combine(other, TreeNode::intersection_nodes)
+    }
+}
+
+/// Consume two RBTrees and return the tree of the values in the first one but not in
+/// the second one
+impl<T: Ord> Sub for RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn sub(self, other: RBTree<T>) -> RBTree<T> {
+        self.combine(other, TreeNode::difference_nodes)
+    }
+}
+
+/// Consume two RBTrees and return the tree of the values in exactly one of them
+impl<T: Ord> BitXor for RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn bitxor(self, other: RBTree<T>) -> RBTree<T> {
+        self.combine(other, TreeNode::symmetric_difference_nodes)
+    }
+}
+
+// the borrowed versions merge the values of both trees in O(m + n) time
+// and only copy the values of the result
+impl<T: Ord + Clone> BitOr<&RBTree<T>> for &RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn bitor(self, other: &RBTree<T>) -> RBTree<T> {
+        RBTree::from_sorted_values(self.union(other).collect(), self.get_comparator())
+    }
+}
+
+impl<T: Ord + Clone> BitAnd<&RBTree<T>> for &RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn bitand(self, other: &RBTree<T>) -> RBTree<T> {
+        RBTree::from_sorted_values(self.intersection(other).collect(), self.get_comparator())
+    }
+}
+
+impl<T: Ord + Clone> Sub<&RBTree<T>> for &RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn sub(self, other: &RBTree<T>) -> RBTree<T> {
+        RBTree::from_sorted_values(self.difference(other).collect(), self.get_comparator())
+    }
+}
+
+impl<T: Ord + Clone> BitXor<&RBTree<T>> for &RBTree<T> {
+    type Output = RBTree<T>;
+
+    fn bitxor(self, other: &RBTree<T>) -> RBTree<T> {
+        let values = self.symmetric_difference(other).collect();
+        RBTree::from_sorted_values(values, self.get_comparator())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fixtures;
+    use crate::nodeHandle::Order;
 
     #[test]
     fn test_rotation() {
@@ -911,11 +1713,10 @@ mod test {
         }
         {
             let root = tree.root.clone().unwrap();
-            TreeNode::left_rotate(root);
+            TreeNode::left_rotate(&mut tree.root, root);
         }
         let mut tree_container = vec![];
         let mut left_rotate_container = vec![];
-        tree.root = TreeNode::get_root(tree.root.clone().unwrap());
         tree.pre_order_traversal_for_test(&mut tree_container);
         after_left_rot.pre_order_traversal_for_test(&mut left_rotate_container);
 
@@ -933,13 +1734,16 @@ mod test {
         let mut container = vec![];
         tree.pre_order_traversal_for_test(&mut container);
         assert_eq!(container, vec![8, 0, 20, 16, 24, 22]);
-        // let mut container = vec![];
-        // RedBlackTreeNode::debug_preorder_traverse(root.clone(), &mut container);
-        // assert_eq!(container, vec![0, -16, 16, 8, 22, 20, 24]);
-
-        // let mut container = vec![];
-        // RedBlackTreeNode::postorder_traverse(root, &mut container);
-        // assert_eq!(container, vec![-16, 8, 20, 24, 22, 16, 0]);
+        let mut container = vec![];
+        tree.traverse(Order::PostOrder, |value, _| container.push(*value));
+        assert_eq!(container, vec![0, 16, 22, 24, 20, 8]);
+        let mut depths = vec![];
+        tree.traverse(Order::LevelOrder, |_, depth| depths.push(depth));
+        assert_eq!(depths, vec![0, 1, 1, 2, 2, 3]);
+        assert_eq!(
+            tree.level_order().collect::<Vec<_>>(),
+            vec![8, 0, 20, 16, 24, 22]
+        );
     }
 
     #[test]
@@ -966,7 +1770,7 @@ mod test {
         rb_tree.insert(8);
         rb_tree.insert(17);
 
-        let result = RBTree::is_valid_red_black_tree(rb_tree.root);
+        let result = RBTree::is_valid_red_black_tree(rb_tree.root.clone());
         assert_eq!(result, true);
     }
 
@@ -979,11 +1783,307 @@ mod test {
             tree.insert(*v);
         });
 
-        tree.delete(16);
+        tree.delete(&16);
         let mut container = vec![];
         tree.pre_order_traversal_for_test(&mut container);
-        let result = RBTree::is_valid_red_black_tree(tree.root);
+        let result = RBTree::is_valid_red_black_tree(tree.root.clone());
         assert_eq!(result, true);
         //  assert_eq!(container, vec![8, 0, 20, 24, 22]);
     }
+
+    // check the sizes of every node, return the size of the subtree
+    fn check_size<T: Ord>(node: &OptionRBTreeNode<T>) -> usize {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return 0,
+        };
+        let size = check_size(&node.left) + check_size(&node.right) + 1;
+        assert_eq!(node.size, size);
+        size
+    }
+
+    fn check_tree<T: Ord>(tree: &RBTree<T>) {
+        assert!(RBTree::is_valid_red_black_tree(tree.root.clone()));
+        check_size(&tree.root);
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(check_tree::<i32>);
+    }
+
+    // counts how many values have been dropped
+    struct DropCounter {
+        value: i32,
+        drops: Rc<std::cell::Cell<usize>>,
+    }
+
+    impl PartialEq for DropCounter {
+        fn eq(&self, other: &Self) -> bool {
+            self.value == other.value
+        }
+    }
+
+    impl Eq for DropCounter {}
+
+    impl PartialOrd for DropCounter {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for DropCounter {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.value.cmp(&other.value)
+        }
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_drop() {
+        let drops = Rc::new(std::cell::Cell::new(0));
+        let counter = |value| DropCounter {
+            value,
+            drops: drops.clone(),
+        };
+        let mut tree = RBTree::new();
+        for i in 0..100 {
+            tree.insert(counter(i * 37 % 100));
+        }
+        for i in 0..30 {
+            tree.delete(&counter(i * 53 % 100));
+        }
+        // every deleted value and every temporary key has been dropped
+        assert_eq!(drops.get(), 60);
+        drop(tree);
+        // the remaining 70 values are freed with the tree
+        assert_eq!(drops.get(), 130);
+    }
+
+    #[test]
+    fn test_debug_and_eq() {
+        let mut tree = RBTree::new();
+        let mut other = RBTree::new();
+        for i in 0..10 {
+            tree.insert(i);
+            other.insert(i);
+        }
+        // neither follows the parent links back up the tree
+        assert!(format!("{:?}", tree).contains("value: 9"));
+        assert_eq!(tree, other);
+        other.delete(&9);
+        assert_ne!(tree, other);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        use std::collections::BTreeSet;
+        // sets of very different sizes and overlaps
+        let sets: Vec<BTreeSet<u32>> = vec![
+            BTreeSet::new(),
+            (0..100).collect(),
+            (0..100).filter(|v| v % 3 == 0).collect(),
+            (50..300).filter(|v| v % 2 == 0).collect(),
+            (1000..1010).collect(),
+            [7, 42].iter().cloned().collect(),
+        ];
+        let to_tree = |set: &BTreeSet<u32>| {
+            let mut tree = RBTree::new();
+            for value in set.iter() {
+                tree.insert(*value);
+            }
+            tree
+        };
+        for a in sets.iter() {
+            for b in sets.iter() {
+                let (tree_a, tree_b) = (to_tree(a), to_tree(b));
+                let expected = [a | b, a & b, a - b, a ^ b];
+                let lazy: [Vec<u32>; 4] = [
+                    tree_a.union(&tree_b).collect(),
+                    tree_a.intersection(&tree_b).collect(),
+                    tree_a.difference(&tree_b).collect(),
+                    tree_a.symmetric_difference(&tree_b).collect(),
+                ];
+                let trees = [
+                    &tree_a | &tree_b,
+                    &tree_a & &tree_b,
+                    &tree_a - &tree_b,
+                    &tree_a ^ &tree_b,
+                ];
+                for ((set, values), tree) in expected.iter().zip(lazy.iter()).zip(trees.iter()) {
+                    let set: Vec<u32> = set.iter().cloned().collect();
+                    assert_eq!(&set, values);
+                    assert_eq!(set, tree.iter().collect::<Vec<_>>());
+                    check_tree(&tree);
+                    assert_eq!(tree.len(), set.len());
+                }
+                // the consuming versions split and join the trees
+                let joined = [
+                    tree_a.clone() | tree_b.clone(),
+                    tree_a.clone() & tree_b.clone(),
+                    tree_a.clone() - tree_b.clone(),
+                    tree_a.clone() ^ tree_b.clone(),
+                ];
+                for (set, tree) in expected.iter().zip(joined.iter()) {
+                    assert!(tree.iter().eq(set.iter().cloned()));
+                    check_tree(tree);
+                }
+                assert_eq!(tree_a.is_subset(&tree_b), a.is_subset(b));
+                assert_eq!(tree_a.is_disjoint(&tree_b), a.is_disjoint(b));
+            }
+        }
+        // the built trees keep working with parent links in place
+        let built = vec![
+            to_tree(&sets[1]) | to_tree(&sets[3]),
+            to_tree(&sets[3]) ^ to_tree(&sets[2]),
+        ];
+        for mut tree in built {
+            for value in 0..300 {
+                tree.delete(&value);
+                check_tree(&tree);
+            }
+            assert!(tree.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_split_off_append() {
+        for split_at in [0, 1, 37, 50, 99, 100, 150].iter() {
+            let mut tree = RBTree::new();
+            for value in 0..100 {
+                tree.insert(value * 7 % 100);
+            }
+            let mut greater = tree.split_off(split_at);
+            check_tree(&tree);
+            check_tree(&greater);
+            let less_end = *split_at.min(&100);
+            assert_eq!(
+                tree.iter().collect::<Vec<_>>(),
+                (0..less_end).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                greater.iter().collect::<Vec<_>>(),
+                (less_end..100).collect::<Vec<_>>()
+            );
+            assert_eq!(tree.len() + greater.len(), 100);
+            // join them back in either order
+            if split_at % 2 == 0 {
+                tree.append(&mut greater);
+            } else {
+                greater.append(&mut tree);
+                std::mem::swap(&mut tree, &mut greater);
+            }
+            assert!(greater.is_empty());
+            check_tree(&tree);
+            assert_eq!(
+                tree.iter().collect::<Vec<_>>(),
+                (0..100).collect::<Vec<_>>()
+            );
+            for k in 0..100 {
+                assert_eq!(tree.select(k), Some(k));
+            }
+            // the parent links still work
+            for value in 0..100 {
+                assert!(tree.delete(&value));
+            }
+        }
+        // trees of very different heights, and overlapping trees
+        let mut small = RBTree::new();
+        small.insert(1000);
+        let mut large = RBTree::new();
+        for value in 0..500 {
+            large.insert(value);
+        }
+        large.append(&mut small);
+        check_tree(&large);
+        assert_eq!(large.len(), 501);
+        let mut overlapping = RBTree::new();
+        for value in 250..750 {
+            overlapping.insert(value);
+        }
+        large.append(&mut overlapping);
+        assert_eq!(
+            large.iter().collect::<Vec<_>>(),
+            (0..750).chain(1000..1001).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for len in 0..70usize {
+            let tree = RBTree::from_sorted_iter(0..len);
+            check_tree(&tree);
+            // as low as a complete tree of the same size
+            assert_eq!(tree.height(), usize::BITS - len.leading_zeros());
+        }
+    }
+
+    #[test]
+    fn test_extend_replaces() {
+        // values equal in the comparator replace the ones in the tree, sorted or not
+        let mut tree =
+            RBTree::with_comparator(|a: &&str, b: &&str| a.to_lowercase().cmp(&b.to_lowercase()));
+        tree.extend(vec!["a", "c", "e"]);
+        tree.extend(vec!["A", "b", "C"]);
+        check_tree(&tree);
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec!["A", "b", "C", "e"]);
+        tree.extend(vec!["E", "a", "D"]);
+        check_tree(&tree);
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec!["a", "b", "C", "D", "E"]
+        );
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(check_tree::<i32>);
+        // the set operations, split and append follow the comparator too
+        let mut tree = RBTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        tree.extend((0..50).filter(|v| v % 2 == 1));
+        let mut other = RBTree::from_comparator(tree.get_comparator());
+        other.extend(vec![60, 50, 3, 1]);
+        let union = (&tree | &other).iter().collect::<Vec<_>>();
+        assert_eq!(union[..3], [60, 50, 49]);
+        assert!(tree.union(&other).eq(union.into_iter()));
+        assert_eq!(tree.intersection(&other).collect::<Vec<_>>(), vec![3, 1]);
+        let greater = tree.split_off(&20);
+        assert_eq!(
+            greater.iter().collect::<Vec<_>>(),
+            vec![19, 17, 15, 13, 11, 9, 7, 5, 3, 1]
+        );
+        tree.append(&mut other);
+        check_tree(&tree);
+        assert_eq!(
+            tree.iter().take(4).collect::<Vec<_>>(),
+            vec![60, 50, 49, 47]
+        );
+        assert_eq!(tree.iter().last(), Some(1));
+    }
+
+    #[test]
+    fn test_mismatched_comparators() {
+        let tree = || (0..10).collect::<RBTree<i32>>();
+        let reversed = || {
+            let mut tree = RBTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+            tree.extend(5..15);
+            tree
+        };
+        assert!(fixtures::panics(|| drop(tree() | reversed())));
+        assert!(fixtures::panics(|| drop(tree() & reversed())));
+        assert!(fixtures::panics(|| drop(tree() - reversed())));
+        assert!(fixtures::panics(|| drop(tree() ^ reversed())));
+        assert!(fixtures::panics(|| drop(&tree() | &reversed())));
+        assert!(fixtures::panics(|| tree().append(&mut reversed())));
+        // neither tree is changed by a refused append
+        let (mut a, mut b) = (tree(), reversed());
+        assert!(fixtures::panics(|| a.append(&mut b)));
+        assert!(a.iter().eq(0..10));
+        assert!(b.iter().eq((5..15).rev()));
+    }
 }
@@ -0,0 +1,434 @@
+//! Arena-backed red-black tree
+//!
+//! You can generate a left-leaning red-black tree whose nodes are stored in an
+//! [Arena](../arena/struct.Arena.html), and insert or delete nodes.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::arena::{Arena, ArenaHandle, NIL};
+pub use crate::commonTrait::{CommonTreeTrait, OrderedSet};
+use crate::commonTrait::{Comparator, IntoIter};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+pub use crate::rbTreeFast::NodeColor;
+
+/// Structure of ArenaRBTree, it balances the same way as
+/// [FastRBTree](../rbTreeFast/struct.FastRBTree.html)
+#[derive(Clone, Debug)]
+pub struct ArenaRBTree<T: Ord> {
+    nodes: Arena<T, NodeColor>,
+    root: u32,
+    comparator: Comparator<T>,
+}
+
+// extend from common tree trait
+impl<T: Ord> CommonTreeTrait<T> for ArenaRBTree<T> {
+    type Handle<'a>
+        = ArenaHandle<'a, T, NodeColor>
+    where
+        Self: 'a;
+
+    fn get_root(&self) -> Option<ArenaHandle<'_, T, NodeColor>> {
+        self.nodes.handle(self.root)
+    }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
+}
+
+// construct and mutate ArenaRBTree in generic code
+impl<T: Ord> OrderedSet<T> for ArenaRBTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        ArenaRBTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        ArenaRBTree {
+            nodes: Arena::new(),
+            root: NIL,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        ArenaRBTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        ArenaRBTree::delete(self, value)
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for ArenaRBTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, u32, Arena<T, NodeColor>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let root = Some(self.root).filter(|&root| root != NIL);
+        IntoIter::with_storage(root, self.len(), self.nodes)
+    }
+}
+
+// draw ArenaRBTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for ArenaRBTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        let nil_leaf = nil_leaves.then(|| DotStyle::filled(NodeColor::Black.fill()));
+        dot::to_dot(self.get_root(), nil_leaf, |node| {
+            DotStyle::filled(self.nodes[node.index()].meta.fill())
+        })
+    }
+}
+
+// draw ArenaRBTree as text
+impl<T: Ord + fmt::Debug> Pretty for ArenaRBTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.get_root(), |node| {
+            Some(self.nodes[node.index()].meta.to_string().to_owned())
+        })
+    }
+}
+
+impl<T: Ord> Default for ArenaRBTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implementations of ArenaRBTree
+impl<T: Ord> ArenaRBTree<T> {
+    /// Create a new red-black Tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeArena::ArenaRBTree;
+    /// let mut tree: ArenaRBTree<u32> = ArenaRBTree::new();
+    /// ```
+    pub fn new() -> Self {
+        ArenaRBTree {
+            nodes: Arena::new(),
+            root: NIL,
+            comparator: Comparator::default(),
+        }
+    }
+
+    /// Create a new red-black Tree ordered by the comparator instead of `Ord`, which tells
+    /// how the first value compares with the second one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = ArenaRBTree::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(Some(2), tree.min());
+    /// ```
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        ArenaRBTree {
+            nodes: Arena::new(),
+            root: NIL,
+            comparator: Comparator::new(cmp),
+        }
+    }
+
+    /// Insert a new value to the red-black Tree, return false if the value already exists
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeArena::ArenaRBTree;
+    /// let mut tree = ArenaRBTree::new();
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
+    /// ```
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        let (root, replaced) = self.node_insert(self.root, insert_value);
+        self.root = root;
+        self.nodes[root].meta = NodeColor::Black;
+        replaced.is_none()
+    }
+
+    /// Delete a value from the red-black Tree, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeArena::ArenaRBTree;
+    /// let mut tree = ArenaRBTree::new();
+    /// tree.insert(1);
+    /// assert!(tree.delete(&1));
+    /// assert!(!tree.delete(&1));
+    /// ```
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        // the top-down delete only works on values that exist
+        if !self.contains(delete_value) {
+            return false;
+        }
+        // if both children of root are black, set root to red
+        let root = self.root;
+        if !self.is_red(self.nodes[root].left) && !self.is_red(self.nodes[root].right) {
+            self.nodes[root].meta = NodeColor::Red;
+        }
+        self.root = self.node_delete(root, delete_value).0;
+        if self.root != NIL {
+            self.nodes[self.root].meta = NodeColor::Black;
+        }
+        true
+    }
+
+    /// Insert data into the subtree, performs any rotations necessary to maintain balance,
+    /// and then returns the new root to this subtree and the replaced equal value
+    fn node_insert(&mut self, node: u32, insert_value: T) -> (u32, Option<T>) {
+        if node == NIL {
+            return (self.nodes.alloc(insert_value, NodeColor::Red), None);
+        }
+        let replaced = match self
+            .comparator
+            .compare(&insert_value, &self.nodes[node].value)
+        {
+            Ordering::Less => {
+                let (left, replaced) = self.node_insert(self.nodes[node].left, insert_value);
+                self.nodes[node].left = left;
+                replaced
+            }
+            Ordering::Greater => {
+                let (right, replaced) = self.node_insert(self.nodes[node].right, insert_value);
+                self.nodes[node].right = right;
+                replaced
+            }
+            // equal, update value, the shape does not change
+            Ordering::Equal => {
+                let value = std::mem::replace(&mut self.nodes[node].value, insert_value);
+                return (node, Some(value));
+            }
+        };
+        (self.balance(node), replaced)
+    }
+
+    /// Delete data from the subtree, performs any rotations necessary to maintain balance,
+    /// and then returns the new root to this subtree and the deleted value
+    ///
+    /// The value must exist in the subtree: on the way down every node we step into
+    /// is made part of a 3-node or 4-node, so that the removed node is never a 2-node.
+    fn node_delete(&mut self, node: u32, delete_value: &T) -> (u32, T) {
+        let mut node = node;
+        let removed;
+        if self
+            .comparator
+            .compare(delete_value, &self.nodes[node].value)
+            == Ordering::Less
+        {
+            // the left child is a 2-node, borrow a red link from the right
+            let left = self.nodes[node].left;
+            if !self.is_red(left) && !self.is_red(self.nodes[left].left) {
+                node = self.move_red_left(node);
+            }
+            let (left, removed_value) = self.node_delete(self.nodes[node].left, delete_value);
+            self.nodes[node].left = left;
+            removed = removed_value;
+        } else {
+            // lean the red link to the right so it can be carried down
+            if self.is_red(self.nodes[node].left) {
+                node = self.right_rotate(node);
+            }
+            // found at the bottom of the tree, just remove the node
+            let found = self
+                .comparator
+                .compare(delete_value, &self.nodes[node].value)
+                == Ordering::Equal;
+            if found && self.nodes[node].right == NIL {
+                return (NIL, self.nodes.free(node).value);
+            }
+            // the right child is a 2-node, borrow a red link from the left
+            let right = self.nodes[node].right;
+            if !self.is_red(right) && !self.is_red(self.nodes[right].left) {
+                node = self.move_red_right(node);
+            }
+            if self
+                .comparator
+                .compare(delete_value, &self.nodes[node].value)
+                == Ordering::Equal
+            {
+                // the min node of right takes the place of the node
+                let (right, min_node) = self.delete_min(self.nodes[node].right);
+                self.nodes[min_node].left = self.nodes[node].left;
+                self.nodes[min_node].right = right;
+                self.nodes[min_node].meta = self.nodes[node].meta.clone();
+                removed = self.nodes.free(node).value;
+                node = min_node;
+            } else {
+                let (right, removed_value) = self.node_delete(self.nodes[node].right, delete_value);
+                self.nodes[node].right = right;
+                removed = removed_value;
+            }
+        }
+        (self.balance(node), removed)
+    }
+
+    /// Detach the min node of the subtree, and then returns the new root to this subtree
+    /// and the detached node
+    fn delete_min(&mut self, node: u32) -> (u32, u32) {
+        let mut node = node;
+        // the min node has no left child, and then it has no right child either
+        let left = self.nodes[node].left;
+        if left == NIL {
+            return (NIL, node);
+        }
+        if !self.is_red(left) && !self.is_red(self.nodes[left].left) {
+            node = self.move_red_left(node);
+        }
+        let (left, min_node) = self.delete_min(self.nodes[node].left);
+        self.nodes[node].left = left;
+        (self.balance(node), min_node)
+    }
+
+    /// Assuming that node is red and both node.left and node.left.left are black,
+    /// make node.left or one of its children red.
+    fn move_red_left(&mut self, node: u32) -> u32 {
+        self.reverse_colors(node);
+        let right = self.nodes[node].right;
+        if self.is_red(self.nodes[right].left) {
+            self.nodes[node].right = self.right_rotate(right);
+            let node = self.left_rotate(node);
+            self.reverse_colors(node);
+            return node;
+        }
+        node
+    }
+
+    /// Assuming that node is red and both node.right and node.right.left are black,
+    /// make node.right or one of its children red.
+    fn move_red_right(&mut self, node: u32) -> u32 {
+        self.reverse_colors(node);
+        let left = self.nodes[node].left;
+        if self.is_red(self.nodes[left].left) {
+            let node = self.right_rotate(node);
+            self.reverse_colors(node);
+            return node;
+        }
+        node
+    }
+
+    /// Restore the left-leaning red-black invariants on the way up
+    fn balance(&mut self, node: u32) -> u32 {
+        let mut node = node;
+        self.nodes.update_size(node);
+        // if right is red and left is black, then left rotate
+        if self.is_red(self.nodes[node].right) && !self.is_red(self.nodes[node].left) {
+            node = self.left_rotate(node);
+        }
+        // if left and left's left are both red, then right rotate
+        let left = self.nodes[node].left;
+        if self.is_red(left) && self.is_red(self.nodes[left].left) {
+            node = self.right_rotate(node);
+        }
+        // if left and right are both red, then change color
+        if self.is_red(self.nodes[node].left) && self.is_red(self.nodes[node].right) {
+            self.reverse_colors(node);
+        }
+        node
+    }
+
+    /// Rotate the subtree rooted at this node to the left and
+    /// return the new root to this subtree.
+    fn left_rotate(&mut self, node: u32) -> u32 {
+        let right = self.nodes[node].right;
+        self.nodes[node].right = self.nodes[right].left;
+        self.nodes[right].left = node;
+        self.nodes[right].meta = self.nodes[node].meta.clone();
+        self.nodes[node].meta = NodeColor::Red;
+        self.nodes.update_size(node);
+        self.nodes.update_size(right);
+        right
+    }
+
+    /// Rotate the subtree rooted at this node to the right and
+    /// returns the new root to this subtree.
+    fn right_rotate(&mut self, node: u32) -> u32 {
+        let left = self.nodes[node].left;
+        self.nodes[node].left = self.nodes[left].right;
+        self.nodes[left].right = node;
+        self.nodes[left].meta = self.nodes[node].meta.clone();
+        self.nodes[node].meta = NodeColor::Red;
+        self.nodes.update_size(node);
+        self.nodes.update_size(left);
+        left
+    }
+
+    // make NIL to be real leaves with black color
+    fn is_red(&self, node: u32) -> bool {
+        node != NIL && self.nodes[node].meta == NodeColor::Red
+    }
+
+    // reverse the color of node and its two children
+    fn reverse_colors(&mut self, node: u32) {
+        for index in [node, self.nodes[node].left, self.nodes[node].right].iter() {
+            let color = &mut self.nodes[*index].meta;
+            *color = match color {
+                NodeColor::Red => NodeColor::Black,
+                NodeColor::Black => NodeColor::Red,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures;
+
+    // check the red-black properties and the stored sizes, return the black height
+    fn check_node<T: Ord>(tree: &ArenaRBTree<T>, node: u32) -> usize {
+        if node == NIL {
+            return 1;
+        }
+        let left = tree.nodes[node].left;
+        let right = tree.nodes[node].right;
+        // red links lean left and never follow each other
+        assert!(!tree.is_red(right));
+        assert!(!(tree.is_red(node) && tree.is_red(left)));
+        let black_height = check_node(tree, left);
+        assert_eq!(black_height, check_node(tree, right));
+        assert_eq!(
+            tree.nodes[node].size,
+            tree.nodes.size(left) + tree.nodes.size(right) + 1
+        );
+        black_height + if tree.is_red(node) { 0 } else { 1 }
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(|tree: &ArenaRBTree<i32>| {
+            check_node(tree, tree.root);
+        });
+    }
+
+    #[test]
+    fn test_reuse_slots() {
+        let mut tree = ArenaRBTree::new();
+        for value in 0..100 {
+            tree.insert(value);
+        }
+        for value in 0..100 {
+            tree.delete(&value);
+        }
+        assert!(tree.is_empty());
+        for value in 100..200 {
+            tree.insert(value);
+        }
+        // the freed slots are used again
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.nodes.capacity(), 100);
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(|tree: &ArenaRBTree<i32>| {
+            check_node(tree, tree.root);
+        });
+    }
+}
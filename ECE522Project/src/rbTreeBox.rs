@@ -0,0 +1,412 @@
+//! Box-based red-black tree
+//!
+//! You can generate a left-leaning red-black tree whose nodes own their children
+//! through `Box`, and insert or delete nodes.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::boxTree::{BoxNode, OptionBoxNode};
+pub use crate::commonTrait::{CommonTreeTrait, OrderedSet};
+use crate::commonTrait::{Comparator, IntoIter};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+pub use crate::rbTreeFast::NodeColor;
+
+type RBNode<T> = Box<BoxNode<T, NodeColor>>;
+type OptionRBNode<T> = OptionBoxNode<T, NodeColor>;
+
+/// Structure of BoxRBTree, it balances the same way as
+/// [FastRBTree](../rbTreeFast/struct.FastRBTree.html)
+#[derive(Clone, Debug)]
+pub struct BoxRBTree<T: Ord> {
+    root: OptionRBNode<T>,
+    comparator: Comparator<T>,
+}
+
+// extend from common tree trait
+impl<T: Ord> CommonTreeTrait<T> for BoxRBTree<T> {
+    type Handle<'a>
+        = &'a BoxNode<T, NodeColor>
+    where
+        Self: 'a;
+
+    fn get_root(&self) -> Option<&BoxNode<T, NodeColor>> {
+        self.root.as_deref()
+    }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
+}
+
+// construct and mutate BoxRBTree in generic code
+impl<T: Ord> OrderedSet<T> for BoxRBTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        BoxRBTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        BoxRBTree {
+            root: None,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        BoxRBTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        BoxRBTree::delete(self, value)
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for BoxRBTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, RBNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter::new(self.root.take(), len)
+    }
+}
+
+// draw BoxRBTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for BoxRBTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        let nil_leaf = nil_leaves.then(|| DotStyle::filled(NodeColor::Black.fill()));
+        dot::to_dot(self.root.as_deref(), nil_leaf, |node| {
+            DotStyle::filled(node.meta.fill())
+        })
+    }
+}
+
+// draw BoxRBTree as text
+impl<T: Ord + fmt::Debug> Pretty for BoxRBTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.root.as_deref(), |node| {
+            Some(node.meta.to_string().to_owned())
+        })
+    }
+}
+
+impl<T: Ord> Default for BoxRBTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implementations of BoxRBTree
+impl<T: Ord> BoxRBTree<T> {
+    /// Create a new red-black Tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeBox::BoxRBTree;
+    /// let mut tree: BoxRBTree<u32> = BoxRBTree::new();
+    /// ```
+    pub fn new() -> Self {
+        BoxRBTree {
+            root: None,
+            comparator: Comparator::default(),
+        }
+    }
+
+    /// Create a new red-black Tree ordered by the comparator instead of `Ord`, which tells
+    /// how the first value compares with the second one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let mut tree = BoxRBTree::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(Some(2), tree.min());
+    /// ```
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        BoxRBTree {
+            root: None,
+            comparator: Comparator::new(cmp),
+        }
+    }
+
+    /// Insert a new value to the red-black Tree, return false if the value already exists
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeBox::BoxRBTree;
+    /// let mut tree = BoxRBTree::new();
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
+    /// ```
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        let (mut root, replaced) =
+            Self::node_insert(self.root.take(), insert_value, &self.comparator);
+        root.meta = NodeColor::Black;
+        self.root = Some(root);
+        replaced.is_none()
+    }
+
+    /// Delete a value from the red-black Tree, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeBox::BoxRBTree;
+    /// let mut tree = BoxRBTree::new();
+    /// tree.insert(1);
+    /// assert!(tree.delete(&1));
+    /// assert!(!tree.delete(&1));
+    /// ```
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        // the top-down delete only works on values that exist
+        if !self.contains(delete_value) {
+            return false;
+        }
+        let mut root = self.root.take().unwrap();
+        // if both children of root are black, set root to red
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            root.meta = NodeColor::Red;
+        }
+        self.root = Self::node_delete(root, delete_value, &self.comparator).0;
+        if let Some(root) = self.root.as_mut() {
+            root.meta = NodeColor::Black;
+        }
+        true
+    }
+
+    /// Insert data into the subtree, performs any rotations necessary to maintain balance,
+    /// and then returns the new root to this subtree and the replaced equal value
+    fn node_insert(
+        node: OptionRBNode<T>,
+        insert_value: T,
+        comparator: &Comparator<T>,
+    ) -> (RBNode<T>, Option<T>) {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (BoxNode::new(insert_value, NodeColor::Red), None),
+        };
+        let replaced = match comparator.compare(&insert_value, &node.value) {
+            Ordering::Less => {
+                let (left, replaced) =
+                    Self::node_insert(node.left.take(), insert_value, comparator);
+                node.left = Some(left);
+                replaced
+            }
+            Ordering::Greater => {
+                let (right, replaced) =
+                    Self::node_insert(node.right.take(), insert_value, comparator);
+                node.right = Some(right);
+                replaced
+            }
+            // equal, update value, the shape does not change
+            Ordering::Equal => {
+                let value = std::mem::replace(&mut node.value, insert_value);
+                return (node, Some(value));
+            }
+        };
+        (Self::balance(node), replaced)
+    }
+
+    /// Delete data from the subtree, performs any rotations necessary to maintain balance,
+    /// and then returns the new root to this subtree and the deleted value
+    ///
+    /// The value must exist in the subtree: on the way down every node we step into
+    /// is made part of a 3-node or 4-node, so that the removed node is never a 2-node.
+    fn node_delete(
+        node: RBNode<T>,
+        delete_value: &T,
+        comparator: &Comparator<T>,
+    ) -> (OptionRBNode<T>, T) {
+        let mut node = node;
+        let removed;
+        if comparator.compare(delete_value, &node.value) == Ordering::Less {
+            // the left child is a 2-node, borrow a red link from the right
+            if !Self::is_red(&node.left) && !Self::is_red(&node.left.as_ref().unwrap().left) {
+                node = Self::move_red_left(node);
+            }
+            let (left, removed_value) =
+                Self::node_delete(node.left.take().unwrap(), delete_value, comparator);
+            node.left = left;
+            removed = removed_value;
+        } else {
+            // lean the red link to the right so it can be carried down
+            if Self::is_red(&node.left) {
+                node = Self::right_rotate(node);
+            }
+            // found at the bottom of the tree, just remove the node
+            let found = comparator.compare(delete_value, &node.value) == Ordering::Equal;
+            if found && node.right.is_none() {
+                return (None, node.value);
+            }
+            // the right child is a 2-node, borrow a red link from the left
+            if !Self::is_red(&node.right) && !Self::is_red(&node.right.as_ref().unwrap().left) {
+                node = Self::move_red_right(node);
+            }
+            if comparator.compare(delete_value, &node.value) == Ordering::Equal {
+                // the min node of right takes the place of the node
+                let (right, mut min_node) = Self::delete_min(node.right.take().unwrap());
+                min_node.left = node.left.take();
+                min_node.right = right;
+                min_node.meta = node.meta.clone();
+                removed = node.value;
+                node = min_node;
+            } else {
+                let (right, removed_value) =
+                    Self::node_delete(node.right.take().unwrap(), delete_value, comparator);
+                node.right = right;
+                removed = removed_value;
+            }
+        }
+        (Some(Self::balance(node)), removed)
+    }
+
+    /// Detach the min node of the subtree, and then returns the new root to this subtree
+    /// and the detached node
+    fn delete_min(node: RBNode<T>) -> (OptionRBNode<T>, RBNode<T>) {
+        let mut node = node;
+        // the min node has no left child, and then it has no right child either
+        if node.left.is_none() {
+            return (None, node);
+        }
+        if !Self::is_red(&node.left) && !Self::is_red(&node.left.as_ref().unwrap().left) {
+            node = Self::move_red_left(node);
+        }
+        let (left, min_node) = Self::delete_min(node.left.take().unwrap());
+        node.left = left;
+        (Some(Self::balance(node)), min_node)
+    }
+
+    /// Assuming that node is red and both node.left and node.left.left are black,
+    /// make node.left or one of its children red.
+    fn move_red_left(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        Self::reverse_colors(&mut node);
+        if Self::is_red(&node.right.as_ref().unwrap().left) {
+            node.right = Some(Self::right_rotate(node.right.take().unwrap()));
+            node = Self::left_rotate(node);
+            Self::reverse_colors(&mut node);
+        }
+        node
+    }
+
+    /// Assuming that node is red and both node.right and node.right.left are black,
+    /// make node.right or one of its children red.
+    fn move_red_right(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        Self::reverse_colors(&mut node);
+        if Self::is_red(&node.left.as_ref().unwrap().left) {
+            node = Self::right_rotate(node);
+            Self::reverse_colors(&mut node);
+        }
+        node
+    }
+
+    /// Restore the left-leaning red-black invariants on the way up
+    fn balance(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        node.update_size();
+        // if right is red and left is black, then left rotate
+        if Self::is_red(&node.right) && !Self::is_red(&node.left) {
+            node = Self::left_rotate(node);
+        }
+        // if left and left's left are both red, then right rotate
+        if Self::is_red(&node.left) && Self::is_red(&node.left.as_ref().unwrap().left) {
+            node = Self::right_rotate(node);
+        }
+        // if left and right are both red, then change color
+        if Self::is_red(&node.left) && Self::is_red(&node.right) {
+            Self::reverse_colors(&mut node);
+        }
+        node
+    }
+
+    /// Rotate the subtree rooted at this node to the left and
+    /// return the new root to this subtree.
+    fn left_rotate(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        let mut right = node.right.take().unwrap();
+        node.right = right.left.take();
+        right.meta = std::mem::replace(&mut node.meta, NodeColor::Red);
+        node.update_size();
+        right.left = Some(node);
+        right.update_size();
+        right
+    }
+
+    /// Rotate the subtree rooted at this node to the right and
+    /// returns the new root to this subtree.
+    fn right_rotate(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        let mut left = node.left.take().unwrap();
+        node.left = left.right.take();
+        left.meta = std::mem::replace(&mut node.meta, NodeColor::Red);
+        node.update_size();
+        left.right = Some(node);
+        left.update_size();
+        left
+    }
+
+    // make None to be real leaves with black color
+    fn is_red(node: &OptionRBNode<T>) -> bool {
+        node.as_ref()
+            .is_some_and(|node| node.meta == NodeColor::Red)
+    }
+
+    // reverse the color of node and its two children
+    fn reverse_colors(node: &mut RBNode<T>) {
+        fn reverse(color: &mut NodeColor) {
+            *color = match color {
+                NodeColor::Red => NodeColor::Black,
+                NodeColor::Black => NodeColor::Red,
+            };
+        }
+        reverse(&mut node.meta);
+        for child in [&mut node.left, &mut node.right].iter_mut() {
+            if let Some(child) = child.as_mut() {
+                reverse(&mut child.meta);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::boxTree::size;
+    use crate::fixtures;
+
+    // check the red-black properties and the stored sizes, return the black height
+    fn check_node<T: Ord>(node: &OptionRBNode<T>) -> usize {
+        let current = match node {
+            Some(current) => current,
+            None => return 1,
+        };
+        // red links lean left and never follow each other
+        assert!(!BoxRBTree::is_red(&current.right));
+        assert!(!(BoxRBTree::is_red(node) && BoxRBTree::is_red(&current.left)));
+        let black_height = check_node(&current.left);
+        assert_eq!(black_height, check_node(&current.right));
+        assert_eq!(current.size, size(&current.left) + size(&current.right) + 1);
+        black_height + if BoxRBTree::is_red(node) { 0 } else { 1 }
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(|tree: &BoxRBTree<i32>| {
+            check_node(&tree.root);
+        });
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(|tree: &BoxRBTree<i32>| {
+            check_node(&tree.root);
+        });
+    }
+}
@@ -2,12 +2,19 @@
 //!
 //! You can generate a red-black tree, and insert or delete nodes.
 
-use std::cell::RefCell;
-use std::cmp::max;
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
 use std::fmt;
+use std::iter::FromIterator;
 use std::rc::Rc;
 
-pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait};
+use crate::commonTrait::{
+    clone_nodes, collect_sorted, dedup_sorted, drop_nodes, is_sorted, Comparator, IntoIter, Iter,
+};
+pub use crate::commonTrait::{CommonTreeNodeTrait, CommonTreeTrait, OrderedSet};
+use crate::dot::{self, DotStyle, ToDot};
+use crate::pretty::{self, Pretty};
+use crate::treeMap::MapBackend;
 
 /// Color representation for the [TreeNode](struct.TreeNode.html)
 /// of [FastRBTree](struct.FastRBTree.html) struct
@@ -19,27 +26,24 @@ pub enum NodeColor {
     Black,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-enum NodeDirection {
-    Left,
-    Right,
-}
-
 /// Structure of FastRBTree
-#[derive(Clone, Debug, PartialEq)]
-pub struct FastRBTree<T: Ord + Copy + fmt::Debug> {
+#[derive(Debug, PartialEq)]
+pub struct FastRBTree<T: Ord> {
     root: OptionFastRBTreeNode<T>,
+    comparator: Comparator<T>,
 }
 
 /// Node struct for [FastRBTree](struct.FastRBTree.html) struct
 #[derive(Clone, Debug, PartialEq)]
-pub struct TreeNode<T: Ord + Copy + fmt::Debug> {
+pub struct TreeNode<T: Ord> {
     /// The color of the node
     color: NodeColor,
     /// Data stored in the node
     value: T,
     left: OptionFastRBTreeNode<T>,
     right: OptionFastRBTreeNode<T>,
+    /// Number of nodes in the subtree rooted at this node
+    size: usize,
 }
 
 type FastRBTreeNode<T> = Rc<RefCell<TreeNode<T>>>;
@@ -47,24 +51,42 @@ type OptionFastRBTreeNode<T> = Option<FastRBTreeNode<T>>;
 
 /// Implementations of NodeColor
 impl NodeColor {
-    fn to_string(&self) -> &str {
+    pub(crate) fn to_string(&self) -> &str {
         if self == &NodeColor::Red {
             "r"
         } else {
             "b"
         }
     }
+
+    // the color of the node in a Graphviz graph
+    pub(crate) fn fill(&self) -> &'static str {
+        if self == &NodeColor::Red {
+            "red"
+        } else {
+            "black"
+        }
+    }
 }
 
 // extend from common tree trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeTrait<T, TreeNode<T>> for FastRBTree<T> {
+impl<T: Ord> CommonTreeTrait<T> for FastRBTree<T> {
+    type Handle<'a>
+        = FastRBTreeNode<T>
+    where
+        Self: 'a;
+
     fn get_root(&self) -> OptionFastRBTreeNode<T> {
         return self.root.clone();
     }
+
+    fn get_comparator(&self) -> Comparator<T> {
+        self.comparator.clone()
+    }
 }
 
 // extend from common tree node trait
-impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
+impl<T: Ord> CommonTreeNodeTrait<T> for TreeNode<T> {
     fn get_left(&self) -> OptionFastRBTreeNode<T> {
         return self.left.clone();
     }
@@ -73,89 +95,282 @@ impl<T: Ord + Copy + fmt::Debug> CommonTreeNodeTrait<T> for TreeNode<T> {
         return self.right.clone();
     }
 
-    fn get_value(&self) -> T {
-        return self.value;
+    fn get_value(&self) -> &T {
+        &self.value
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn take_left(&mut self) -> OptionFastRBTreeNode<T> {
+        self.left.take()
+    }
+
+    fn take_right(&mut self) -> OptionFastRBTreeNode<T> {
+        self.right.take()
+    }
+
+    fn into_value(self) -> T {
+        self.value
+    }
+}
+
+// free the nodes without recursing through them
+impl<T: Ord> Drop for FastRBTree<T> {
+    fn drop(&mut self) {
+        drop_nodes(self.root.take());
+    }
+}
+
+// copy every node, so that changing the copy leaves the original alone
+impl<T: Ord + Clone> Clone for FastRBTree<T> {
+    fn clone(&self) -> Self {
+        let root = clone_nodes(&self.root, |node, left, right| {
+            Rc::new(RefCell::new(TreeNode {
+                color: node.color.clone(),
+                value: node.value.clone(),
+                left,
+                right,
+                size: node.size,
+            }))
+        });
+        FastRBTree {
+            root,
+            comparator: self.comparator.clone(),
+        }
+    }
+}
+
+// consume the tree in order
+impl<T: Ord> IntoIterator for FastRBTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T, FastRBTreeNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter::new(self.root.take(), len)
+    }
+}
+
+// iterate over the tree in order
+impl<'a, T: Ord + Clone> IntoIterator for &'a FastRBTree<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, FastRBTreeNode<T>>;
+
+    fn into_iter(self) -> Iter<'a, T, FastRBTreeNode<T>> {
+        self.iter()
+    }
+}
+
+// build the tree from values in ascending order directly, or insert them one by one
+impl<T: Ord> FromIterator<T> for FastRBTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = FastRBTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+// values in ascending order are built into the tree directly if it is empty,
+// otherwise they are inserted one by one, since FastRBTree cannot join two trees
+impl<T: Ord> Extend<T> for FastRBTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let values: Vec<T> = iter.into_iter().collect();
+        if self.root.is_none() && is_sorted(&values, &self.comparator) {
+            let values = dedup_sorted(values, &self.comparator);
+            *self = Self::from_sorted_values(values, self.comparator.clone());
+        } else {
+            for value in values {
+                self.insert(value);
+            }
+        }
+    }
+}
+
+// construct and mutate FastRBTree in generic code
+impl<T: Ord> OrderedSet<T> for FastRBTree<T> {
+    fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        FastRBTree::with_comparator(cmp)
+    }
+
+    fn from_comparator(comparator: Comparator<T>) -> Self {
+        FastRBTree {
+            root: None,
+            comparator,
+        }
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        FastRBTree::insert(self, value)
+    }
+
+    fn delete(&mut self, value: &T) -> bool {
+        FastRBTree::delete(self, value)
+    }
+}
+
+// back a TreeMap with FastRBTree
+impl<T: Ord> MapBackend<T> for FastRBTree<T> {
+    type Node = TreeNode<T>;
+
+    fn get_root_node(&self) -> OptionFastRBTreeNode<T> {
+        self.root.clone()
+    }
+
+    fn borrow_value(node: &Rc<RefCell<TreeNode<T>>>) -> Ref<'_, T> {
+        Ref::map(node.borrow(), |node| &node.value)
+    }
+
+    fn borrow_value_mut(node: &Rc<RefCell<TreeNode<T>>>) -> RefMut<'_, T> {
+        RefMut::map(node.borrow_mut(), |node| &mut node.value)
+    }
+
+    fn replace(&mut self, value: T) -> Option<T> {
+        let (root, replaced) = TreeNode::node_insert(self.root.take(), value, &self.comparator);
+        self.root = root;
+        if let Some(root) = &self.root {
+            root.borrow_mut().color = NodeColor::Black;
+        }
+        replaced
+    }
+
+    fn remove_node<F: Fn(&T) -> Ordering>(&mut self, cmp: &F) -> OptionFastRBTreeNode<T> {
+        // the top-down delete only works on values that exist
+        self.find_node(cmp)?;
+        self.delete_from_root(|root| TreeNode::node_delete(root, cmp))
+    }
+}
+
+// draw FastRBTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for FastRBTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        let nil_leaf = nil_leaves.then(|| DotStyle::filled(NodeColor::Black.fill()));
+        dot::to_dot(self.get_root(), nil_leaf, |node| {
+            DotStyle::filled(node.borrow().color.fill())
+        })
+    }
+}
+
+// draw FastRBTree as text
+impl<T: Ord + fmt::Debug> Pretty for FastRBTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.get_root(), |node| {
+            Some(node.borrow().color.to_string().to_owned())
+        })
+    }
+}
+
+impl<T: Ord> Default for FastRBTree<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Implementations of FastRBTree
 // FastRBTree
-impl<T: Ord + Copy + fmt::Debug> FastRBTree<T> {
+impl<T: Ord> FastRBTree<T> {
     /// Create a new red-black Tree
     ///
     /// # Example
     ///
     /// ```
-    /// use tree_collections::FastRBTree::FastRBTree;
-    /// let mut tree = FastRBTree::new();
+    /// use tree_collections::rbTreeFast::FastRBTree;
+    /// let mut tree: FastRBTree<u32> = FastRBTree::new();
     /// ```
     pub fn new() -> Self {
-        FastRBTree { root: None }
+        FastRBTree {
+            root: None,
+            comparator: Comparator::default(),
+        }
     }
 
-    /// Insert a new value to the red-black Tree
+    /// Create a new red-black Tree ordered by the comparator instead of `Ord`,
+    /// which tells how the first value compares with the second one
     ///
     /// # Example
     ///
     /// ```
-    /// use tree_collections::FastRBTree::FastRBTree;
-    /// let mut tree = FastRBTree::new();
-    /// tree.insert(1);
+    /// use tree_collections::prelude::*;
+    /// let mut tree = FastRBTree::with_comparator(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+    /// tree.insert("ccc");
+    /// tree.insert("a");
+    /// tree.insert("bb");
+    /// assert_eq!(vec!["a", "bb", "ccc"], tree.iter().collect::<Vec<_>>());
+    /// assert!(tree.delete(&"zz"));
     /// ```
-    pub fn insert(&mut self, insert_value: T) {
-        self.root = TreeNode::node_insert(self.root.clone(), insert_value);
-        self.root.clone().unwrap().borrow_mut().color = NodeColor::Black;
-    }
-
-    // /// Delete a value from the red-black Tree
-    // ///
-    // /// # Example
-    // ///
-    // /// ```
-    // /// use tree_collections::FastRBTree::FastRBTree;
-    // /// let mut tree = FastRBTree::new();
-    // /// tree.delete(1);
-    // /// ```
-    // pub fn delete(&mut self, delete_value: T) {
+    pub fn with_comparator<F: Fn(&T, &T) -> Ordering + Send + Sync + 'static>(cmp: F) -> Self {
+        FastRBTree {
+            root: None,
+            comparator: Comparator::new(cmp),
+        }
+    }
 
-    // }
+    /// Insert a new value to the red-black Tree, return false if the value already exists
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeFast::FastRBTree;
+    /// let mut tree = FastRBTree::new();
+    /// assert!(tree.insert(1));
+    /// assert!(!tree.insert(1));
+    /// ```
+    pub fn insert(&mut self, insert_value: T) -> bool {
+        self.replace(insert_value).is_none()
+    }
 
-    pub fn pre_order_traverse(&self, node: FastRBTreeNode<T>, container: &mut Vec<T>) {
-        container.push(node.borrow().value);
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.pre_order_traverse(left.unwrap(), container);
-        }
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.pre_order_traverse(right.unwrap(), container);
-        }
+    /// Delete a value from the red-black Tree, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeFast::FastRBTree;
+    /// let mut tree = FastRBTree::new();
+    /// tree.insert(1);
+    /// assert!(tree.delete(&1));
+    /// assert!(!tree.delete(&1));
+    /// ```
+    pub fn delete(&mut self, delete_value: &T) -> bool {
+        let comparator = self.comparator.clone();
+        self.remove_node(&|value: &T| comparator.compare(delete_value, value))
+            .is_some()
     }
 
-    // 下面这三个之后会不要，用上面的in_order_traversal
-    pub fn debug_preorder_traverse(&self, node: FastRBTreeNode<T>, container: &mut Vec<T>) {
-        container.push(node.borrow().value);
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.debug_preorder_traverse(left.unwrap(), container);
-        }
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.debug_preorder_traverse(right.unwrap(), container);
-        }
+    /// Delete the min value of the red-black Tree and return it, return None if the tree is empty
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeFast::FastRBTree;
+    /// let mut tree = FastRBTree::new();
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(tree.delete_min(), Some(1));
+    /// assert_eq!(tree.delete_min(), Some(2));
+    /// assert_eq!(tree.delete_min(), None);
+    /// ```
+    pub fn delete_min(&mut self) -> Option<T> {
+        self.delete_from_root(TreeNode::delete_min)
+            .map(Self::into_removed_value)
     }
 
-    // for testing
-    pub fn inorder_traverse(&self, node: FastRBTreeNode<T>, container: &mut Vec<T>) {
-        let left = node.borrow().left.clone();
-        if left.is_some() {
-            self.inorder_traverse(left.unwrap(), container);
-        }
-        container.push(node.borrow().value);
-        let right = node.borrow().right.clone();
-        if right.is_some() {
-            self.inorder_traverse(right.unwrap(), container);
-        }
+    /// Delete the max value of the red-black Tree and return it, return None if the tree is empty
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreeFast::FastRBTree;
+    /// let mut tree = FastRBTree::new();
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(tree.delete_max(), Some(2));
+    /// assert_eq!(tree.delete_max(), Some(1));
+    /// assert_eq!(tree.delete_max(), None);
+    /// ```
+    pub fn delete_max(&mut self) -> Option<T> {
+        self.delete_from_root(TreeNode::delete_max)
+            .map(Self::into_removed_value)
     }
 
     /// Return true if the tree match propertity 5
@@ -174,10 +389,8 @@ impl<T: Ord + Copy + fmt::Debug> FastRBTree<T> {
             (None, None) => true,
             (Some(_), None) | (None, Some(_)) => false,
             (Some(left), Some(right)) => {
-                let left_data = left.borrow().value;
-                let right_data = right.borrow().value;
                 //Test if 2 trees are equal
-                if left_data == right_data {
+                if left.borrow().value == right.borrow().value {
                     let left_left = left.borrow().left.clone();
                     let left_right = left.borrow().right.clone();
                     let right_left = right.borrow().left.clone();
@@ -189,11 +402,111 @@ impl<T: Ord + Copy + fmt::Debug> FastRBTree<T> {
             }
         }
     }
+
+    // run a top-down delete from the root, returning the detached node
+    fn delete_from_root<F>(&mut self, delete: F) -> OptionFastRBTreeNode<T>
+    where
+        F: FnOnce(FastRBTreeNode<T>) -> (OptionFastRBTreeNode<T>, FastRBTreeNode<T>),
+    {
+        let root = self.root.take()?;
+        // if both children of root are black, set root to red
+        if TreeNode::is_black(root.borrow().left.clone())
+            && TreeNode::is_black(root.borrow().right.clone())
+        {
+            root.borrow_mut().color = NodeColor::Red;
+        }
+        let (root, removed) = delete(root);
+        self.root = root;
+        if let Some(root) = &self.root {
+            root.borrow_mut().color = NodeColor::Black;
+        }
+        Some(removed)
+    }
+
+    // take the value out of a node detached by a delete
+    fn into_removed_value(node: FastRBTreeNode<T>) -> T {
+        match Rc::try_unwrap(node) {
+            Ok(node) => node.into_inner().into_value(),
+            Err(_) => panic!("removed node is still shared"),
+        }
+    }
+}
+
+/// Building FastRBTree from sorted values in O(n) time
+impl<T: Ord> FastRBTree<T> {
+    /// Build a balanced tree from values in ascending order without inserting them
+    /// one by one, keeping the last one of repeated values
+    ///
+    /// Panics if the values are not in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let tree = FastRBTree::from_sorted_iter(0..100);
+    /// assert_eq!(100, tree.len());
+    /// assert_eq!(7, tree.height());
+    /// ```
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted_values(collect_sorted(iter), Comparator::default())
+    }
+
+    // build a balanced tree from values in strictly ascending order of the comparator
+    fn from_sorted_values(values: Vec<T>, comparator: Comparator<T>) -> Self {
+        let len = values.len();
+        // the most black nodes on a path that a tree of len values can have
+        let black_height = usize::BITS - (len + 1).leading_zeros() - 1;
+        let root = Self::build_sorted(&mut values.into_iter(), len, black_height);
+        FastRBTree { root, comparator }
+    }
+
+    // build a subtree of the next len values with the given number of black nodes on every
+    // path, like a 2-3 tree: a black node with two children if they can hold the values,
+    // otherwise a black node with a red left child, which has three children together
+    fn build_sorted(
+        values: &mut std::vec::IntoIter<T>,
+        len: usize,
+        black_height: u32,
+    ) -> OptionFastRBTreeNode<T> {
+        if len == 0 {
+            return None;
+        }
+        let new_node = |value, color, left, right| {
+            let node = TreeNode {
+                color,
+                value,
+                left,
+                right,
+                size: 0,
+            };
+            let node = Rc::new(RefCell::new(node));
+            TreeNode::update_size(&node);
+            node
+        };
+        // the most values a child subtree can hold, with all nodes having red left children
+        let child_max = 3usize.saturating_pow(black_height - 1) - 1;
+        if len - 1 <= 2 * child_max {
+            let left_len = (len - 1) / 2;
+            let left = Self::build_sorted(values, left_len, black_height - 1);
+            let value = values.next().unwrap();
+            let right = Self::build_sorted(values, len - 1 - left_len, black_height - 1);
+            return Some(new_node(value, NodeColor::Black, left, right));
+        }
+        let rest = len - 2;
+        let (left_len, middle_len) = (rest / 3, (rest - rest / 3) / 2);
+        let left = Self::build_sorted(values, left_len, black_height - 1);
+        let red_value = values.next().unwrap();
+        let middle = Self::build_sorted(values, middle_len, black_height - 1);
+        let value = values.next().unwrap();
+        let right = Self::build_sorted(values, rest - left_len - middle_len, black_height - 1);
+        let red = new_node(red_value, NodeColor::Red, left, middle);
+        Some(new_node(value, NodeColor::Black, Some(red), right))
+    }
 }
 
 /// Implementations of TreeNode
 // TreeNode
-impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
+impl<T: Ord> TreeNode<T> {
     /// Create a new node
     /// ,which will be called by [FastRBTree](struct.FastRBTree.html)
     fn new(value: T) -> Self {
@@ -202,34 +515,51 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
             value: value,
             left: None,
             right: None,
+            size: 1,
         }
     }
 
     /// Insert data into the subtree, performs any rotations
     /// necessary to maintain banlance, and then returns the new root to this subtree
-    /// , which will be called by
+    /// and the replaced equal value, which will be called by
     /// [FastRBTree.insert](struct.FastRBTree.html#method.insert)
-    fn node_insert(node: OptionFastRBTreeNode<T>, insert_value: T) -> OptionFastRBTreeNode<T> {
+    fn node_insert(
+        node: OptionFastRBTreeNode<T>,
+        insert_value: T,
+        comparator: &Comparator<T>,
+    ) -> (OptionFastRBTreeNode<T>, Option<T>) {
         // if h is none, then return the first node
         match node {
-            None => return Some((Rc::new(RefCell::new(TreeNode::new(insert_value))))),
+            None => (
+                Some(Rc::new(RefCell::new(TreeNode::new(insert_value)))),
+                None,
+            ),
             Some(n) => {
                 // compare with root
-                let node_value = n.borrow().value;
-                if insert_value < node_value {
-                    // insert to left
-                    let left = n.borrow().left.clone();
-                    n.borrow_mut().left = Self::node_insert(left, insert_value);
-                } else if insert_value > node_value {
-                    // insert to right
-                    let right = n.borrow().right.clone();
-                    n.borrow_mut().right = Self::node_insert(right, insert_value);
-                } else {
-                    // insert here
-                    n.borrow_mut().value = insert_value; // equal, update value
-                }
+                let ordering = comparator.compare(&insert_value, &n.borrow().value);
+                let replaced = match ordering {
+                    Ordering::Less => {
+                        // insert to left
+                        let left = n.borrow_mut().left.take();
+                        let (left, replaced) = Self::node_insert(left, insert_value, comparator);
+                        n.borrow_mut().left = left;
+                        replaced
+                    }
+                    Ordering::Greater => {
+                        // insert to right
+                        let right = n.borrow_mut().right.take();
+                        let (right, replaced) = Self::node_insert(right, insert_value, comparator);
+                        n.borrow_mut().right = right;
+                        replaced
+                    }
+                    // equal, update value
+                    Ordering::Equal => {
+                        Some(std::mem::replace(&mut n.borrow_mut().value, insert_value))
+                    }
+                };
                 // maintain the tree by the RB tree rule
-                return Self::insert_maintain_rb(n.clone());
+                Self::update_size(&n);
+                (Self::insert_maintain_rb(n), replaced)
             }
         }
     }
@@ -237,16 +567,16 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
     /// Repair the coloring from inserting into a tree.
     fn insert_maintain_rb(node: FastRBTreeNode<T>) -> OptionFastRBTreeNode<T> {
         // if right is red and left is black, then left rotate
-        if Self::is_red(node.borrow().get_right()) && Self::is_black(node.borrow().get_left()) {
+        if Self::is_red(node.borrow().right.clone()) && Self::is_black(node.borrow().left.clone()) {
             let temp1 = Self::left_rotate(node.clone());
             // if left and left's left are both red, then right rotate
-            if Self::is_red(temp1.borrow().get_left())
-                && Self::is_red(temp1.borrow().get_left().unwrap().borrow().get_left())
+            if Self::is_red(temp1.borrow().left.clone())
+                && Self::is_red(temp1.borrow().left.clone().unwrap().borrow().left.clone())
             {
                 let temp2 = Self::right_rotate(temp1.clone());
                 // if left and right are both red, then change color
-                if Self::is_red(temp2.borrow().get_left())
-                    && Self::is_red(temp2.borrow().get_right())
+                if Self::is_red(temp2.borrow().left.clone())
+                    && Self::is_red(temp2.borrow().right.clone())
                 {
                     Self::flip_color(temp2.clone());
                 }
@@ -256,231 +586,170 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         }
 
         // if left and left's left are both red, then right rotate
-        if Self::is_red(node.borrow().get_left())
-            && Self::is_red(node.borrow().get_left().unwrap().borrow().get_left())
+        if Self::is_red(node.borrow().left.clone())
+            && Self::is_red(node.borrow().left.clone().unwrap().borrow().left.clone())
         {
             let temp1 = Self::right_rotate(node.clone());
             // if left and right are both red, then change color
-            if Self::is_red(temp1.borrow().get_left()) && Self::is_red(temp1.borrow().get_right()) {
+            if Self::is_red(temp1.borrow().left.clone())
+                && Self::is_red(temp1.borrow().right.clone())
+            {
                 Self::flip_color(temp1.clone());
             }
             return Some(temp1);
         }
 
         // if left and right are both red, then change color
-        if Self::is_red(node.borrow().get_left()) && Self::is_red(node.borrow().get_right()) {
+        if Self::is_red(node.borrow().left.clone()) && Self::is_red(node.borrow().right.clone()) {
             Self::flip_color(node.clone());
         }
 
         return Some(node);
     }
 
-    // /// Delete data from the subtree, performs any rotations
-    // /// necessary to maintain banlance, and then returns the new root to this subtree
-    // /// , which will be called by
-    // /// [FastRBTree.delete](struct.FastRBTree.html#method.delete)
-    // fn node_delete(node: FastRBTreeNode<T>, delete_value: T) -> OptionFastRBTreeNode<T> {
-    //     if node.borrow().value > delete_value {
-    //         let left_child = node.borrow().left.clone();
-    //         if left_child.is_some() {
-    //             Self::node_delete(left_child.unwrap(), delete_value);
-    //         }
-    //     } else if node.borrow().value < delete_value {
-    //         let right_child = node.borrow_mut().right.clone();
-    //         if right_child.is_some() {
-    //             Self::node_delete(right_child.unwrap(), delete_value);
-    //         }
-    //     } else {
-    //         let left = node.borrow_mut().left.clone();
-    //         let right = node.borrow_mut().right.clone();
-    //         // 1. Two children case: current node has two children
-    //         // if current node has two children, then recursively replace it with the min value of right
-    //         // delete the min value of right in the right tree
-    //         // the goal is to make the problem to be the case where current node has only one child
-    //         if left.is_some() && right.is_some() {
-    //             let min_of_right = right.clone().unwrap().borrow().get_min_value_in_children();
-    //             node.borrow_mut().value = min_of_right;
-    //             Self::node_delete(right.unwrap(), min_of_right);
-    //         }
-    //         // current node has one child or no child
-    //         else {
-    //             // 2. Red case: current node is red
-    //             // it means that current node has no child, just delete this node
-    //             if node.borrow().color == NodeColor::Red {
-    //                 let parent = node.borrow().parent.clone().unwrap();
-    //                 if Self::is_left(node.clone()) {
-    //                     parent.borrow_mut().left = None;
-    //                 } else {
-    //                     parent.borrow_mut().right = None;
-    //                 }
-    //             }
-    //             // current node is black
-    //             else {
-    //                 // 3.1 Black + left red case: current node is black and left child is red, right child is None
-    //                 // delete the current black node and move the left child to the current node place
-    //                 if left.is_some() && right.is_none() {
-    //                     let left = left.unwrap();
-    //                     // TODO
-    //                     // node.parent.child = left, left.parent = node.parent
-    //                     let parent = node.borrow().parent.clone();
-    //                     match parent {
-    //                         None => {
-    //                             left.borrow_mut().color = node.borrow().color.clone();
-    //                             left.borrow_mut().parent = None;
-    //                             return Some(left);
-    //                         }
-    //                         Some(parent) => {
-    //                             if Self::is_left(node.clone()) {
-    //                                 parent.borrow_mut().left = Some(left.clone());
-    //                                 left.borrow_mut().parent = Some(parent.clone());
-    //                                 left.borrow_mut().color = node.borrow().color.clone();
-    //                             } else {
-    //                                 parent.borrow_mut().right = Some(left.clone());
-    //                                 left.borrow_mut().parent = Some(parent.clone());
-    //                                 left.borrow_mut().color = node.borrow().color.clone();
-    //                             }
-    //                         }
-    //                     }
-    //                 }
-    //                 // 3.2 Black + right red case: current node is black and right child is red, left child is None
-    //                 // delete the current black node and move the right child to the current node place
-    //                 else if left.is_none() && right.is_some() {
-    //                     let right = right.unwrap();
-    //                     let parent = node.borrow().parent.clone();
-    //                     match parent {
-    //                         None => {
-    //                             right.borrow_mut().color = node.borrow().color.clone();
-    //                             right.borrow_mut().parent = None;
-    //                             return Some(right);
-    //                         }
-    //                         Some(parent) => {
-    //                             if Self::is_left(node.clone()) {
-    //                                 parent.borrow_mut().left = Some(right.clone());
-    //                                 right.borrow_mut().parent = Some(parent.clone());
-    //                                 right.borrow_mut().color = node.borrow().color.clone();
-    //                             } else {
-    //                                 parent.borrow_mut().right = Some(right.clone());
-    //                                 right.borrow_mut().parent = Some(parent.clone());
-    //                                 right.borrow_mut().color = node.borrow().color.clone();
-    //                             }
-    //                         }
-    //                     }
-    //                 }
-    //                 // 4. Black + no children case: current node is black and has no children
-    //                 else {
-    //                     let parent = node.borrow().parent.clone();
-    //                     match parent {
-    //                         // 4.1 current node is the root, then return None
-    //                         None => return None,
-    //                         // 4.2 current node has parent, then call delete_maintain_rb
-    //                         // and then delete the link between current node and its parent
-    //                         Some(parent) => {
-    //                             Self::delete_maintain_rb(node.clone());
-    //                             if Self::is_left(node.clone()) {
-    //                                 parent.borrow_mut().left = None;
-    //                             } else {
-    //                                 parent.borrow_mut().right = None;
-    //                             }
-    //                             node.borrow_mut().parent = None;
-    //                         }
-    //                     }
-    //                 }
-    //             }
-    //         }
-    //     }
-    //     // return the root
-    //     return Self::get_root(node);
-    // }
+    /// Delete data from the subtree, performs any rotations
+    /// necessary to maintain banlance, and then returns the new root to this subtree
+    /// and the detached node holding the deleted value, which will be called by
+    /// [FastRBTree.delete](struct.FastRBTree.html#method.delete)
+    ///
+    /// The value must exist in the subtree: on the way down every node we step into
+    /// is made part of a 3-node or 4-node, so that the removed node is never a 2-node.
+    fn node_delete<F: Fn(&T) -> Ordering>(
+        node: FastRBTreeNode<T>,
+        cmp: &F,
+    ) -> (OptionFastRBTreeNode<T>, FastRBTreeNode<T>) {
+        let mut node = node;
+        let removed;
+        if cmp(&node.borrow().value) == Ordering::Less {
+            // the left child is a 2-node, borrow a red link from the right
+            if Self::is_black(node.borrow().left.clone())
+                && Self::is_black(node.borrow().left.clone().unwrap().borrow().left.clone())
+            {
+                node = Self::move_red_left(node);
+            }
+            let left = node.borrow_mut().left.take().unwrap();
+            let (left, removed_node) = Self::node_delete(left, cmp);
+            node.borrow_mut().left = left;
+            removed = removed_node;
+        } else {
+            // lean the red link to the right so it can be carried down
+            if Self::is_red(node.borrow().left.clone()) {
+                node = Self::right_rotate(node);
+            }
+            // found at the bottom of the tree, just remove the node
+            if cmp(&node.borrow().value) == Ordering::Equal && node.borrow().right.is_none() {
+                return (None, node);
+            }
+            // the right child is a 2-node, borrow a red link from the left
+            if Self::is_black(node.borrow().right.clone())
+                && Self::is_black(node.borrow().right.clone().unwrap().borrow().left.clone())
+            {
+                node = Self::move_red_right(node);
+            }
+            let right = node.borrow_mut().right.take().unwrap();
+            let (right, removed_node) = if cmp(&node.borrow().value) == Ordering::Equal {
+                // replace current value with the min value of right, then delete that min
+                let (right, min_of_right) = Self::delete_min(right);
+                std::mem::swap(
+                    &mut node.borrow_mut().value,
+                    &mut min_of_right.borrow_mut().value,
+                );
+                (right, min_of_right)
+            } else {
+                Self::node_delete(right, cmp)
+            };
+            node.borrow_mut().right = right;
+            removed = removed_node;
+        }
+        (Some(Self::balance(node)), removed)
+    }
 
-    // /// Repair the coloring of the remaining nodes in the tree
-    // fn delete_maintain_rb(node: FastRBTreeNode<T>) {
-    //     let parent = node.borrow().parent.clone();
-    //     match parent {
-    //         // delete case 2: parent is None
-    //         // it means current node is the new root, just return
-    //         None => return,
-    //         Some(parent) => {
-    //             let sibling = Self::get_sibling(node.clone());
-    //             let direction;
-    //             if Self::is_left(node.clone()) {
-    //                 direction = NodeDirection::Left;
-    //             } else {
-    //                 direction = NodeDirection::Right;
-    //             }
-    //             match sibling {
-    //                 None => return,
-    //                 Some(sibling) => {
-    //                     // delete case 3: sibling is red; parent, close, distant are black
-    //                     if sibling.borrow().color == NodeColor::Red {
-    //                         // RotateDirRoot(T, P, dir);
-    //                         if direction == NodeDirection::Left {
-    //                             Self::left_rotate(parent.clone());
-    //                         } else {
-    //                             Self::right_rotate(parent.clone());
-    //                         }
-    //                         Self::set_red(parent.clone());
-    //                         Self::set_black(sibling.clone());
-    //                         // recursive
-    //                         Self::delete_maintain_rb(node.clone());
-    //                     }
-    //                     // sibling is black
-    //                     else {
-    //                         let option_close;
-    //                         let option_distant;
-    //                         if direction == NodeDirection::Left {
-    //                             option_close = sibling.borrow().left.clone();
-    //                             option_distant = sibling.borrow().right.clone()
-    //                         } else {
-    //                             option_close = sibling.borrow().right.clone();
-    //                             option_distant = sibling.borrow().left.clone()
-    //                         }
-    //                         // close and distant are black
-    //                         if Self::get_color(option_close.clone()) == NodeColor::Black
-    //                             && Self::get_color(option_distant.clone()) == NodeColor::Black
-    //                         {
-    //                             // delete case 1: parent, sibling, close, distant are all black
-    //                             if parent.borrow().color == NodeColor::Black {
-    //                                 Self::set_red(sibling.clone());
-    //                                 Self::delete_maintain_rb(parent.clone());
-    //                             }
-    //                             // delete case 4: parent is red; sibling, close, distant are black
-    //                             else {
-    //                                 Self::set_red(sibling.clone());
-    //                                 Self::set_black(parent.clone());
-    //                             }
-    //                         }
-    //                         // delete case 5: sibling and distant are black, close is red
-    //                         else if Self::get_color(option_close.clone()) == NodeColor::Red
-    //                             && Self::get_color(option_distant.clone()) == NodeColor::Black
-    //                         {
-    //                             // RotateDir(S,1-dir);  S is never the root
-    //                             if direction == NodeDirection::Left {
-    //                                 Self::right_rotate(sibling.clone());
-    //                             } else {
-    //                                 Self::left_rotate(sibling.clone());
-    //                             }
-    //                             Self::set_red(sibling.clone());
-    //                             Self::set_black(option_close.clone().unwrap());
-    //                             Self::delete_maintain_rb(node.clone());
-    //                         }
-    //                         // delete case 6: sibling is black, distant is red
-    //                         else if Self::get_color(option_distant.clone()) == NodeColor::Red {
-    //                             // RotateDirRoot(T,P,dir);  P may be the root
-    //                             if direction == NodeDirection::Left {
-    //                                 Self::left_rotate(parent.clone());
-    //                             } else {
-    //                                 Self::right_rotate(parent.clone());
-    //                             }
-    //                             sibling.borrow_mut().color = parent.borrow().color.clone();
-    //                             Self::set_black(parent.clone());
-    //                             Self::set_black(option_distant.clone().unwrap());
-    //                         }
-    //                     }
-    //                 }
-    //             }
-    //         }
-    //     }
-    // }
+    /// Delete the min value of the subtree, and then returns the new root to this subtree
+    /// and the detached min node
+    fn delete_min(node: FastRBTreeNode<T>) -> (OptionFastRBTreeNode<T>, FastRBTreeNode<T>) {
+        let mut node = node;
+        // the min node has no left child, and then it has no right child either
+        let left = match node.borrow().left.clone() {
+            Some(left) => left,
+            None => return (None, node.clone()),
+        };
+        if left.borrow().color == NodeColor::Black && Self::is_black(left.borrow().left.clone()) {
+            node = Self::move_red_left(node);
+        }
+        let left = node.borrow_mut().left.take().unwrap();
+        let (left, min_node) = Self::delete_min(left);
+        node.borrow_mut().left = left;
+        (Some(Self::balance(node)), min_node)
+    }
+
+    /// Delete the max value of the subtree, and then returns the new root to this subtree
+    /// and the detached max node
+    fn delete_max(node: FastRBTreeNode<T>) -> (OptionFastRBTreeNode<T>, FastRBTreeNode<T>) {
+        let mut node = node;
+        // lean the red link to the right so it can be carried down
+        if Self::is_red(node.borrow().left.clone()) {
+            node = Self::right_rotate(node);
+        }
+        // the max node has no right child, and then it has no left child either
+        let right = match node.borrow().right.clone() {
+            Some(right) => right,
+            None => return (None, node.clone()),
+        };
+        if right.borrow().color == NodeColor::Black && Self::is_black(right.borrow().left.clone()) {
+            node = Self::move_red_right(node);
+        }
+        let right = node.borrow_mut().right.take().unwrap();
+        let (right, max_node) = Self::delete_max(right);
+        node.borrow_mut().right = right;
+        (Some(Self::balance(node)), max_node)
+    }
+
+    /// Assuming that node is red and both node.left and node.left.left are black,
+    /// make node.left or one of its children red.
+    fn move_red_left(node: FastRBTreeNode<T>) -> FastRBTreeNode<T> {
+        Self::reverse_colors(node.clone());
+        let right = node.borrow().right.clone().unwrap();
+        if Self::is_red(right.borrow().left.clone()) {
+            node.borrow_mut().right = Some(Self::right_rotate(right));
+            let node = Self::left_rotate(node);
+            Self::reverse_colors(node.clone());
+            return node;
+        }
+        node
+    }
+
+    /// Assuming that node is red and both node.right and node.right.left are black,
+    /// make node.right or one of its children red.
+    fn move_red_right(node: FastRBTreeNode<T>) -> FastRBTreeNode<T> {
+        Self::reverse_colors(node.clone());
+        if Self::is_red(node.borrow().left.clone().unwrap().borrow().left.clone()) {
+            let node = Self::right_rotate(node);
+            Self::reverse_colors(node.clone());
+            return node;
+        }
+        node
+    }
+
+    /// Restore the left-leaning red-black invariants on the way up from deleting
+    fn balance(node: FastRBTreeNode<T>) -> FastRBTreeNode<T> {
+        let mut node = node;
+        Self::update_size(&node);
+        // if right is red and left is black, then left rotate
+        if Self::is_red(node.borrow().right.clone()) && Self::is_black(node.borrow().left.clone()) {
+            node = Self::left_rotate(node);
+        }
+        // if left and left's left are both red, then right rotate
+        if Self::is_red(node.borrow().left.clone())
+            && Self::is_red(node.borrow().left.clone().unwrap().borrow().left.clone())
+        {
+            node = Self::right_rotate(node);
+        }
+        // if left and right are both red, then change color
+        if Self::is_red(node.borrow().left.clone()) && Self::is_red(node.borrow().right.clone()) {
+            Self::reverse_colors(node.clone());
+        }
+        node
+    }
 
     // left and right rotate
     // node is the root of the subtree
@@ -495,6 +764,8 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         node.borrow_mut().right = temp;
         node_right.borrow_mut().color = node.borrow().color.clone();
         node.borrow_mut().color = NodeColor::Red;
+        Self::update_size(&node);
+        Self::update_size(&node_right);
         return node_right;
     }
 
@@ -508,33 +779,11 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         node.borrow_mut().left = temp;
         node_left.borrow_mut().color = node.borrow().color.clone();
         node.borrow_mut().color = NodeColor::Red;
+        Self::update_size(&node);
+        Self::update_size(&node_left);
         return node_left;
     }
 
-    // // Helper function for maintaining
-    // fn is_left(node: FastRBTreeNode<T>) -> bool {
-    //     // Return true if the node is the left child of its parent.
-    //     match node.borrow().parent.clone() {
-    //         Some(parent) => match parent.borrow().left.clone() {
-    //             Some(left) => Rc::ptr_eq(&left, &node),
-    //             None => false,
-    //         },
-    //         _ => false,
-    //     }
-    // }
-
-    // // Helper function for maintaining
-    // fn is_right(node: FastRBTreeNode<T>) -> bool {
-    //     // Return true if the node is the right child of its parent.
-    //     match node.borrow().parent.clone() {
-    //         Some(parent) => match parent.borrow().right.clone() {
-    //             Some(right) => Rc::ptr_eq(&right, &node),
-    //             None => false,
-    //         },
-    //         _ => false,
-    //     }
-    // }
-
     fn is_red(node: OptionFastRBTreeNode<T>) -> bool {
         return Self::get_color(node) == NodeColor::Red;
     }
@@ -543,19 +792,6 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         return Self::get_color(node) == NodeColor::Black;
     }
 
-    // Helper function for maintaining
-    // set node color
-    fn set_red(node: FastRBTreeNode<T>) -> FastRBTreeNode<T> {
-        node.borrow_mut().color = NodeColor::Red;
-        return node;
-    }
-
-    // Helper function for maintaining
-    fn set_black(node: FastRBTreeNode<T>) -> FastRBTreeNode<T> {
-        node.borrow_mut().color = NodeColor::Black;
-        return node;
-    }
-
     // Helper function for maintaining
     fn flip_color(node: FastRBTreeNode<T>) {
         node.borrow_mut().left.clone().unwrap().borrow_mut().color = NodeColor::Black;
@@ -563,52 +799,36 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
         node.borrow_mut().color = NodeColor::Red;
     }
 
-    // fn get_root(node: FastRBTreeNode<T>) -> OptionFastRBTreeNode<T> {
-    //     let parent = node.borrow().parent.clone();
-    //     match parent {
-    //         Some(p) => Self::get_root(p),
-    //         None => Some(node),
-    //     }
-    // }
+    // Helper function for deleting
+    fn reverse_color(node: FastRBTreeNode<T>) {
+        if node.borrow().color == NodeColor::Red {
+            node.borrow_mut().color = NodeColor::Black;
+        } else {
+            node.borrow_mut().color = NodeColor::Red;
+        }
+    }
 
-    // // Helper function for maintaining
-    // // get uncle
-    // fn get_uncle(node: FastRBTreeNode<T>) -> OptionFastRBTreeNode<T> {
-    //     let parent = node.borrow().parent.clone();
-    //     match parent {
-    //         // self is root
-    //         None => None,
-    //         Some(parent) => {
-    //             let grand_parent = parent.borrow().parent.clone();
-    //             match grand_parent {
-    //                 // parent is root
-    //                 None => None,
-    //                 Some(grand_parent) => {
-    //                     if Self::is_left(parent) {
-    //                         grand_parent.borrow().right.clone()
-    //                     } else {
-    //                         grand_parent.borrow().left.clone()
-    //                     }
-    //                 }
-    //             }
-    //         }
-    //     }
-    // }
+    // Helper function for deleting
+    // reverse the color of node and its two children
+    fn reverse_colors(node: FastRBTreeNode<T>) {
+        Self::reverse_color(node.borrow().left.clone().unwrap());
+        Self::reverse_color(node.borrow().right.clone().unwrap());
+        Self::reverse_color(node);
+    }
 
-    // // Helper function for maintaining
-    // fn get_sibling(node: FastRBTreeNode<T>) -> OptionFastRBTreeNode<T> {
-    //     // Get the current node's sibling, or None if it does not exist.
-    //     match node.borrow().parent.clone() {
-    //         None => None,
-    //         Some(parent) => {
-    //             if Self::is_left(node.clone()) {
-    //                 parent.borrow().right.clone()
-    //             } else {
-    //                 parent.borrow().left.clone()
-    //             }
-    //         }
-    //     }
-    // }
+    fn get_size(node: &OptionFastRBTreeNode<T>) -> usize {
+        match node {
+            None => 0,
+            Some(node) => node.borrow().size,
+        }
+    }
+
+    // Helper function for rotating
+    // recount the subtree size from the children
+    fn update_size(node: &FastRBTreeNode<T>) {
+        let size = Self::get_size(&node.borrow().left) + Self::get_size(&node.borrow().right) + 1;
+        node.borrow_mut().size = size;
+    }
 
     // Helper function for maintaining
     // make None to be real leaves with black color
@@ -650,6 +870,8 @@ impl<T: Ord + Copy + fmt::Debug> TreeNode<T> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fixtures;
+    use crate::nodeHandle::Order;
 
     // #[test]
     // fn test_rotation() {
@@ -743,13 +965,16 @@ mod test {
         let mut container = vec![];
         tree.pre_order_traversal_for_test(&mut container);
         assert_eq!(container, vec![20, 8, 0, 16, 24, 22]);
-        // let mut container = vec![];
-        // RedBlackTreeNode::debug_preorder_traverse(root.clone(), &mut container);
-        // assert_eq!(container, vec![0, -16, 16, 8, 22, 20, 24]);
-
-        // let mut container = vec![];
-        // RedBlackTreeNode::postorder_traverse(root, &mut container);
-        // assert_eq!(container, vec![-16, 8, 20, 24, 22, 16, 0]);
+        let mut container = vec![];
+        tree.traverse(Order::PostOrder, |value, _| container.push(*value));
+        assert_eq!(container, vec![0, 16, 8, 22, 24, 20]);
+        let mut depths = vec![];
+        tree.traverse(Order::LevelOrder, |_, depth| depths.push(depth));
+        assert_eq!(depths, vec![0, 1, 1, 2, 2, 2]);
+        assert_eq!(
+            tree.level_order().collect::<Vec<_>>(),
+            vec![20, 8, 24, 0, 16, 22]
+        );
     }
 
     #[test]
@@ -776,7 +1001,7 @@ mod test {
         rb_tree.insert(8);
         rb_tree.insert(17);
 
-        let result = FastRBTree::is_valid_red_black_tree(rb_tree.root);
+        let result = FastRBTree::is_valid_red_black_tree(rb_tree.root.clone());
         assert_eq!(result, true);
     }
 
@@ -789,11 +1014,81 @@ mod test {
             tree.insert(*v);
         });
 
-        // tree.delete(16);
+        tree.delete(&16);
         let mut container = vec![];
-        tree.pre_order_traversal_for_test(&mut container);
-        let result = FastRBTree::is_valid_red_black_tree(tree.root);
-        assert_eq!(result, true);
-        //  assert_eq!(container, vec![8, 0, 20, 24, 22]);
+        tree.in_order_traversal_for_test(&mut container);
+        check_tree(&tree);
+        assert_eq!(container, vec![0, 8, 20, 22, 24]);
+
+        // deleting a value which does not exist changes nothing
+        tree.delete(&16);
+        let mut container = vec![];
+        tree.in_order_traversal_for_test(&mut container);
+        assert_eq!(container, vec![0, 8, 20, 22, 24]);
+    }
+
+    // check the sizes of every node, return the size of the subtree
+    fn check_size<T: Ord>(node: &OptionFastRBTreeNode<T>) -> usize {
+        let node = match node {
+            Some(node) => node.borrow(),
+            None => return 0,
+        };
+        let size = check_size(&node.left) + check_size(&node.right) + 1;
+        assert_eq!(node.size, size);
+        size
+    }
+
+    fn check_tree<T: Ord>(tree: &FastRBTree<T>) {
+        assert!(FastRBTree::is_valid_red_black_tree(tree.root.clone()));
+        check_size(&tree.root);
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        fixtures::check_insert_delete(check_tree::<i32>);
+    }
+
+    #[test]
+    fn test_delete_min_max() {
+        let mut tree = FastRBTree::new();
+        for i in 0..100 {
+            tree.insert(i * 37 % 100);
+        }
+        for i in 0..50 {
+            assert_eq!(tree.delete_min(), Some(i));
+            check_tree(&tree);
+            assert_eq!(tree.delete_max(), Some(99 - i));
+            check_tree(&tree);
+            assert_eq!(tree.len(), 98 - 2 * i);
+        }
+        assert_eq!(tree.delete_min(), None);
+        assert_eq!(tree.delete_max(), None);
+    }
+
+    #[test]
+    fn test_delete_all() {
+        let mut tree = FastRBTree::new();
+        for i in 0..100 {
+            tree.insert(i * 37 % 100);
+        }
+        for i in 0..100 {
+            let value = i * 53 % 100;
+            tree.delete(&value);
+            assert!(!tree.contains(&value));
+            check_tree(&tree);
+        }
+        assert!(tree.is_tree_empty());
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for len in 0..70usize {
+            check_tree(&FastRBTree::from_sorted_iter(0..len));
+        }
+    }
+
+    #[test]
+    fn test_comparator() {
+        fixtures::check_comparator(check_tree::<i32>);
     }
 }
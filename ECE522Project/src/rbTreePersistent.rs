@@ -0,0 +1,368 @@
+//! Persistent red-black tree
+//!
+//! You can generate a left-leaning red-black tree whose `insert` and `delete` return a
+//! new version, while the older versions stay valid and share their unchanged subtrees
+//! with it.
+
+use std::fmt;
+use std::rc::Rc;
+
+pub use crate::commonTrait::CommonTreeTrait;
+use crate::commonTrait::Comparator;
+use crate::dot::{self, DotStyle, ToDot};
+use crate::persistentTree::{OptionPersistentNode, PersistentNode};
+use crate::pretty::{self, Pretty};
+pub use crate::rbTreeFast::NodeColor;
+
+type RBNode<T> = Rc<PersistentNode<T, NodeColor>>;
+type OptionRBNode<T> = OptionPersistentNode<T, NodeColor>;
+
+/// Structure of PersistentRBTree, one version of the tree, it balances the same way as
+/// [FastRBTree](../rbTreeFast/struct.FastRBTree.html)
+#[derive(Debug)]
+pub struct PersistentRBTree<T: Ord> {
+    root: OptionRBNode<T>,
+}
+
+// cloning a version only shares its root
+impl<T: Ord> Clone for PersistentRBTree<T> {
+    fn clone(&self) -> Self {
+        PersistentRBTree {
+            root: self.root.clone(),
+        }
+    }
+}
+
+// extend from common tree trait
+impl<T: Ord> CommonTreeTrait<T> for PersistentRBTree<T> {
+    type Handle<'a>
+        = &'a PersistentNode<T, NodeColor>
+    where
+        Self: 'a;
+
+    fn get_root(&self) -> Option<&PersistentNode<T, NodeColor>> {
+        self.root.as_deref()
+    }
+
+    // the values are ordered by `Ord`
+    fn get_comparator(&self) -> Comparator<T> {
+        Comparator::default()
+    }
+}
+
+// draw PersistentRBTree with Graphviz
+impl<T: Ord + fmt::Debug> ToDot for PersistentRBTree<T> {
+    fn write_dot(&self, nil_leaves: bool) -> String {
+        let nil_leaf = nil_leaves.then(|| DotStyle::filled(NodeColor::Black.fill()));
+        dot::to_dot(self.root.as_deref(), nil_leaf, |node| {
+            DotStyle::filled(node.meta.fill())
+        })
+    }
+}
+
+// draw PersistentRBTree as text
+impl<T: Ord + fmt::Debug> Pretty for PersistentRBTree<T> {
+    fn pretty(&self) -> String {
+        pretty::pretty(self.root.as_deref(), |node| {
+            Some(node.meta.to_string().to_owned())
+        })
+    }
+}
+
+impl<T: Ord> Default for PersistentRBTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implementations of PersistentRBTree
+impl<T: Ord> PersistentRBTree<T> {
+    /// Create a new empty red-black Tree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::rbTreePersistent::PersistentRBTree;
+    /// let tree: PersistentRBTree<u32> = PersistentRBTree::new();
+    /// ```
+    pub fn new() -> Self {
+        PersistentRBTree { root: None }
+    }
+}
+
+impl<T: Ord + Clone> PersistentRBTree<T> {
+    /// Return a new version with the value inserted, this version is not changed
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let empty = PersistentRBTree::new();
+    /// let tree = empty.insert(1);
+    /// assert!(tree.contains(&1));
+    /// assert!(empty.is_empty());
+    /// ```
+    pub fn insert(&self, insert_value: T) -> Self {
+        if self.contains(&insert_value) {
+            return self.clone();
+        }
+        let mut root = Self::node_insert(self.root.clone(), insert_value);
+        Rc::make_mut(&mut root).meta = NodeColor::Black;
+        PersistentRBTree { root: Some(root) }
+    }
+
+    /// Return a new version with the value deleted, this version is not changed
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::prelude::*;
+    /// let tree = PersistentRBTree::new().insert(1);
+    /// assert!(tree.delete(&1).is_empty());
+    /// assert!(tree.contains(&1));
+    /// ```
+    pub fn delete(&self, delete_value: &T) -> Self {
+        // the top-down delete only works on values that exist
+        if !self.contains(delete_value) {
+            return self.clone();
+        }
+        let mut root = self.root.clone().unwrap();
+        // if both children of root are black, set root to red
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            Rc::make_mut(&mut root).meta = NodeColor::Red;
+        }
+        let mut root = Self::node_delete(root, delete_value);
+        if let Some(root) = root.as_mut() {
+            Rc::make_mut(root).meta = NodeColor::Black;
+        }
+        PersistentRBTree { root }
+    }
+
+    /// Insert data which is not in the subtree, performs any rotations necessary to
+    /// maintain balance, and then returns the new root to this subtree
+    fn node_insert(node: OptionRBNode<T>, insert_value: T) -> RBNode<T> {
+        let mut node = match node {
+            Some(node) => node,
+            None => return PersistentNode::new(insert_value, NodeColor::Red),
+        };
+        // copy the node if an older version still refers to it
+        let current = Rc::make_mut(&mut node);
+        if insert_value < current.value {
+            current.left = Some(Self::node_insert(current.left.take(), insert_value));
+        } else {
+            current.right = Some(Self::node_insert(current.right.take(), insert_value));
+        }
+        Self::balance(node)
+    }
+
+    /// Delete data from the subtree, performs any rotations necessary to maintain balance,
+    /// and then returns the new root to this subtree
+    ///
+    /// The value must exist in the subtree: on the way down every node we step into
+    /// is made part of a 3-node or 4-node, so that the removed node is never a 2-node.
+    fn node_delete(node: RBNode<T>, delete_value: &T) -> OptionRBNode<T> {
+        let mut node = node;
+        if *delete_value < node.value {
+            // the left child is a 2-node, borrow a red link from the right
+            if !Self::is_red(&node.left) && !Self::is_red(&node.left.as_ref().unwrap().left) {
+                node = Self::move_red_left(node);
+            }
+            let current = Rc::make_mut(&mut node);
+            current.left = Self::node_delete(current.left.take().unwrap(), delete_value);
+        } else {
+            // lean the red link to the right so it can be carried down
+            if Self::is_red(&node.left) {
+                node = Self::right_rotate(node);
+            }
+            // found at the bottom of the tree, just remove the node
+            if *delete_value == node.value && node.right.is_none() {
+                return None;
+            }
+            // the right child is a 2-node, borrow a red link from the left
+            if !Self::is_red(&node.right) && !Self::is_red(&node.right.as_ref().unwrap().left) {
+                node = Self::move_red_right(node);
+            }
+            let current = Rc::make_mut(&mut node);
+            if *delete_value == current.value {
+                // the min node of right takes the place of the node
+                let (right, mut min_node) = Self::delete_min(current.right.take().unwrap());
+                let min = Rc::make_mut(&mut min_node);
+                min.left = current.left.take();
+                min.right = right;
+                min.meta = current.meta.clone();
+                node = min_node;
+            } else {
+                current.right = Self::node_delete(current.right.take().unwrap(), delete_value);
+            }
+        }
+        Some(Self::balance(node))
+    }
+
+    /// Detach the min node of the subtree, and then returns the new root to this subtree
+    /// and the detached node
+    fn delete_min(node: RBNode<T>) -> (OptionRBNode<T>, RBNode<T>) {
+        let mut node = node;
+        // the min node has no left child, and then it has no right child either
+        if node.left.is_none() {
+            return (None, node);
+        }
+        if !Self::is_red(&node.left) && !Self::is_red(&node.left.as_ref().unwrap().left) {
+            node = Self::move_red_left(node);
+        }
+        let current = Rc::make_mut(&mut node);
+        let (left, min_node) = Self::delete_min(current.left.take().unwrap());
+        current.left = left;
+        (Some(Self::balance(node)), min_node)
+    }
+
+    /// Assuming that node is red and both node.left and node.left.left are black,
+    /// make node.left or one of its children red.
+    fn move_red_left(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        Self::reverse_colors(&mut node);
+        if Self::is_red(&node.right.as_ref().unwrap().left) {
+            let current = Rc::make_mut(&mut node);
+            current.right = Some(Self::right_rotate(current.right.take().unwrap()));
+            node = Self::left_rotate(node);
+            Self::reverse_colors(&mut node);
+        }
+        node
+    }
+
+    /// Assuming that node is red and both node.right and node.right.left are black,
+    /// make node.right or one of its children red.
+    fn move_red_right(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        Self::reverse_colors(&mut node);
+        if Self::is_red(&node.left.as_ref().unwrap().left) {
+            node = Self::right_rotate(node);
+            Self::reverse_colors(&mut node);
+        }
+        node
+    }
+
+    /// Restore the left-leaning red-black invariants on the way up
+    fn balance(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        Rc::make_mut(&mut node).update_size();
+        // if right is red and left is black, then left rotate
+        if Self::is_red(&node.right) && !Self::is_red(&node.left) {
+            node = Self::left_rotate(node);
+        }
+        // if left and left's left are both red, then right rotate
+        if Self::is_red(&node.left) && Self::is_red(&node.left.as_ref().unwrap().left) {
+            node = Self::right_rotate(node);
+        }
+        // if left and right are both red, then change color
+        if Self::is_red(&node.left) && Self::is_red(&node.right) {
+            Self::reverse_colors(&mut node);
+        }
+        node
+    }
+
+    /// Rotate the subtree rooted at this node to the left and
+    /// return the new root to this subtree.
+    fn left_rotate(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        let current = Rc::make_mut(&mut node);
+        let mut right = current.right.take().unwrap();
+        let top = Rc::make_mut(&mut right);
+        current.right = top.left.take();
+        top.meta = std::mem::replace(&mut current.meta, NodeColor::Red);
+        current.update_size();
+        top.left = Some(node);
+        top.update_size();
+        right
+    }
+
+    /// Rotate the subtree rooted at this node to the right and
+    /// returns the new root to this subtree.
+    fn right_rotate(node: RBNode<T>) -> RBNode<T> {
+        let mut node = node;
+        let current = Rc::make_mut(&mut node);
+        let mut left = current.left.take().unwrap();
+        let top = Rc::make_mut(&mut left);
+        current.left = top.right.take();
+        top.meta = std::mem::replace(&mut current.meta, NodeColor::Red);
+        current.update_size();
+        top.right = Some(node);
+        top.update_size();
+        left
+    }
+
+    // make None to be real leaves with black color
+    fn is_red(node: &OptionRBNode<T>) -> bool {
+        node.as_ref()
+            .is_some_and(|node| node.meta == NodeColor::Red)
+    }
+
+    // reverse the color of node and its two children
+    fn reverse_colors(node: &mut RBNode<T>) {
+        fn reverse(color: &mut NodeColor) {
+            *color = match color {
+                NodeColor::Red => NodeColor::Black,
+                NodeColor::Black => NodeColor::Red,
+            };
+        }
+        let current = Rc::make_mut(node);
+        reverse(&mut current.meta);
+        for child in [&mut current.left, &mut current.right].iter_mut() {
+            if let Some(child) = child.as_mut() {
+                reverse(&mut Rc::make_mut(child).meta);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures;
+    use crate::persistentTree::size;
+
+    // check the red-black properties and the stored sizes, return the black height
+    fn check_node<T: Ord + Clone>(node: &OptionRBNode<T>) -> usize {
+        let current = match node {
+            Some(current) => current,
+            None => return 1,
+        };
+        // red links lean left and never follow each other
+        assert!(!PersistentRBTree::is_red(&current.right));
+        assert!(!(PersistentRBTree::is_red(node) && PersistentRBTree::is_red(&current.left)));
+        let black_height = check_node(&current.left);
+        assert_eq!(black_height, check_node(&current.right));
+        assert_eq!(current.size, size(&current.left) + size(&current.right) + 1);
+        black_height + if PersistentRBTree::is_red(node) { 0 } else { 1 }
+    }
+
+    #[test]
+    fn test_snapshots() {
+        fixtures::check_snapshots(
+            |tree: &PersistentRBTree<i32>, value| tree.insert(value),
+            |tree, value| tree.delete(value),
+            |tree| {
+                check_node(&tree.root);
+            },
+        );
+    }
+
+    #[test]
+    fn test_structural_sharing() {
+        let mut tree = PersistentRBTree::new();
+        for value in 0..1000 {
+            tree = tree.insert(value);
+        }
+        let newer = tree.insert(1000);
+        // the new version shares the whole left subtree of the root
+        let old_root = tree.root.as_ref().unwrap();
+        let new_root = newer.root.as_ref().unwrap();
+        assert!(Rc::ptr_eq(
+            old_root.left.as_ref().unwrap(),
+            new_root.left.as_ref().unwrap()
+        ));
+        assert!(!tree.contains(&1000));
+        assert!(newer.contains(&1000));
+        assert_eq!(tree.max(), Some(999));
+        assert_eq!(newer.max(), Some(1000));
+    }
+}
@@ -0,0 +1,180 @@
+//! Tree shared between threads
+//!
+//! The box-based trees ([BoxAVLTree](../avlTreeBox/struct.BoxAVLTree.html),
+//! [BoxRBTree](../rbTreeBox/struct.BoxRBTree.html)) and the arena-backed trees
+//! ([ArenaAVLTree](../avlTreeArena/struct.ArenaAVLTree.html),
+//! [ArenaRBTree](../rbTreeArena/struct.ArenaRBTree.html)) own their nodes without `Rc`,
+//! so they are `Send + Sync` whenever `T` is and can be moved to another thread.
+//! [SharedTree](struct.SharedTree.html) lets several threads use one of them at the same
+//! time: any number of readers, or a single writer.
+
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// message of the panic passed on from a writer that panicked while holding the lock
+const POISONED: &str = "a writer panicked while holding the shared tree";
+
+/// Handle of a tree shared between threads, cloning it gives another handle of the same tree
+///
+/// # Example
+///
+/// ```
+/// use tree_collections::prelude::*;
+/// use std::thread;
+/// let shared = SharedTree::new(BoxAVLTree::new());
+/// let writer = shared.clone();
+/// thread::spawn(move || {
+///     for value in 0..10 {
+///         writer.write().insert(value);
+///     }
+/// })
+/// .join()
+/// .unwrap();
+/// assert_eq!(10, shared.read().len());
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedTree<Tree> {
+    tree: Arc<RwLock<Tree>>,
+}
+
+// a derived Clone would require Tree: Clone
+impl<Tree> Clone for SharedTree<Tree> {
+    fn clone(&self) -> Self {
+        SharedTree {
+            tree: Arc::clone(&self.tree),
+        }
+    }
+}
+
+/// Implementations of SharedTree
+impl<Tree> SharedTree<Tree> {
+    /// Share the tree
+    pub fn new(tree: Tree) -> Self {
+        SharedTree {
+            tree: Arc::new(RwLock::new(tree)),
+        }
+    }
+
+    /// Lock the tree for reading, blocking while a writer holds it
+    ///
+    /// Panics if a writer panicked while holding the lock, since an insert or delete
+    /// stopped halfway may have left the tree unbalanced or without some of its nodes.
+    pub fn read(&self) -> RwLockReadGuard<'_, Tree> {
+        self.tree.read().expect(POISONED)
+    }
+
+    /// Lock the tree for writing, blocking while any reader or writer holds it
+    ///
+    /// Panics if a writer panicked while holding the lock, the same as `read`.
+    pub fn write(&self) -> RwLockWriteGuard<'_, Tree> {
+        self.tree.write().expect(POISONED)
+    }
+
+    /// Take the tree back, return the handle itself if other handles still exist
+    ///
+    /// Panics if a writer panicked while holding the lock, the same as `read`.
+    pub fn try_unwrap(self) -> Result<Tree, Self> {
+        match Arc::try_unwrap(self.tree) {
+            Ok(lock) => Ok(lock.into_inner().expect(POISONED)),
+            Err(tree) => Err(SharedTree { tree }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::collections::BTreeSet;
+    use std::fmt;
+    use std::thread;
+
+    fn assert_send_sync<S: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<BoxAVLTree<String>>();
+        assert_send_sync::<BoxRBTree<String>>();
+        assert_send_sync::<ArenaAVLTree<String>>();
+        assert_send_sync::<ArenaRBTree<String>>();
+        assert_send_sync::<SharedTree<BoxAVLTree<String>>>();
+    }
+
+    // writers insert every value of 0..VALUES into a shared empty tree and then delete
+    // the multiples of 3, while readers check the tree
+    fn stress<Tree>()
+    where
+        Tree: OrderedSet<u32> + IntoIterator<Item = u32> + fmt::Debug + Send + Sync + 'static,
+    {
+        const WRITERS: u32 = 4;
+        const READERS: u32 = 4;
+        const VALUES: u32 = 2000;
+        let shared = SharedTree::new(Tree::new());
+        let mut handles = vec![];
+        // every writer owns the values equal to its id modulo WRITERS
+        for id in 0..WRITERS {
+            let shared = shared.clone();
+            handles.push(thread::spawn(move || {
+                let values = (0..VALUES).filter(|value| value % WRITERS == id);
+                for value in values.clone() {
+                    assert!(shared.write().insert(value));
+                }
+                for value in values.filter(|value| value % 3 == 0) {
+                    assert!(shared.write().delete(&value));
+                }
+            }));
+        }
+        // readers always see a sorted tree whose stored size matches its contents
+        for _ in 0..READERS {
+            let shared = shared.clone();
+            handles.push(thread::spawn(move || {
+                for _ in 0..200 {
+                    let tree = shared.read();
+                    let values = tree.iter().collect::<Vec<_>>();
+                    assert_eq!(values.len(), tree.len());
+                    assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let expected = (0..VALUES)
+            .filter(|value| value % 3 != 0)
+            .collect::<BTreeSet<_>>();
+        let tree = shared.try_unwrap().unwrap();
+        assert!(tree.into_iter().eq(expected.into_iter()));
+    }
+
+    #[test]
+    fn test_stress() {
+        stress::<BoxAVLTree<u32>>();
+        stress::<BoxRBTree<u32>>();
+        stress::<ArenaAVLTree<u32>>();
+        stress::<ArenaRBTree<u32>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "a writer panicked while holding the shared tree")]
+    fn test_poisoned() {
+        let shared = SharedTree::new(BoxAVLTree::new());
+        let writer = shared.clone();
+        let result = thread::spawn(move || {
+            let mut tree = writer.write();
+            tree.insert(1);
+            panic!("the writer failed");
+        })
+        .join();
+        assert!(result.is_err());
+        let _tree = shared.read();
+    }
+
+    #[test]
+    fn test_try_unwrap() {
+        let shared = SharedTree::new(ArenaAVLTree::new());
+        let other = shared.clone();
+        other.write().insert(1);
+        let shared = shared.try_unwrap().unwrap_err();
+        drop(other);
+        assert!(shared.try_unwrap().unwrap().contains(&1));
+    }
+}
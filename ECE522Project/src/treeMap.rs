@@ -0,0 +1,307 @@
+//! Key-value maps
+//!
+//! You can generate a map backed by any of the trees, and insert, look up or remove
+//! values by their keys.
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::avlTree::AVLTree;
+use crate::bsTree::BSTree;
+use crate::commonTrait::{CommonTreeNodeTrait, OrderedSet};
+use crate::rbTree::RBTree;
+use crate::rbTreeFast::FastRBTree;
+
+/// Map backed by an [AVLTree](../avlTree/struct.AVLTree.html)
+pub type AVLMap<K, V> = TreeMap<K, V, AVLTree<MapEntry<K, V>>>;
+
+/// Map backed by a [RBTree](../rbTree/struct.RBTree.html)
+pub type RBMap<K, V> = TreeMap<K, V, RBTree<MapEntry<K, V>>>;
+
+/// Map backed by a [FastRBTree](../rbTreeFast/struct.FastRBTree.html)
+pub type FastRBMap<K, V> = TreeMap<K, V, FastRBTree<MapEntry<K, V>>>;
+
+/// Map backed by a [BSTree](../bsTree/struct.BSTree.html)
+pub type BSMap<K, V> = TreeMap<K, V, BSTree<MapEntry<K, V>>>;
+
+/// A key-value pair stored in the tree of a [TreeMap](struct.TreeMap.html).
+/// Entries are ordered by their keys only.
+#[derive(Clone, Debug)]
+pub struct MapEntry<K: Ord, V> {
+    pub key: K,
+    pub value: V,
+}
+
+impl<K: Ord, V> PartialEq for MapEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Ord, V> Eq for MapEntry<K, V> {}
+
+impl<K: Ord, V> PartialOrd for MapEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for MapEntry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Provide the operations a tree needs to back a [TreeMap](struct.TreeMap.html)
+pub trait MapBackend<T: Ord>: OrderedSet<T> {
+    /// Node of the tree, which is shared through `Rc<RefCell<...>>`
+    type Node: CommonTreeNodeTrait<T>;
+
+    /// Get the root node
+    fn get_root_node(&self) -> Option<Rc<RefCell<Self::Node>>>;
+
+    /// Immutably borrow the value of the node
+    fn borrow_value(node: &Rc<RefCell<Self::Node>>) -> Ref<'_, T>;
+
+    /// Mutably borrow the value of the node, the ordering of the value must not be changed
+    fn borrow_value_mut(node: &Rc<RefCell<Self::Node>>) -> RefMut<'_, T>;
+
+    /// Insert a value, replace and return the equal value if there is one
+    fn replace(&mut self, value: T) -> Option<T>;
+
+    /// Remove the node whose value `cmp` returns `Ordering::Equal` for, and return it
+    /// detached from the tree. `cmp` tells how the wanted value compares with the given one.
+    fn remove_node<F: Fn(&T) -> Ordering>(&mut self, cmp: &F) -> Option<Rc<RefCell<Self::Node>>>;
+
+    /// Find the node whose value `cmp` returns `Ordering::Equal` for
+    fn find_node<F: Fn(&T) -> Ordering>(&self, cmp: &F) -> Option<Rc<RefCell<Self::Node>>> {
+        let mut current = self.get_root_node();
+        while let Some(node) = current {
+            let ordering = cmp(node.borrow().get_value());
+            current = match ordering {
+                Ordering::Less => node.borrow().get_left(),
+                Ordering::Greater => node.borrow().get_right(),
+                Ordering::Equal => return Some(node),
+            };
+        }
+        None
+    }
+}
+
+/// Structure of TreeMap, which stores [MapEntry](struct.MapEntry.html)s in the tree `B`
+pub struct TreeMap<K: Ord, V, B: MapBackend<MapEntry<K, V>>> {
+    tree: B,
+    marker: PhantomData<MapEntry<K, V>>,
+}
+
+/// Reference to a value in a [TreeMap](struct.TreeMap.html), returned by
+/// [TreeMap.get](struct.TreeMap.html#method.get)
+pub struct ValueRef<'a, K: Ord, V, B: MapBackend<MapEntry<K, V>>> {
+    node: Rc<RefCell<B::Node>>,
+    marker: PhantomData<&'a TreeMap<K, V, B>>,
+}
+
+/// Mutable reference to a value in a [TreeMap](struct.TreeMap.html), returned by
+/// [TreeMap.get_mut](struct.TreeMap.html#method.get_mut)
+pub struct ValueMut<'a, K: Ord, V, B: MapBackend<MapEntry<K, V>>> {
+    node: Rc<RefCell<B::Node>>,
+    marker: PhantomData<&'a mut TreeMap<K, V, B>>,
+}
+
+impl<'a, K: Ord, V, B: MapBackend<MapEntry<K, V>>> ValueRef<'a, K, V, B> {
+    /// Immutably borrow the value
+    pub fn borrow(&self) -> Ref<'_, V> {
+        Ref::map(B::borrow_value(&self.node), |entry| &entry.value)
+    }
+}
+
+impl<'a, K: Ord, V, B: MapBackend<MapEntry<K, V>>> ValueMut<'a, K, V, B> {
+    /// Immutably borrow the value
+    pub fn borrow(&self) -> Ref<'_, V> {
+        Ref::map(B::borrow_value(&self.node), |entry| &entry.value)
+    }
+
+    /// Mutably borrow the value
+    pub fn borrow_mut(&self) -> RefMut<'_, V> {
+        RefMut::map(B::borrow_value_mut(&self.node), |entry| &mut entry.value)
+    }
+}
+
+impl<K: Ord, V, B: MapBackend<MapEntry<K, V>>> Default for TreeMap<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug, B: MapBackend<MapEntry<K, V>> + fmt::Debug> fmt::Debug
+    for TreeMap<K, V, B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeMap").field("tree", &self.tree).finish()
+    }
+}
+
+/// Implementations of TreeMap
+impl<K: Ord, V, B: MapBackend<MapEntry<K, V>>> TreeMap<K, V, B> {
+    /// Create a new map
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMap::AVLMap;
+    /// let mut map: AVLMap<u32, String> = AVLMap::new();
+    /// ```
+    pub fn new() -> Self {
+        TreeMap {
+            tree: B::default(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Insert a key-value pair into the map, return the old value if the key was present
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMap::RBMap;
+    /// let mut map = RBMap::new();
+    /// assert_eq!(None, map.insert(1, "a"));
+    /// assert_eq!(Some("a"), map.insert(1, "b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.tree
+            .replace(MapEntry { key, value })
+            .map(|entry| entry.value)
+    }
+
+    /// Get a reference to the value of the key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMap::FastRBMap;
+    /// let mut map = FastRBMap::new();
+    /// map.insert(1, String::from("a"));
+    /// assert_eq!("a", *map.get(&1).unwrap().borrow());
+    /// assert!(map.get(&2).is_none());
+    /// ```
+    pub fn get(&self, key: &K) -> Option<ValueRef<'_, K, V, B>> {
+        self.tree
+            .find_node(&|entry: &MapEntry<K, V>| key.cmp(&entry.key))
+            .map(|node| ValueRef {
+                node,
+                marker: PhantomData,
+            })
+    }
+
+    /// Get a mutable reference to the value of the key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMap::BSMap;
+    /// let mut map = BSMap::new();
+    /// map.insert(1, vec![1]);
+    /// map.get_mut(&1).unwrap().borrow_mut().push(2);
+    /// assert_eq!(vec![1, 2], *map.get(&1).unwrap().borrow());
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<ValueMut<'_, K, V, B>> {
+        self.tree
+            .find_node(&|entry: &MapEntry<K, V>| key.cmp(&entry.key))
+            .map(|node| ValueMut {
+                node,
+                marker: PhantomData,
+            })
+    }
+
+    /// Remove the key from the map, return its value if the key was present
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMap::AVLMap;
+    /// let mut map = AVLMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(Some("a"), map.remove(&1));
+    /// assert_eq!(None, map.remove(&1));
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let node = self
+            .tree
+            .remove_node(&|entry: &MapEntry<K, V>| key.cmp(&entry.key))?;
+        // the node is detached and nobody else can hold it since the tree is private
+        let node = match Rc::try_unwrap(node) {
+            Ok(node) => node.into_inner(),
+            Err(_) => panic!("removed node is still shared"),
+        };
+        Some(node.into_value().value)
+    }
+
+    /// Determine whether the map contains the key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMap::RBMap;
+    /// let mut map = RBMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn check_map<B: MapBackend<MapEntry<u32, String>>>() {
+        let mut map: TreeMap<u32, String, B> = TreeMap::new();
+        let mut expected = BTreeMap::new();
+        for i in 0..100 {
+            let key = i * 37 % 100;
+            assert_eq!(map.insert(key, key.to_string()), None);
+            expected.insert(key, key.to_string());
+        }
+        // replace the values of the even keys
+        for key in (0..100).step_by(2) {
+            let old = map.insert(key, format!("{}!", key));
+            assert_eq!(old, expected.insert(key, format!("{}!", key)));
+        }
+        for i in 0..50 {
+            let key = i * 53 % 100;
+            assert_eq!(map.remove(&key), expected.remove(&key));
+            assert_eq!(map.remove(&key), None);
+        }
+        for key in 0..100 {
+            assert_eq!(map.contains_key(&key), expected.contains_key(&key));
+            let value = map.get(&key).map(|v| v.borrow().clone());
+            assert_eq!(value.as_ref(), expected.get(&key));
+        }
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        check_map::<AVLTree<MapEntry<u32, String>>>();
+        check_map::<RBTree<MapEntry<u32, String>>>();
+        check_map::<FastRBTree<MapEntry<u32, String>>>();
+        check_map::<BSTree<MapEntry<u32, String>>>();
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut map = AVLMap::new();
+        map.insert("b", vec![2]);
+        map.insert("a", vec![1]);
+        map.get_mut(&"a").unwrap().borrow_mut().push(10);
+        assert_eq!(*map.get(&"a").unwrap().borrow(), vec![1, 10]);
+        assert_eq!(*map.get(&"b").unwrap().borrow(), vec![2]);
+        assert!(map.get_mut(&"c").is_none());
+    }
+}
@@ -0,0 +1,334 @@
+//! Multisets
+//!
+//! You can generate a multiset backed by any of the trees, which stores equal values
+//! once together with how many times they were inserted.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::avlTree::AVLTree;
+use crate::bsTree::BSTree;
+use crate::commonTrait::Iter;
+use crate::rbTree::RBTree;
+use crate::rbTreeFast::FastRBTree;
+use crate::treeMap::{MapBackend, MapEntry};
+
+/// Multiset backed by an [AVLTree](../avlTree/struct.AVLTree.html)
+pub type AVLMultiSet<T> = TreeMultiSet<T, AVLTree<MapEntry<T, usize>>>;
+
+/// Multiset backed by a [RBTree](../rbTree/struct.RBTree.html)
+pub type RBMultiSet<T> = TreeMultiSet<T, RBTree<MapEntry<T, usize>>>;
+
+/// Multiset backed by a [FastRBTree](../rbTreeFast/struct.FastRBTree.html)
+pub type FastRBMultiSet<T> = TreeMultiSet<T, FastRBTree<MapEntry<T, usize>>>;
+
+/// Multiset backed by a [BSTree](../bsTree/struct.BSTree.html)
+pub type BSMultiSet<T> = TreeMultiSet<T, BSTree<MapEntry<T, usize>>>;
+
+/// Structure of TreeMultiSet, which stores every distinct value in the tree `B` as a
+/// [MapEntry](../treeMap/struct.MapEntry.html) whose value is the count of the key
+pub struct TreeMultiSet<T: Ord, B: MapBackend<MapEntry<T, usize>>> {
+    tree: B,
+    /// Number of values counted with their multiplicity
+    len: usize,
+    marker: PhantomData<MapEntry<T, usize>>,
+}
+
+impl<T: Ord, B: MapBackend<MapEntry<T, usize>>> Default for TreeMultiSet<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + fmt::Debug, B: MapBackend<MapEntry<T, usize>> + fmt::Debug> fmt::Debug
+    for TreeMultiSet<T, B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeMultiSet")
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
+
+// iterate over the multiset in order, repeating every value by its count
+impl<'a, T: Ord + Clone, B: MapBackend<MapEntry<T, usize>>> IntoIterator
+    for &'a TreeMultiSet<T, B>
+{
+    type Item = T;
+    type IntoIter = MultiSetIter<'a, T, B>;
+
+    fn into_iter(self) -> MultiSetIter<'a, T, B> {
+        self.iter()
+    }
+}
+
+/// Implementations of TreeMultiSet
+impl<T: Ord, B: MapBackend<MapEntry<T, usize>>> TreeMultiSet<T, B> {
+    /// Create a new multiset
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMultiSet::AVLMultiSet;
+    /// let mut set: AVLMultiSet<u32> = AVLMultiSet::new();
+    /// ```
+    pub fn new() -> Self {
+        TreeMultiSet {
+            tree: B::default(),
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Insert one occurrence of the value, return true if the value was not present before
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMultiSet::RBMultiSet;
+    /// let mut set = RBMultiSet::new();
+    /// assert!(set.insert(1));
+    /// assert!(!set.insert(1));
+    /// assert_eq!(2, set.count(&1));
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        self.len += 1;
+        match self
+            .tree
+            .find_node(&|entry: &MapEntry<T, usize>| value.cmp(&entry.key))
+        {
+            Some(node) => {
+                B::borrow_value_mut(&node).value += 1;
+                false
+            }
+            None => {
+                self.tree.replace(MapEntry {
+                    key: value,
+                    value: 1,
+                });
+                true
+            }
+        }
+    }
+
+    /// Delete one occurrence of the value, return false if the value does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMultiSet::FastRBMultiSet;
+    /// let mut set = FastRBMultiSet::new();
+    /// set.insert(1);
+    /// set.insert(1);
+    /// assert!(set.delete(&1));
+    /// assert_eq!(1, set.count(&1));
+    /// ```
+    pub fn delete(&mut self, value: &T) -> bool {
+        let cmp = |entry: &MapEntry<T, usize>| value.cmp(&entry.key);
+        let node = match self.tree.find_node(&cmp) {
+            Some(node) => node,
+            None => return false,
+        };
+        self.len -= 1;
+        let count = {
+            let mut entry = B::borrow_value_mut(&node);
+            entry.value -= 1;
+            entry.value
+        };
+        // the last occurrence takes the node out of the tree
+        if count == 0 {
+            drop(node);
+            self.tree.remove_node(&cmp);
+        }
+        true
+    }
+
+    /// Delete all occurrences of the value, return how many were deleted
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMultiSet::BSMultiSet;
+    /// let mut set = BSMultiSet::new();
+    /// set.insert(1);
+    /// set.insert(1);
+    /// assert_eq!(2, set.delete_all(&1));
+    /// assert_eq!(0, set.delete_all(&1));
+    /// ```
+    pub fn delete_all(&mut self, value: &T) -> usize {
+        let node = self
+            .tree
+            .remove_node(&|entry: &MapEntry<T, usize>| value.cmp(&entry.key));
+        let count = node.map_or(0, |node| B::borrow_value(&node).value);
+        self.len -= count;
+        count
+    }
+
+    /// Get how many times the value occurs, 0 if it does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMultiSet::AVLMultiSet;
+    /// let mut set = AVLMultiSet::new();
+    /// set.insert("a");
+    /// assert_eq!(1, set.count(&"a"));
+    /// assert_eq!(0, set.count(&"b"));
+    /// ```
+    pub fn count(&self, value: &T) -> usize {
+        self.tree
+            .find_node(&|entry: &MapEntry<T, usize>| value.cmp(&entry.key))
+            .map_or(0, |node| B::borrow_value(&node).value)
+    }
+
+    /// Determine whether the multiset contains the value
+    pub fn contains(&self, value: &T) -> bool {
+        self.count(value) > 0
+    }
+
+    /// Gets the number of values in the multiset, counting every occurrence
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine whether the multiset has no values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the number of distinct values in the multiset
+    pub fn distinct_len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Gets an iterator over the values of the multiset in ascending order,
+    /// a value is repeated as many times as it occurs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tree_collections::treeMultiSet::RBMultiSet;
+    /// let mut set = RBMultiSet::new();
+    /// for value in vec![2, 1, 2, 3, 2] {
+    ///     set.insert(value);
+    /// }
+    /// assert_eq!(vec![1, 2, 2, 2, 3], set.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn iter(&self) -> MultiSetIter<'_, T, B>
+    where
+        T: Clone,
+    {
+        MultiSetIter {
+            entries: self.tree.iter(),
+            current: None,
+            remaining: self.len,
+        }
+    }
+}
+
+/// Iterator over the values of a [TreeMultiSet](struct.TreeMultiSet.html) in order,
+/// created by [TreeMultiSet.iter](struct.TreeMultiSet.html#method.iter)
+pub struct MultiSetIter<'a, T: Ord, B: MapBackend<MapEntry<T, usize>> + 'a> {
+    entries: Iter<'a, MapEntry<T, usize>, B::Handle<'a>>,
+    // the entry being repeated and how many times it is left to be yielded
+    current: Option<MapEntry<T, usize>>,
+    remaining: usize,
+}
+
+impl<'a, T: Ord + Clone, B: MapBackend<MapEntry<T, usize>>> Iterator for MultiSetIter<'a, T, B> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.current.is_none() {
+            self.current = self.entries.next();
+        }
+        let entry = self.current.as_mut()?;
+        self.remaining -= 1;
+        entry.value -= 1;
+        if entry.value == 0 {
+            // the last occurrence moves the value out
+            return self.current.take().map(|entry| entry.key);
+        }
+        Some(entry.key.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: Ord + Clone, B: MapBackend<MapEntry<T, usize>>> ExactSizeIterator
+    for MultiSetIter<'a, T, B>
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn check_multiset<B: MapBackend<MapEntry<u32, usize>>>() {
+        let mut set: TreeMultiSet<u32, B> = TreeMultiSet::new();
+        let mut expected: BTreeMap<u32, usize> = BTreeMap::new();
+        // every value in 0..50 is inserted i % 4 times
+        for i in 0..200 {
+            let value = i * 37 % 200 / 4;
+            if value % 4 < i % 4 {
+                continue;
+            }
+            let is_new = !expected.contains_key(&value);
+            *expected.entry(value).or_insert(0) += 1;
+            assert_eq!(set.insert(value), is_new);
+        }
+        for value in (0..50).step_by(3) {
+            let is_present = expected.contains_key(&value);
+            assert_eq!(set.delete(&value), is_present);
+            if let Some(count) = expected.get_mut(&value) {
+                *count -= 1;
+                if *count == 0 {
+                    expected.remove(&value);
+                }
+            }
+        }
+        for value in (0..50).step_by(7) {
+            assert_eq!(set.delete_all(&value), expected.remove(&value).unwrap_or(0));
+        }
+        for value in 0..60 {
+            assert_eq!(
+                set.count(&value),
+                expected.get(&value).cloned().unwrap_or(0)
+            );
+            assert_eq!(set.contains(&value), expected.contains_key(&value));
+        }
+        let values = expected
+            .iter()
+            .flat_map(|(value, count)| std::iter::repeat_n(*value, *count))
+            .collect::<Vec<_>>();
+        assert_eq!(set.len(), values.len());
+        assert_eq!(set.distinct_len(), expected.len());
+        assert_eq!(set.iter().len(), values.len());
+        assert_eq!(set.iter().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_insert_delete_count() {
+        check_multiset::<AVLTree<MapEntry<u32, usize>>>();
+        check_multiset::<RBTree<MapEntry<u32, usize>>>();
+        check_multiset::<FastRBTree<MapEntry<u32, usize>>>();
+        check_multiset::<BSTree<MapEntry<u32, usize>>>();
+    }
+
+    #[test]
+    fn test_string_values() {
+        let mut set = AVLMultiSet::new();
+        for word in "the cat and the dog and the bird".split(' ') {
+            set.insert(word.to_string());
+        }
+        assert_eq!(set.count(&"the".to_string()), 3);
+        assert!(set.delete(&"the".to_string()));
+        assert_eq!(set.delete_all(&"and".to_string()), 2);
+        let words = set.iter().collect::<Vec<_>>();
+        assert_eq!(words, vec!["bird", "cat", "dog", "the", "the"]);
+        assert!(!set.is_empty());
+    }
+}